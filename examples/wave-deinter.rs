@@ -8,7 +8,7 @@ use std::io;
 use std::path::Path;
 
 extern crate bwavfile;
-use bwavfile::{Error,WaveReader, WaveWriter, ChannelDescriptor, ChannelMask, WaveFmt};
+use bwavfile::{Error,WaveReader, WaveWriter, ChannelDescriptor, ChannelMask, WaveFmt, CommonFormat};
 
 #[macro_use]
 extern crate clap;
@@ -57,9 +57,15 @@ fn process_file(infile: &str, delim : &str, numeric_channel_names : bool) -> Res
     let basename = infile_path.file_stem().expect("Unable to extract file basename").to_str().unwrap();
     let output_dir = infile_path.parent().expect("Unable to derive parent directory");
 
-    let ouptut_format = WaveFmt::new_pcm_mono(input_format.sample_rate, input_format.bits_per_sample);
+    let ouptut_format = match input_format.common_format() {
+        CommonFormat::IeeeFloatPCM | CommonFormat::AmbisonicBFormatIeeeFloatPCM =>
+            WaveFmt::new_float_mono(input_format.sample_rate, input_format.bits_per_sample),
+        _ => WaveFmt::new_pcm_mono(input_format.sample_rate, input_format.bits_per_sample)
+    };
     let mut input_wave_reader = input_file.audio_frame_reader()?;
 
+    let mut output_wave_writers = vec![];
+
     for (n, channel) in channel_desc.iter().enumerate() {
         let suffix = name_suffix(numeric_channel_names, delim, n + 1, channel);
         let outfile_name = output_dir.join(format!("{}{}.wav", basename, suffix))
@@ -69,15 +75,13 @@ fn process_file(infile: &str, delim : &str, numeric_channel_names : bool) -> Res
 
         let output_file = WaveWriter::create(&outfile_name, ouptut_format).expect("Failed to create new file");
         
-        let mut output_wave_writer = output_file.audio_frame_writer()?;
-        let mut buffer = input_format.create_frame_buffer(1);
+        output_wave_writers.push(output_file.audio_frame_writer()?);
+    }
 
-        while input_wave_reader.read_integer_frame(&mut buffer)? > 0 {
-            output_wave_writer.write_integer_frames(&buffer[n..=n])?;
-        }
+    input_wave_reader.deinterleave_into(&mut output_wave_writers)?;
 
+    for output_wave_writer in output_wave_writers {
         output_wave_writer.end()?;
-        input_wave_reader.locate(0)?;
     }
 
     Ok(())
@@ -0,0 +1,962 @@
+use xmltree::{Element, XMLNode, EmitterConfig};
+
+use super::errors::Error;
+
+/// ADM (Audio Definition Model) metadata.
+///
+/// Only available with the `adm` feature.
+///
+/// This is the `audioFormatExtended` content of an `axml` chunk, as defined
+/// by ITU-R BS.2076. Read from a file with `WaveReader::adm()`, and written
+/// to a file by passing `to_bytes()` to `WaveWriter::write_axml()`. Tracks
+/// in the file are related to the ADM with a `chna` chunk, read as part of
+/// `WaveReader::channels()` and written with `WaveWriter::write_chna()`.
+///
+/// Elements and attributes not described by these structures are
+/// preserved, so metadata read from a file can be modified and written back
+/// without loss.
+///
+/// ```
+/// use bwavfile::{Adm, AudioProgramme, AudioContent, AudioObject, AudioTrackUid};
+///
+/// let mut adm = Adm::default();
+///
+/// let mut programme = AudioProgramme::new("APR_1001", "Main Mix");
+/// programme.content_refs.push(String::from("ACO_1001"));
+/// adm.programmes.push(programme);
+///
+/// let mut content = AudioContent::new("ACO_1001", "Dialogue");
+/// content.object_refs.push(String::from("AO_1001"));
+/// adm.contents.push(content);
+///
+/// let mut object = AudioObject::new("AO_1001", "Dialogue");
+/// object.pack_format_refs.push(String::from("AP_00010001"));
+/// object.track_uid_refs.push(String::from("ATU_00000001"));
+/// adm.objects.push(object);
+///
+/// let mut track_uid = AudioTrackUid::new("ATU_00000001");
+/// track_uid.track_format_ref = Some(String::from("AT_00010003_01"));
+/// track_uid.pack_format_ref = Some(String::from("AP_00010001"));
+/// adm.track_uids.push(track_uid);
+///
+/// let read = Adm::parse(&adm.to_bytes()).unwrap();
+/// assert_eq!(read, adm);
+/// assert_eq!(read.programmes[0].name, "Main Mix");
+/// ```
+///
+/// ## Resources
+/// - [ITU-R BS.2076-2](https://www.itu.int/rec/R-REC-BS.2076/en) (October 2019), "Audio Definition Model"
+/// - [ITU-R BS.2088-1](https://www.itu.int/rec/R-REC-BS.2088/en) (October 2019), "Long-form file format for the international exchange of audio programme materials with metadata"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Adm {
+    /// `audioProgramme` elements
+    pub programmes: Vec<AudioProgramme>,
+
+    /// `audioContent` elements
+    pub contents: Vec<AudioContent>,
+
+    /// `audioObject` elements
+    pub objects: Vec<AudioObject>,
+
+    /// `audioPackFormat` elements
+    pub pack_formats: Vec<AudioPackFormat>,
+
+    /// `audioChannelFormat` elements
+    pub channel_formats: Vec<AudioChannelFormat>,
+
+    /// `audioStreamFormat` elements
+    pub stream_formats: Vec<AudioStreamFormat>,
+
+    /// `audioTrackFormat` elements
+    pub track_formats: Vec<AudioTrackFormat>,
+
+    /// `audioTrackUID` elements
+    pub track_uids: Vec<AudioTrackUid>,
+
+    /// The document containing the `audioFormatExtended` element, with its
+    /// modeled children removed
+    document: Element,
+
+    /// Children of `audioFormatExtended` that aren't modeled
+    other: Vec<XMLNode>
+}
+
+const DEFAULT_DOCUMENT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ebuCoreMain xmlns="urn:ebu:metadata-schema:ebuCore_2014" xmlns:dc="http://purl.org/dc/elements/1.1/" schema="EBU_CORE_20140201.xsd" xml:lang="en">
+<coreMetadata><format><audioFormatExtended version="ITU-R_BS.2076-2"></audioFormatExtended></format></coreMetadata>
+</ebuCoreMain>"#;
+
+const AUDIO_FORMAT_EXTENDED: &str = "audioFormatExtended";
+
+impl Default for Adm {
+    fn default() -> Self {
+        let document = Element::parse(DEFAULT_DOCUMENT.as_bytes())
+            .expect("Error parsing default ADM document");
+        Adm {
+            programmes: vec![],
+            contents: vec![],
+            objects: vec![],
+            pack_formats: vec![],
+            channel_formats: vec![],
+            stream_formats: vec![],
+            track_formats: vec![],
+            track_uids: vec![],
+            document,
+            other: vec![]
+        }
+    }
+}
+
+/// An `audioProgramme` element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioProgramme {
+    /// `audioProgrammeID`
+    pub id: String,
+
+    /// `audioProgrammeName`
+    pub name: String,
+
+    /// `audioProgrammeLanguage`
+    pub language: Option<String>,
+
+    /// `start` time
+    pub start: Option<String>,
+
+    /// `end` time
+    pub end: Option<String>,
+
+    /// `audioContentIDRef`s
+    pub content_refs: Vec<String>,
+
+    extra: Element
+}
+
+/// An `audioContent` element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioContent {
+    /// `audioContentID`
+    pub id: String,
+
+    /// `audioContentName`
+    pub name: String,
+
+    /// `audioContentLanguage`
+    pub language: Option<String>,
+
+    /// `audioObjectIDRef`s
+    pub object_refs: Vec<String>,
+
+    extra: Element
+}
+
+/// An `audioObject` element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioObject {
+    /// `audioObjectID`
+    pub id: String,
+
+    /// `audioObjectName`
+    pub name: String,
+
+    /// `start` time
+    pub start: Option<String>,
+
+    /// `duration`
+    pub duration: Option<String>,
+
+    /// `audioPackFormatIDRef`s
+    pub pack_format_refs: Vec<String>,
+
+    /// `audioTrackUIDRef`s
+    pub track_uid_refs: Vec<String>,
+
+    /// `audioObjectIDRef`s, the objects nested in this object
+    pub object_refs: Vec<String>,
+
+    extra: Element
+}
+
+/// An `audioPackFormat` element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioPackFormat {
+    /// `audioPackFormatID`
+    pub id: String,
+
+    /// `audioPackFormatName`
+    pub name: String,
+
+    /// `typeLabel`, e.g. "0001" for DirectSpeakers
+    pub type_label: Option<String>,
+
+    /// `typeDefinition`, e.g. "DirectSpeakers"
+    pub type_definition: Option<String>,
+
+    /// `audioChannelFormatIDRef`s
+    pub channel_format_refs: Vec<String>,
+
+    /// `audioPackFormatIDRef`s
+    pub pack_format_refs: Vec<String>,
+
+    extra: Element
+}
+
+/// An `audioChannelFormat` element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioChannelFormat {
+    /// `audioChannelFormatID`
+    pub id: String,
+
+    /// `audioChannelFormatName`
+    pub name: String,
+
+    /// `typeLabel`
+    pub type_label: Option<String>,
+
+    /// `typeDefinition`
+    pub type_definition: Option<String>,
+
+    /// `audioBlockFormat`s
+    pub block_formats: Vec<AudioBlockFormat>,
+
+    extra: Element
+}
+
+/// An `audioBlockFormat` element of an `audioChannelFormat`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioBlockFormat {
+    /// `audioBlockFormatID`
+    pub id: String,
+
+    /// `rtime`, the start time of the block
+    pub rtime: Option<String>,
+
+    /// `duration`
+    pub duration: Option<String>,
+
+    /// `speakerLabel`s
+    pub speaker_labels: Vec<String>,
+
+    /// `position`s, each a coordinate name and value, e.g. ("azimuth", 30.0)
+    ///
+    /// `position` elements with a `bound` or other attributes are not
+    /// included here, and are preserved as-is.
+    pub positions: Vec<(String, f64)>,
+
+    extra: Element
+}
+
+/// An `audioStreamFormat` element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioStreamFormat {
+    /// `audioStreamFormatID`
+    pub id: String,
+
+    /// `audioStreamFormatName`
+    pub name: String,
+
+    /// `formatLabel`
+    pub format_label: Option<String>,
+
+    /// `formatDefinition`
+    pub format_definition: Option<String>,
+
+    /// `audioChannelFormatIDRef`
+    pub channel_format_ref: Option<String>,
+
+    /// `audioPackFormatIDRef`
+    pub pack_format_ref: Option<String>,
+
+    /// `audioTrackFormatIDRef`s
+    pub track_format_refs: Vec<String>,
+
+    extra: Element
+}
+
+/// An `audioTrackFormat` element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioTrackFormat {
+    /// `audioTrackFormatID`
+    pub id: String,
+
+    /// `audioTrackFormatName`
+    pub name: String,
+
+    /// `formatLabel`
+    pub format_label: Option<String>,
+
+    /// `formatDefinition`
+    pub format_definition: Option<String>,
+
+    /// `audioStreamFormatIDRef`
+    pub stream_format_ref: Option<String>,
+
+    extra: Element
+}
+
+/// An `audioTrackUID` element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioTrackUid {
+    /// `UID`, e.g. "ATU_00000001"
+    pub uid: String,
+
+    /// `sampleRate`
+    pub sample_rate: Option<u32>,
+
+    /// `bitDepth`
+    pub bit_depth: Option<u16>,
+
+    /// `audioTrackFormatIDRef`
+    pub track_format_ref: Option<String>,
+
+    /// `audioChannelFormatIDRef`
+    pub channel_format_ref: Option<String>,
+
+    /// `audioPackFormatIDRef`
+    pub pack_format_ref: Option<String>,
+
+    extra: Element
+}
+
+// Parsing and writing helpers
+//
+// Each modeled element takes the attributes and children it understands
+// out of the parsed element, and keeps what's left as `extra`. When it's
+// written, the modeled attributes and children are put in front of the
+// extra ones.
+
+/// Clear namespaces, so elements inherit their parent's default namespace
+/// when they're written and compare equal to newly-created elements.
+fn strip_namespaces(element: &mut Element) {
+    element.prefix = None;
+    element.namespace = None;
+    element.namespaces = None;
+    for child in element.children.iter_mut() {
+        if let XMLNode::Element(e) = child {
+            strip_namespaces(e);
+        }
+    }
+}
+
+fn prepare_extra(mut element: Element) -> Element {
+    strip_namespaces(&mut element);
+    element.children.retain(|node| match node {
+        XMLNode::Text(t) => !t.trim().is_empty(),
+        _ => true
+    });
+    element
+}
+
+fn element_text(element: &Element) -> String {
+    element.get_text().map(|t| t.trim().to_string()).unwrap_or_default()
+}
+
+fn take_attr(element: &mut Element, name: &str) -> Option<String> {
+    element.attributes.shift_remove(name)
+}
+
+fn take_children(element: &mut Element, name: &str) -> Vec<Element> {
+    let mut taken = vec![];
+    let mut kept = vec![];
+    for node in element.children.drain(..) {
+        match node {
+            XMLNode::Element(e) if e.name == name => taken.push(e),
+            node => kept.push(node)
+        }
+    }
+    element.children = kept;
+    taken
+}
+
+fn take_refs(element: &mut Element, name: &str) -> Vec<String> {
+    take_children(element, name).iter().map(element_text).collect()
+}
+
+fn take_ref(element: &mut Element, name: &str) -> Option<String> {
+    let position = element.children.iter().position(|node| match node {
+        XMLNode::Element(e) => e.name == name,
+        _ => false
+    })?;
+    match element.children.remove(position) {
+        XMLNode::Element(e) => Some(element_text(&e)),
+        _ => None
+    }
+}
+
+fn text_element(name: &str, text: &str) -> XMLNode {
+    let mut element = Element::new(name);
+    element.children.push(XMLNode::Text(text.to_string()));
+    XMLNode::Element(element)
+}
+
+fn ref_elements(name: &str, refs: &[String]) -> Vec<XMLNode> {
+    refs.iter().map(|r| text_element(name, r)).collect()
+}
+
+/// Write `extra` with `attributes` and `children` in front of its own.
+fn build_element(extra: &Element, attributes: &[(&str, Option<&str>)], children: Vec<XMLNode>) -> Element {
+    let mut element = Element::new(&extra.name);
+    for (name, value) in attributes {
+        if let Some(v) = value {
+            element.attributes.insert(name.to_string(), v.to_string());
+        }
+    }
+    for (name, value) in extra.attributes.iter() {
+        element.attributes.insert(name.clone(), value.clone());
+    }
+    element.children = children;
+    element.children.extend(extra.children.iter().cloned());
+    element
+}
+
+/// Put `element` and its descendants in the namespace of `parent`, the
+/// reverse of `strip_namespaces()`.
+fn apply_namespace(element: &mut Element, parent: &Element) {
+    element.prefix = parent.prefix.clone();
+    element.namespace = parent.namespace.clone();
+    for child in element.children.iter_mut() {
+        if let XMLNode::Element(e) = child {
+            apply_namespace(e, parent);
+        }
+    }
+}
+
+fn find_audio_format_extended(element: &mut Element) -> Option<&mut Element> {
+    if element.name == AUDIO_FORMAT_EXTENDED {
+        return Some(element);
+    }
+    for node in element.children.iter_mut() {
+        if let XMLNode::Element(child) = node {
+            if let Some(found) = find_audio_format_extended(child) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+impl Adm {
+
+    /// Parse ADM metadata from the content of an `axml` chunk.
+    ///
+    /// The `audioFormatExtended` element may be the root of the document, or
+    /// nested in it, as it is in an `ebuCoreMain` document.
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let end = data.iter().rposition(|b| *b != 0 && !b.is_ascii_whitespace())
+            .map(|p| p + 1).unwrap_or(0);
+        let mut document = Element::parse(&data[..end])?;
+        let mut adm = Adm::default();
+
+        if let Some(afe) = find_audio_format_extended(&mut document) {
+            for node in afe.children.drain(..) {
+                let e = match node {
+                    XMLNode::Element(e) => e,
+                    XMLNode::Text(_) => continue,
+                    node => { adm.other.push(node); continue }
+                };
+                match e.name.as_str() {
+                    "audioProgramme" => adm.programmes.push(AudioProgramme::from_element(e)),
+                    "audioContent" => adm.contents.push(AudioContent::from_element(e)),
+                    "audioObject" => adm.objects.push(AudioObject::from_element(e)),
+                    "audioPackFormat" => adm.pack_formats.push(AudioPackFormat::from_element(e)),
+                    "audioChannelFormat" => adm.channel_formats.push(AudioChannelFormat::from_element(e)),
+                    "audioStreamFormat" => adm.stream_formats.push(AudioStreamFormat::from_element(e)),
+                    "audioTrackFormat" => adm.track_formats.push(AudioTrackFormat::from_element(e)),
+                    "audioTrackUID" => adm.track_uids.push(AudioTrackUid::from_element(e)),
+                    _ => adm.other.push(XMLNode::Element(prepare_extra(e)))
+                }
+            }
+        }
+
+        adm.document = document;
+        Ok( adm )
+    }
+
+    /// Serialize this ADM metadata as the content for an `axml` chunk.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut document = self.document.clone();
+        if let Some(afe) = find_audio_format_extended(&mut document) {
+            let mut children : Vec<XMLNode> = vec![];
+            children.extend(self.programmes.iter().map(|e| XMLNode::Element(e.to_element())));
+            children.extend(self.contents.iter().map(|e| XMLNode::Element(e.to_element())));
+            children.extend(self.objects.iter().map(|e| XMLNode::Element(e.to_element())));
+            children.extend(self.pack_formats.iter().map(|e| XMLNode::Element(e.to_element())));
+            children.extend(self.channel_formats.iter().map(|e| XMLNode::Element(e.to_element())));
+            children.extend(self.stream_formats.iter().map(|e| XMLNode::Element(e.to_element())));
+            children.extend(self.track_formats.iter().map(|e| XMLNode::Element(e.to_element())));
+            children.extend(self.track_uids.iter().map(|e| XMLNode::Element(e.to_element())));
+            children.extend(self.other.iter().cloned());
+            for child in children.iter_mut() {
+                if let XMLNode::Element(e) = child {
+                    apply_namespace(e, afe);
+                }
+            }
+            afe.children = children;
+        }
+
+        let mut buf = vec![];
+        let config = EmitterConfig::new().perform_indent(true);
+        document.write_with_config(&mut buf, config).expect("Error writing ADM");
+        buf
+    }
+
+    /// The `audioTrackUID` with the given UID.
+    ///
+    /// This can be used to look up the ADM description of a track, with the
+    /// track UIDs in `ChannelDescriptor::adm_track_audio_ids`.
+    pub fn track_uid(&self, uid: &str) -> Option<&AudioTrackUid> {
+        self.track_uids.iter().find(|t| t.uid == uid)
+    }
+
+    /// The `audioObject` with the given ID.
+    pub fn object(&self, id: &str) -> Option<&AudioObject> {
+        self.objects.iter().find(|o| o.id == id)
+    }
+
+    /// The `audioPackFormat` with the given ID.
+    pub fn pack_format(&self, id: &str) -> Option<&AudioPackFormat> {
+        self.pack_formats.iter().find(|p| p.id == id)
+    }
+
+    /// The `audioChannelFormat` with the given ID.
+    pub fn channel_format(&self, id: &str) -> Option<&AudioChannelFormat> {
+        self.channel_formats.iter().find(|c| c.id == id)
+    }
+}
+
+impl AudioProgramme {
+
+    /// A new `audioProgramme`
+    pub fn new(id: &str, name: &str) -> Self {
+        AudioProgramme { id: id.to_string(), name: name.to_string(), language: None,
+            start: None, end: None, content_refs: vec![], extra: Element::new("audioProgramme") }
+    }
+
+    fn from_element(mut e: Element) -> Self {
+        AudioProgramme {
+            id: take_attr(&mut e, "audioProgrammeID").unwrap_or_default(),
+            name: take_attr(&mut e, "audioProgrammeName").unwrap_or_default(),
+            language: take_attr(&mut e, "audioProgrammeLanguage"),
+            start: take_attr(&mut e, "start"),
+            end: take_attr(&mut e, "end"),
+            content_refs: take_refs(&mut e, "audioContentIDRef"),
+            extra: prepare_extra(e)
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        build_element(&self.extra, &[
+                ("audioProgrammeID", Some(&self.id)),
+                ("audioProgrammeName", Some(&self.name)),
+                ("audioProgrammeLanguage", self.language.as_deref()),
+                ("start", self.start.as_deref()),
+                ("end", self.end.as_deref())
+            ],
+            ref_elements("audioContentIDRef", &self.content_refs))
+    }
+}
+
+impl AudioContent {
+
+    /// A new `audioContent`
+    pub fn new(id: &str, name: &str) -> Self {
+        AudioContent { id: id.to_string(), name: name.to_string(), language: None,
+            object_refs: vec![], extra: Element::new("audioContent") }
+    }
+
+    fn from_element(mut e: Element) -> Self {
+        AudioContent {
+            id: take_attr(&mut e, "audioContentID").unwrap_or_default(),
+            name: take_attr(&mut e, "audioContentName").unwrap_or_default(),
+            language: take_attr(&mut e, "audioContentLanguage"),
+            object_refs: take_refs(&mut e, "audioObjectIDRef"),
+            extra: prepare_extra(e)
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        build_element(&self.extra, &[
+                ("audioContentID", Some(&self.id)),
+                ("audioContentName", Some(&self.name)),
+                ("audioContentLanguage", self.language.as_deref())
+            ],
+            ref_elements("audioObjectIDRef", &self.object_refs))
+    }
+}
+
+impl AudioObject {
+
+    /// A new `audioObject`
+    pub fn new(id: &str, name: &str) -> Self {
+        AudioObject { id: id.to_string(), name: name.to_string(), start: None, duration: None,
+            pack_format_refs: vec![], track_uid_refs: vec![], object_refs: vec![],
+            extra: Element::new("audioObject") }
+    }
+
+    fn from_element(mut e: Element) -> Self {
+        AudioObject {
+            id: take_attr(&mut e, "audioObjectID").unwrap_or_default(),
+            name: take_attr(&mut e, "audioObjectName").unwrap_or_default(),
+            start: take_attr(&mut e, "start"),
+            duration: take_attr(&mut e, "duration"),
+            pack_format_refs: take_refs(&mut e, "audioPackFormatIDRef"),
+            track_uid_refs: take_refs(&mut e, "audioTrackUIDRef"),
+            object_refs: take_refs(&mut e, "audioObjectIDRef"),
+            extra: prepare_extra(e)
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        let mut children = ref_elements("audioPackFormatIDRef", &self.pack_format_refs);
+        children.extend(ref_elements("audioTrackUIDRef", &self.track_uid_refs));
+        children.extend(ref_elements("audioObjectIDRef", &self.object_refs));
+        build_element(&self.extra, &[
+                ("audioObjectID", Some(&self.id)),
+                ("audioObjectName", Some(&self.name)),
+                ("start", self.start.as_deref()),
+                ("duration", self.duration.as_deref())
+            ], children)
+    }
+}
+
+impl AudioPackFormat {
+
+    /// A new `audioPackFormat`
+    pub fn new(id: &str, name: &str) -> Self {
+        AudioPackFormat { id: id.to_string(), name: name.to_string(), type_label: None,
+            type_definition: None, channel_format_refs: vec![], pack_format_refs: vec![],
+            extra: Element::new("audioPackFormat") }
+    }
+
+    fn from_element(mut e: Element) -> Self {
+        AudioPackFormat {
+            id: take_attr(&mut e, "audioPackFormatID").unwrap_or_default(),
+            name: take_attr(&mut e, "audioPackFormatName").unwrap_or_default(),
+            type_label: take_attr(&mut e, "typeLabel"),
+            type_definition: take_attr(&mut e, "typeDefinition"),
+            channel_format_refs: take_refs(&mut e, "audioChannelFormatIDRef"),
+            pack_format_refs: take_refs(&mut e, "audioPackFormatIDRef"),
+            extra: prepare_extra(e)
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        let mut children = ref_elements("audioChannelFormatIDRef", &self.channel_format_refs);
+        children.extend(ref_elements("audioPackFormatIDRef", &self.pack_format_refs));
+        build_element(&self.extra, &[
+                ("audioPackFormatID", Some(&self.id)),
+                ("audioPackFormatName", Some(&self.name)),
+                ("typeLabel", self.type_label.as_deref()),
+                ("typeDefinition", self.type_definition.as_deref())
+            ], children)
+    }
+}
+
+impl AudioChannelFormat {
+
+    /// A new `audioChannelFormat`
+    pub fn new(id: &str, name: &str) -> Self {
+        AudioChannelFormat { id: id.to_string(), name: name.to_string(), type_label: None,
+            type_definition: None, block_formats: vec![],
+            extra: Element::new("audioChannelFormat") }
+    }
+
+    fn from_element(mut e: Element) -> Self {
+        AudioChannelFormat {
+            id: take_attr(&mut e, "audioChannelFormatID").unwrap_or_default(),
+            name: take_attr(&mut e, "audioChannelFormatName").unwrap_or_default(),
+            type_label: take_attr(&mut e, "typeLabel"),
+            type_definition: take_attr(&mut e, "typeDefinition"),
+            block_formats: take_children(&mut e, "audioBlockFormat").into_iter()
+                .map(AudioBlockFormat::from_element).collect(),
+            extra: prepare_extra(e)
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        build_element(&self.extra, &[
+                ("audioChannelFormatID", Some(&self.id)),
+                ("audioChannelFormatName", Some(&self.name)),
+                ("typeLabel", self.type_label.as_deref()),
+                ("typeDefinition", self.type_definition.as_deref())
+            ],
+            self.block_formats.iter().map(|b| XMLNode::Element(b.to_element())).collect())
+    }
+}
+
+impl AudioBlockFormat {
+
+    /// A new `audioBlockFormat`
+    pub fn new(id: &str) -> Self {
+        AudioBlockFormat { id: id.to_string(), rtime: None, duration: None,
+            speaker_labels: vec![], positions: vec![], extra: Element::new("audioBlockFormat") }
+    }
+
+    fn from_element(mut e: Element) -> Self {
+        let id = take_attr(&mut e, "audioBlockFormatID").unwrap_or_default();
+        let rtime = take_attr(&mut e, "rtime");
+        let duration = take_attr(&mut e, "duration");
+        let speaker_labels = take_refs(&mut e, "speakerLabel");
+
+        // only simple positions, with just a coordinate attribute, are modeled
+        let mut positions = vec![];
+        let mut kept = vec![];
+        for node in e.children.drain(..) {
+            match node {
+                XMLNode::Element(p) if p.name == "position" && p.attributes.len() == 1 => {
+                    match (p.attributes.get("coordinate"), element_text(&p).parse::<f64>()) {
+                        (Some(coordinate), Ok(value)) => positions.push((coordinate.clone(), value)),
+                        _ => kept.push(XMLNode::Element(p))
+                    }
+                },
+                node => kept.push(node)
+            }
+        }
+        e.children = kept;
+
+        AudioBlockFormat { id, rtime, duration, speaker_labels, positions, extra: prepare_extra(e) }
+    }
+
+    fn to_element(&self) -> Element {
+        let mut children = ref_elements("speakerLabel", &self.speaker_labels);
+        for (coordinate, value) in self.positions.iter() {
+            let mut position = Element::new("position");
+            position.attributes.insert(String::from("coordinate"), coordinate.clone());
+            position.children.push(XMLNode::Text(value.to_string()));
+            children.push(XMLNode::Element(position));
+        }
+        build_element(&self.extra, &[
+                ("audioBlockFormatID", Some(&self.id)),
+                ("rtime", self.rtime.as_deref()),
+                ("duration", self.duration.as_deref())
+            ], children)
+    }
+}
+
+impl AudioStreamFormat {
+
+    /// A new `audioStreamFormat`
+    pub fn new(id: &str, name: &str) -> Self {
+        AudioStreamFormat { id: id.to_string(), name: name.to_string(), format_label: None,
+            format_definition: None, channel_format_ref: None, pack_format_ref: None,
+            track_format_refs: vec![], extra: Element::new("audioStreamFormat") }
+    }
+
+    fn from_element(mut e: Element) -> Self {
+        AudioStreamFormat {
+            id: take_attr(&mut e, "audioStreamFormatID").unwrap_or_default(),
+            name: take_attr(&mut e, "audioStreamFormatName").unwrap_or_default(),
+            format_label: take_attr(&mut e, "formatLabel"),
+            format_definition: take_attr(&mut e, "formatDefinition"),
+            channel_format_ref: take_ref(&mut e, "audioChannelFormatIDRef"),
+            pack_format_ref: take_ref(&mut e, "audioPackFormatIDRef"),
+            track_format_refs: take_refs(&mut e, "audioTrackFormatIDRef"),
+            extra: prepare_extra(e)
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        let mut children = vec![];
+        children.extend(self.channel_format_ref.iter().map(|r| text_element("audioChannelFormatIDRef", r)));
+        children.extend(self.pack_format_ref.iter().map(|r| text_element("audioPackFormatIDRef", r)));
+        children.extend(ref_elements("audioTrackFormatIDRef", &self.track_format_refs));
+        build_element(&self.extra, &[
+                ("audioStreamFormatID", Some(&self.id)),
+                ("audioStreamFormatName", Some(&self.name)),
+                ("formatLabel", self.format_label.as_deref()),
+                ("formatDefinition", self.format_definition.as_deref())
+            ], children)
+    }
+}
+
+impl AudioTrackFormat {
+
+    /// A new `audioTrackFormat`
+    pub fn new(id: &str, name: &str) -> Self {
+        AudioTrackFormat { id: id.to_string(), name: name.to_string(), format_label: None,
+            format_definition: None, stream_format_ref: None,
+            extra: Element::new("audioTrackFormat") }
+    }
+
+    fn from_element(mut e: Element) -> Self {
+        AudioTrackFormat {
+            id: take_attr(&mut e, "audioTrackFormatID").unwrap_or_default(),
+            name: take_attr(&mut e, "audioTrackFormatName").unwrap_or_default(),
+            format_label: take_attr(&mut e, "formatLabel"),
+            format_definition: take_attr(&mut e, "formatDefinition"),
+            stream_format_ref: take_ref(&mut e, "audioStreamFormatIDRef"),
+            extra: prepare_extra(e)
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        build_element(&self.extra, &[
+                ("audioTrackFormatID", Some(&self.id)),
+                ("audioTrackFormatName", Some(&self.name)),
+                ("formatLabel", self.format_label.as_deref()),
+                ("formatDefinition", self.format_definition.as_deref())
+            ],
+            self.stream_format_ref.iter().map(|r| text_element("audioStreamFormatIDRef", r)).collect())
+    }
+}
+
+impl AudioTrackUid {
+
+    /// A new `audioTrackUID`
+    pub fn new(uid: &str) -> Self {
+        AudioTrackUid { uid: uid.to_string(), sample_rate: None, bit_depth: None,
+            track_format_ref: None, channel_format_ref: None, pack_format_ref: None,
+            extra: Element::new("audioTrackUID") }
+    }
+
+    fn from_element(mut e: Element) -> Self {
+        let uid = take_attr(&mut e, "UID").unwrap_or_default();
+
+        // keep numeric attributes that don't parse
+        let sample_rate = e.attributes.get("sampleRate").and_then(|v| v.parse().ok());
+        if sample_rate.is_some() { take_attr(&mut e, "sampleRate"); }
+        let bit_depth = e.attributes.get("bitDepth").and_then(|v| v.parse().ok());
+        if bit_depth.is_some() { take_attr(&mut e, "bitDepth"); }
+
+        AudioTrackUid {
+            uid,
+            sample_rate,
+            bit_depth,
+            track_format_ref: take_ref(&mut e, "audioTrackFormatIDRef"),
+            channel_format_ref: take_ref(&mut e, "audioChannelFormatIDRef"),
+            pack_format_ref: take_ref(&mut e, "audioPackFormatIDRef"),
+            extra: prepare_extra(e)
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        let sample_rate = self.sample_rate.map(|v| v.to_string());
+        let bit_depth = self.bit_depth.map(|v| v.to_string());
+        let mut children = vec![];
+        children.extend(self.track_format_ref.iter().map(|r| text_element("audioTrackFormatIDRef", r)));
+        children.extend(self.channel_format_ref.iter().map(|r| text_element("audioChannelFormatIDRef", r)));
+        children.extend(self.pack_format_ref.iter().map(|r| text_element("audioPackFormatIDRef", r)));
+        build_element(&self.extra, &[
+                ("UID", Some(&self.uid)),
+                ("sampleRate", sample_rate.as_deref()),
+                ("bitDepth", bit_depth.as_deref())
+            ], children)
+    }
+}
+
+#[cfg(test)]
+const TEST_AXML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ebuCoreMain xmlns="urn:ebu:metadata-schema:ebuCore_2014" xmlns:dc="http://purl.org/dc/elements/1.1/" schema="EBU_CORE_20140201.xsd" xml:lang="en">
+  <coreMetadata>
+    <format>
+      <audioFormatExtended version="ITU-R_BS.2076-2">
+        <audioProgramme audioProgrammeID="APR_1001" audioProgrammeName="Programme" start="00:00:00.00000" audioProgrammeLanguage="en">
+          <audioContentIDRef>ACO_1001</audioContentIDRef>
+          <loudnessMetadata><integratedLoudness>-23.0</integratedLoudness></loudnessMetadata>
+        </audioProgramme>
+        <audioContent audioContentID="ACO_1001" audioContentName="Bed">
+          <audioObjectIDRef>AO_1001</audioObjectIDRef>
+          <dialogue nonDialogueContentKind="1">0</dialogue>
+        </audioContent>
+        <audioObject audioObjectID="AO_1001" audioObjectName="Stereo Bed" interact="0">
+          <audioPackFormatIDRef>AP_00010002</audioPackFormatIDRef>
+          <audioTrackUIDRef>ATU_00000001</audioTrackUIDRef>
+          <audioTrackUIDRef>ATU_00000002</audioTrackUIDRef>
+        </audioObject>
+        <audioPackFormat audioPackFormatID="AP_00031001" audioPackFormatName="Object" typeLabel="0003" typeDefinition="Objects">
+          <audioChannelFormatIDRef>AC_00031001</audioChannelFormatIDRef>
+        </audioPackFormat>
+        <audioChannelFormat audioChannelFormatID="AC_00031001" audioChannelFormatName="Object 1" typeLabel="0003" typeDefinition="Objects">
+          <audioBlockFormat audioBlockFormatID="AB_00031001_00000001" rtime="00:00:00.00000" duration="00:00:05.00000">
+            <position coordinate="azimuth">-30.0</position>
+            <position coordinate="elevation">0.0</position>
+            <position coordinate="distance">1.0</position>
+            <position coordinate="azimuth" bound="max">-20.0</position>
+            <gain>1.0</gain>
+          </audioBlockFormat>
+        </audioChannelFormat>
+        <audioTrackUID UID="ATU_00000001" sampleRate="48000" bitDepth="24">
+          <audioTrackFormatIDRef>AT_00010001_01</audioTrackFormatIDRef>
+          <audioPackFormatIDRef>AP_00010002</audioPackFormatIDRef>
+        </audioTrackUID>
+        <audioTrackUID UID="ATU_00000002" sampleRate="48000" bitDepth="24">
+          <audioTrackFormatIDRef>AT_00010002_01</audioTrackFormatIDRef>
+          <audioPackFormatIDRef>AP_00010002</audioPackFormatIDRef>
+        </audioTrackUID>
+        <vendorElement>Vendor</vendorElement>
+      </audioFormatExtended>
+    </format>
+  </coreMetadata>
+</ebuCoreMain>
+"#;
+
+#[test]
+fn test_parse_adm() {
+    let adm = Adm::parse(TEST_AXML.as_bytes()).unwrap();
+
+    assert_eq!(adm.programmes.len(), 1);
+    assert_eq!(adm.programmes[0].id, "APR_1001");
+    assert_eq!(adm.programmes[0].language.as_deref(), Some("en"));
+    assert_eq!(adm.programmes[0].content_refs, ["ACO_1001"]);
+
+    assert_eq!(adm.contents[0].object_refs, ["AO_1001"]);
+    assert_eq!(adm.objects[0].track_uid_refs, ["ATU_00000001", "ATU_00000002"]);
+    assert_eq!(adm.pack_formats[0].type_definition.as_deref(), Some("Objects"));
+
+    let block = &adm.channel_format("AC_00031001").unwrap().block_formats[0];
+    assert_eq!(block.duration.as_deref(), Some("00:00:05.00000"));
+    assert_eq!(block.positions.len(), 3);
+    assert_eq!(block.positions[0], (String::from("azimuth"), -30.0));
+
+    let track = adm.track_uid("ATU_00000002").unwrap();
+    assert_eq!(track.sample_rate, Some(48000));
+    assert_eq!(track.bit_depth, Some(24));
+    assert_eq!(track.track_format_ref.as_deref(), Some("AT_00010002_01"));
+    assert_eq!(track.pack_format_ref.as_deref(), Some("AP_00010002"));
+}
+
+#[test]
+fn test_adm_round_trip_preserves_unknown() {
+    let adm = Adm::parse(TEST_AXML.as_bytes()).unwrap();
+    let bytes = adm.to_bytes();
+    let text = String::from_utf8(bytes.clone()).unwrap();
+
+    assert!(text.contains("<ebuCoreMain"));
+    assert!(text.contains("integratedLoudness"));
+    assert!(text.contains("nonDialogueContentKind=\"1\""));
+    assert!(text.contains("interact=\"0\""));
+    assert!(text.contains("bound=\"max\""));
+    assert!(text.contains("<vendorElement>Vendor</vendorElement>"));
+    assert!(!text.contains("xmlns=\"\""));
+
+    assert_eq!(Adm::parse(&bytes).unwrap(), adm);
+}
+
+#[test]
+fn test_adm_round_trip_prefixed_document() {
+    let axml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ebuCore:ebuCoreMain xmlns:ebuCore="urn:ebu:metadata-schema:ebuCore_2016">
+  <ebuCore:coreMetadata>
+    <ebuCore:format>
+      <ebuCore:audioFormatExtended>
+        <ebuCore:audioObject audioObjectID="AO_1001" audioObjectName="Dialogue">
+          <ebuCore:audioTrackUIDRef>ATU_00000001</ebuCore:audioTrackUIDRef>
+          <ebuCore:gain>0.5</ebuCore:gain>
+        </ebuCore:audioObject>
+        <ebuCore:vendorElement>Vendor</ebuCore:vendorElement>
+      </ebuCore:audioFormatExtended>
+    </ebuCore:format>
+  </ebuCore:coreMetadata>
+</ebuCore:ebuCoreMain>
+"#;
+    let adm = Adm::parse(axml.as_bytes()).unwrap();
+    let bytes = adm.to_bytes();
+    let text = String::from_utf8(bytes.clone()).unwrap();
+
+    assert!(text.contains("<ebuCore:audioObject "));
+    assert!(text.contains("<ebuCore:audioTrackUIDRef>ATU_00000001</ebuCore:audioTrackUIDRef>"));
+    assert!(text.contains("<ebuCore:gain>0.5</ebuCore:gain>"));
+    assert!(text.contains("<ebuCore:vendorElement>Vendor</ebuCore:vendorElement>"));
+    assert!(!text.contains("<audioObject"));
+
+    assert_eq!(Adm::parse(&bytes).unwrap(), adm);
+}
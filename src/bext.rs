@@ -1,3 +1,11 @@
+use super::errors::Error;
+use super::fourcc::BEXT_SIG;
+use super::chunks::validate_text_field;
+use super::umid::Umid;
+use super::coding_history::CodingHistoryEntry;
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub type LU = f32;
 pub type LUFS = f32;
@@ -14,17 +22,15 @@ pub type Decibels = f32;
 ///  For a Wave file to be a complaint "Broadcast-WAV" file, it must contain
 ///  a `bext` metadata record.
 ///
+///  New records can be made with `Bext::builder()`.
+///
 /// ## Resources
 /// - [EBU Tech 3285](https://tech.ebu.ch/docs/tech/tech3285.pdf).
 /// - [EBU Tech R098](https://tech.ebu.ch/docs/r/r098.pdf) (1999) "Format for the &lt;CodingHistory&gt; field in Broadcast Wave Format files, BWF"
 /// - [EBU Tech R099](https://tech.ebu.ch/docs/r/r099.pdf) (October 2011) "‘Unique’ Source Identifier (USID) for use in the 
 ///   &lt;OriginatorReference&gt; field of the Broadcast Wave Format"
 
-// Note for me later:
-// get env values: https://doc.rust-lang.org/std/macro.option_env.html
-// Cargo env values: https://doc.rust-lang.org/cargo/reference/environment-variables.html
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Bext {
 
     /// 256 ASCII character field with free text.
@@ -86,3 +92,498 @@ pub struct Bext {
     /// Coding History.
     pub coding_history: String
 }
+
+/// A calendar date, as in a `Bext`'s `origination_date`.
+///
+/// ```
+/// use bwavfile::OriginationDate;
+///
+/// let date = OriginationDate::new(2024, 2, 29).unwrap();
+/// assert_eq!(date.to_string(), "2024-02-29");
+/// assert_eq!(OriginationDate::parse("2024:02:29"), Some(date));
+/// assert_eq!(OriginationDate::new(2023, 2, 29), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OriginationDate {
+    year: u16,
+    month: u8,
+    day: u8
+}
+
+impl OriginationDate {
+
+    /// A date, or `None` if it doesn't exist or the year isn't four digits.
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        let days_in_month = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+            2 => 28,
+            _ => return None
+        };
+        if year > 9999 || day == 0 || day > days_in_month {
+            return None;
+        }
+        Some( OriginationDate { year, month, day } )
+    }
+
+    /// Parse a date in the form `YYYY-MM-DD`.
+    ///
+    /// EBU Tech 3285 permits any of `-`, `_`, `:`, space or `.` as the
+    /// separator.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (a, b, c) = split_fields(s, 4)?;
+        Self::new(a, b as u8, c as u8)
+    }
+
+    /// The date of `time`, in UTC.
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        Self::from_days_since_epoch((secs / 86400) as i64)
+    }
+
+    /// The date `days` days after 1970-01-01.
+    pub(crate) fn from_days_since_epoch(days: i64) -> Self {
+        // civil date from days since the epoch, after Howard Hinnant's algorithm
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let doe = days.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        OriginationDate { year: year.clamp(0, 9999) as u16, month: month as u8, day: day as u8 }
+    }
+
+    /// The number of days from 1970-01-01 to this date.
+    pub(crate) fn days_since_epoch(&self) -> i64 {
+        // days from a civil date, after Howard Hinnant's algorithm
+        let year = self.year as i64 - if self.month <= 2 { 1 } else { 0 };
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let mp = (self.month as i64 + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+
+    /// The year
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// The month, 1 to 12
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// The day of the month, from 1
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl fmt::Display for OriginationDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A time of day, as in a `Bext`'s `origination_time`.
+///
+/// ```
+/// use bwavfile::OriginationTime;
+///
+/// let time = OriginationTime::new(13, 5, 0).unwrap();
+/// assert_eq!(time.to_string(), "13:05:00");
+/// assert_eq!(OriginationTime::parse("13-05-00"), Some(time));
+/// assert_eq!(OriginationTime::new(24, 0, 0), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OriginationTime {
+    hour: u8,
+    minute: u8,
+    second: u8
+}
+
+impl OriginationTime {
+
+    /// A time, or `None` if it isn't a valid time of day.
+    pub fn new(hour: u8, minute: u8, second: u8) -> Option<Self> {
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+        Some( OriginationTime { hour, minute, second } )
+    }
+
+    /// Parse a time in the form `HH:MM:SS`.
+    ///
+    /// EBU Tech 3285 permits any of `-`, `_`, `:`, space or `.` as the
+    /// separator.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (a, b, c) = split_fields(s, 2)?;
+        Self::new(a as u8, b as u8, c as u8)
+    }
+
+    /// The time of day of `time`, in UTC.
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let tod = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() % 86400;
+        OriginationTime { hour: (tod / 3600) as u8, minute: ((tod / 60) % 60) as u8, second: (tod % 60) as u8 }
+    }
+
+    /// The hour, 0 to 23
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// The minute, 0 to 59
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// The second, 0 to 59
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+}
+
+impl fmt::Display for OriginationTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)
+    }
+}
+
+/// Split `s` into three numbers, the first `first_len` digits long and the
+/// others two digits long, separated by single separator characters.
+fn split_fields(s: &str, first_len: usize) -> Option<(u16, u16, u16)> {
+    let bytes = s.as_bytes();
+    if bytes.len() != first_len + 6 {
+        return None;
+    }
+    let is_separator = |c: u8| matches!(c, b'-' | b'_' | b':' | b' ' | b'.');
+    if !is_separator(bytes[first_len]) || !is_separator(bytes[first_len + 3]) {
+        return None;
+    }
+    let number = |r: std::ops::Range<usize>| -> Option<u16> {
+        let digits = &bytes[r];
+        if digits.iter().all(|c| c.is_ascii_digit()) {
+            Some(digits.iter().fold(0, |n, c| n * 10 + (c - b'0') as u16))
+        } else {
+            None
+        }
+    };
+    Some( (number(0..first_len)?, number(first_len + 1..first_len + 3)?,
+        number(first_len + 4..first_len + 6)?) )
+}
+
+/// Lengths of the text fields of a `bext` record, and their names
+const DESCRIPTION_LIMIT: (&str, usize) = ("description", 256);
+const ORIGINATOR_LIMIT: (&str, usize) = ("originator", 32);
+const ORIGINATOR_REFERENCE_LIMIT: (&str, usize) = ("originator_reference", 32);
+const ORIGINATION_DATE_LIMIT: (&str, usize) = ("origination_date", 10);
+const ORIGINATION_TIME_LIMIT: (&str, usize) = ("origination_time", 8);
+
+impl Bext {
+
+    /// A builder for a new `Bext`, with default values for every field.
+    ///
+    /// The defaults are:
+    /// - An empty `description` and `originator_reference`, and no UMID,
+    ///   loudness or coding history.
+    /// - The name of the running executable as the `originator`, or
+    ///   "bwavfile" if it can't be determined or doesn't fit.
+    /// - The current date and time, in UTC, as the `origination_date` and
+    ///   `origination_time`.
+    /// - A `time_reference` of zero.
+    ///
+    /// The `version` is chosen by `BextBuilder::build()`: 2 if any loudness
+    /// field is set, 1 if a UMID is set, and 0 otherwise.
+    ///
+    /// ```
+    /// use bwavfile::{Bext, Error};
+    ///
+    /// let bext = Bext::builder()
+    ///     .description("Dialogue, scene 4")
+    ///     .originator("Field Recorder")
+    ///     .time_reference(172_800_000)
+    ///     .loudness_value(-23.0)
+    ///     .build().unwrap();
+    ///
+    /// assert_eq!(bext.version, 2);
+    /// assert!(bext.parsed_origination_date().is_some());
+    ///
+    /// let too_long = Bext::builder().originator("An originator more than 32 characters long").build();
+    /// assert!(matches!(too_long, Err(Error::FieldTooLong { field: "originator", .. })));
+    ///
+    /// let not_ascii = Bext::builder().description("Café").build();
+    /// assert!(matches!(not_ascii, Err(Error::FieldNotAscii { field: "description", .. })));
+    /// ```
+    pub fn builder() -> BextBuilder {
+        BextBuilder::new()
+    }
+
+    /// Check that every text field fits in its space in a `bext` record
+    /// and is ASCII without any NUL characters.
+    ///
+    /// Line endings and tabs are allowed in every field.
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_text_field(BEXT_SIG, &self.description, DESCRIPTION_LIMIT)?;
+        validate_text_field(BEXT_SIG, &self.originator, ORIGINATOR_LIMIT)?;
+        validate_text_field(BEXT_SIG, &self.originator_reference, ORIGINATOR_REFERENCE_LIMIT)?;
+        validate_text_field(BEXT_SIG, &self.origination_date, ORIGINATION_DATE_LIMIT)?;
+        validate_text_field(BEXT_SIG, &self.origination_time, ORIGINATION_TIME_LIMIT)?;
+        validate_text_field(BEXT_SIG, &self.coding_history, ("coding_history", usize::MAX))?;
+        Ok(())
+    }
+
+    /// The `umid` field as a typed `Umid`.
+    ///
+    /// Returns `None` if there is no UMID, or the field doesn't contain a
+    /// SMPTE UMID.
+    pub fn parsed_umid(&self) -> Option<Umid> {
+        self.umid.as_ref().and_then(|u| Umid::from_bytes(u))
+    }
+
+    /// Set the `umid` field, raising `version` to 1 if it is lower.
+    pub fn set_umid(&mut self, umid: &Umid) {
+        let mut buf = [0u8; 64];
+        let bytes = umid.to_bytes();
+        buf[..bytes.len()].copy_from_slice(&bytes);
+        self.umid = Some(buf);
+        self.version = self.version.max(1);
+    }
+
+    /// The lines of the `coding_history` field.
+    pub fn parsed_coding_history(&self) -> Vec<CodingHistoryEntry> {
+        CodingHistoryEntry::parse_history(&self.coding_history)
+    }
+
+    /// Set the `coding_history` field to `entries`, one per line.
+    pub fn set_coding_history(&mut self, entries: &[CodingHistoryEntry]) {
+        self.coding_history = CodingHistoryEntry::format_history(entries);
+    }
+
+    /// The `origination_date` field as a validated date.
+    pub fn parsed_origination_date(&self) -> Option<OriginationDate> {
+        OriginationDate::parse(&self.origination_date)
+    }
+
+    /// Set the `origination_date` field.
+    pub fn set_origination_date(&mut self, date: OriginationDate) {
+        self.origination_date = date.to_string();
+    }
+
+    /// The `origination_time` field as a validated time.
+    pub fn parsed_origination_time(&self) -> Option<OriginationTime> {
+        OriginationTime::parse(&self.origination_time)
+    }
+
+    /// Set the `origination_time` field.
+    pub fn set_origination_time(&mut self, time: OriginationTime) {
+        self.origination_time = time.to_string();
+    }
+}
+
+/// A builder for `Bext` records, created by `Bext::builder()`.
+#[derive(Debug, Clone)]
+pub struct BextBuilder {
+    bext: Bext
+}
+
+impl BextBuilder {
+
+    fn new() -> Self {
+        let now = SystemTime::now();
+        let originator = std::env::current_exe().ok()
+            .and_then(|path| path.file_stem().and_then(|s| s.to_str()).map(String::from))
+            .filter(|name| validate_text_field(BEXT_SIG, name, ORIGINATOR_LIMIT).is_ok())
+            .unwrap_or_else(|| String::from("bwavfile"));
+
+        BextBuilder {
+            bext: Bext {
+                description: String::new(),
+                originator,
+                originator_reference: String::new(),
+                origination_date: OriginationDate::from_system_time(now).to_string(),
+                origination_time: OriginationTime::from_system_time(now).to_string(),
+                time_reference: 0,
+                version: 0,
+                umid: None,
+                loudness_value: None,
+                loudness_range: None,
+                max_true_peak_level: None,
+                max_momentary_loudness: None,
+                max_short_term_loudness: None,
+                coding_history: String::new()
+            }
+        }
+    }
+
+    /// Set the description, at most 256 characters
+    pub fn description(mut self, description: &str) -> Self {
+        self.bext.description = description.to_string();
+        self
+    }
+
+    /// Set the originator, at most 32 characters
+    pub fn originator(mut self, originator: &str) -> Self {
+        self.bext.originator = originator.to_string();
+        self
+    }
+
+    /// Set the originator reference, at most 32 characters. A `Usid` can
+    /// be passed with `Usid::to_string()`.
+    pub fn originator_reference(mut self, originator_reference: &str) -> Self {
+        self.bext.originator_reference = originator_reference.to_string();
+        self
+    }
+
+    /// Set the origination date
+    pub fn origination_date(mut self, date: OriginationDate) -> Self {
+        self.bext.set_origination_date(date);
+        self
+    }
+
+    /// Set the origination time
+    pub fn origination_time(mut self, time: OriginationTime) -> Self {
+        self.bext.set_origination_time(time);
+        self
+    }
+
+    /// Set the time reference, in samples since midnight
+    pub fn time_reference(mut self, time_reference: u64) -> Self {
+        self.bext.time_reference = time_reference;
+        self
+    }
+
+    /// Set the UMID
+    pub fn umid(mut self, umid: &Umid) -> Self {
+        self.bext.set_umid(umid);
+        self
+    }
+
+    /// Set the integrated loudness in LUFS
+    pub fn loudness_value(mut self, value: LUFS) -> Self {
+        self.bext.loudness_value = Some(value);
+        self
+    }
+
+    /// Set the loudness range in LU
+    pub fn loudness_range(mut self, value: LU) -> Self {
+        self.bext.loudness_range = Some(value);
+        self
+    }
+
+    /// Set the maximum true peak level in dBTP
+    pub fn max_true_peak_level(mut self, value: Decibels) -> Self {
+        self.bext.max_true_peak_level = Some(value);
+        self
+    }
+
+    /// Set the maximum momentary loudness in LUFS
+    pub fn max_momentary_loudness(mut self, value: LUFS) -> Self {
+        self.bext.max_momentary_loudness = Some(value);
+        self
+    }
+
+    /// Set the maximum short-term loudness in LUFS
+    pub fn max_short_term_loudness(mut self, value: LUFS) -> Self {
+        self.bext.max_short_term_loudness = Some(value);
+        self
+    }
+
+    /// Set the coding history
+    pub fn coding_history(mut self, coding_history: &str) -> Self {
+        self.bext.coding_history = coding_history.to_string();
+        self
+    }
+
+    /// Add a line to the end of the coding history
+    pub fn add_coding_history(mut self, entry: &CodingHistoryEntry) -> Self {
+        self.bext.coding_history.push_str(&format!("{}\r\n", entry));
+        self
+    }
+
+    /// The `Bext`, or an error if a text field is too long or isn't ASCII.
+    pub fn build(self) -> Result<Bext, Error> {
+        let mut bext = self.bext;
+        let has_loudness = bext.loudness_value.is_some() || bext.loudness_range.is_some()
+            || bext.max_true_peak_level.is_some() || bext.max_momentary_loudness.is_some()
+            || bext.max_short_term_loudness.is_some();
+
+        bext.version = if has_loudness { 2 } else if bext.umid.is_some() { 1 } else { 0 };
+        if has_loudness {
+            bext.umid = bext.umid.or(Some([0u8; 64]));
+        }
+        bext.validate()?;
+        Ok(bext)
+    }
+}
+
+#[test]
+fn test_bext_builder_versions() {
+    let bext = Bext::builder().build().unwrap();
+    assert_eq!(bext.version, 0);
+    assert!(bext.umid.is_none());
+    assert!(!bext.originator.is_empty());
+    assert!(bext.parsed_origination_time().is_some());
+
+    let umid = Umid::new(0x08, 0x20, 0, [1u8; 16]);
+    let bext = Bext::builder().umid(&umid).build().unwrap();
+    assert_eq!(bext.version, 1);
+    assert_eq!(bext.parsed_umid(), Some(umid));
+
+    let bext = Bext::builder().max_true_peak_level(-1.0).build().unwrap();
+    assert_eq!(bext.version, 2);
+    assert_eq!(bext.umid, Some([0u8; 64]));
+    assert_eq!(bext.loudness_value, None);
+}
+
+#[test]
+fn test_bext_validation() {
+    let history = CodingHistoryEntry::parse("A=PCM,F=48000,W=24,M=mono");
+    let bext = Bext::builder()
+        .description(&"D".repeat(256))
+        .originator_reference(&"R".repeat(32))
+        .add_coding_history(&history)
+        .add_coding_history(&history)
+        .build().unwrap();
+    assert_eq!(bext.parsed_coding_history(), vec![history.clone(), history]);
+
+    let error = Bext::builder().description(&"D".repeat(257)).build();
+    assert!(matches!(error, Err(Error::FieldTooLong { chunk: BEXT_SIG, field: "description", length: 257, limit: 256 })));
+
+    let error = Bext::builder().originator("Null\0Terminated").build();
+    assert!(matches!(error, Err(Error::FieldNotAscii { chunk: BEXT_SIG, field: "originator" })));
+
+    assert!(Bext::builder().originator("Tab\tSeparated").build().is_ok());
+
+    let error = Bext::builder().coding_history("A=PCM,T=Zoë\r\n").build();
+    assert!(matches!(error, Err(Error::FieldNotAscii { chunk: BEXT_SIG, field: "coding_history" })));
+}
+
+#[test]
+fn test_origination_date_time() {
+    assert_eq!(OriginationDate::parse("2021-01-31"), OriginationDate::new(2021, 1, 31));
+    assert_eq!(OriginationDate::parse("2021_12_01").unwrap().month(), 12);
+    assert_eq!(OriginationDate::parse("2000.02.29").unwrap().day(), 29);
+    assert_eq!(OriginationDate::parse("1900-02-29"), None);
+    assert_eq!(OriginationDate::parse("2021-13-01"), None);
+    assert_eq!(OriginationDate::parse("21-01-01"), None);
+    assert_eq!(OriginationDate::parse("2021/01/01"), None);
+    assert_eq!(OriginationDate::parse(""), None);
+
+    assert_eq!(OriginationTime::parse("23:59:59").unwrap().hour(), 23);
+    assert_eq!(OriginationTime::parse("00 00 00"), OriginationTime::new(0, 0, 0));
+    assert_eq!(OriginationTime::parse("12:60:00"), None);
+    assert_eq!(OriginationTime::parse("1:02:03"), None);
+    assert_eq!(OriginationTime::parse("+1:02:03"), None);
+
+    let time = UNIX_EPOCH + std::time::Duration::from_secs(951827696);
+    assert_eq!(OriginationDate::from_system_time(time).to_string(), "2000-02-29");
+    assert_eq!(OriginationTime::from_system_time(time).to_string(), "12:34:56");
+}
@@ -0,0 +1,290 @@
+use super::errors::Error;
+use super::fourcc::{FourCC, ReadFourCC, WriteFourCC, CART_SIG};
+use super::chunks::validate_text_field;
+
+use byteorder::{WriteBytesExt, ReadBytesExt, LittleEndian};
+
+use std::io::{Cursor, Read, Write};
+
+/// Radio traffic metadata, the content of a `cart` chunk.
+///
+/// Carries the information radio automation systems exchange about a cut:
+/// its title and artist, traffic identifiers, the dates it may air between,
+/// the application that produced it, and post timers marking points in the
+/// audio such as the end of an intro or the start of a segue.
+///
+/// Text fields are ASCII, and each has a fixed space in the chunk;
+/// `WaveWriter::write_cart()` returns an error if a field doesn't fit.
+///
+/// ```
+/// use bwavfile::{Cart, CartTimer, FourCC};
+///
+/// let mut cart = Cart::default();
+/// cart.title = String::from("Morning Drive Promo");
+/// cart.cut_id = String::from("PR-1042");
+/// cart.start_date = String::from("2021-04-01");
+/// cart.post_timers[0] = Some(CartTimer { usage: FourCC::make(b"INT1"), value: 240000 });
+///
+/// assert_eq!(cart.version, "0101");
+/// assert_eq!(cart.timer(FourCC::make(b"INT1")), Some(240000));
+/// assert!(cart.validate().is_ok());
+/// ```
+///
+/// ## Resources
+/// - AES46-2002 "AES standard for network and file transfer of audio - Audio-file transfer and
+///   exchange - Radio traffic audio delivery extension to the broadcast-WAVE-file format"
+/// - [CartChunk](http://www.cartchunk.org)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cart {
+    /// Version of the cart format, as four digits, "0101" for version 1.01
+    pub version: String,
+
+    /// Title of the cut, at most 64 characters
+    pub title: String,
+
+    /// Artist or creator, at most 64 characters
+    pub artist: String,
+
+    /// Cut number identification, at most 64 characters
+    pub cut_id: String,
+
+    /// Client identification, at most 64 characters
+    pub client_id: String,
+
+    /// Category, such as "DEMO" or "PSA", at most 64 characters
+    pub category: String,
+
+    /// Classification or auxiliary key, at most 64 characters
+    pub classification: String,
+
+    /// Out cue text, at most 64 characters
+    pub out_cue: String,
+
+    /// First date the cut may air, `YYYY-MM-DD`
+    pub start_date: String,
+
+    /// Time of day the cut may first air, `HH:MM:SS`
+    pub start_time: String,
+
+    /// Last date the cut may air, `YYYY-MM-DD`
+    pub end_date: String,
+
+    /// Time of day the cut may last air, `HH:MM:SS`
+    pub end_time: String,
+
+    /// Name of the application that produced the file, at most 64
+    /// characters
+    pub producer_app_id: String,
+
+    /// Version of the application that produced the file, at most 64
+    /// characters
+    pub producer_app_version: String,
+
+    /// User-defined text, at most 64 characters
+    pub user_def: String,
+
+    /// Sample value of the 0 dB reference level
+    pub level_reference: i32,
+
+    /// Post timers, `None` where a timer is unused
+    pub post_timers: [Option<CartTimer>; 8],
+
+    /// URL, at most 1024 characters
+    pub url: String,
+
+    /// Free text, which may contain CR LF line endings
+    pub tag_text: String
+}
+
+/// A post timer of a `Cart`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CartTimer {
+    /// What the timer marks, for example `SEG1` for the start of a segue,
+    /// `INT1` for the end of an intro or `EOD ` for the end of the cut
+    pub usage: FourCC,
+
+    /// Position of the timer, in sample frames from the start of the audio
+    pub value: u32
+}
+
+const VERSION_LENGTH: usize = 4;
+const TEXT_LENGTH: usize = 64;
+const DATE_LENGTH: usize = 10;
+const TIME_LENGTH: usize = 8;
+const RESERVED_LENGTH: usize = 276;
+const URL_LENGTH: usize = 1024;
+
+impl Default for Cart {
+    fn default() -> Self {
+        Cart {
+            version: String::from("0101"),
+            title: String::new(), artist: String::new(), cut_id: String::new(),
+            client_id: String::new(), category: String::new(), classification: String::new(),
+            out_cue: String::new(), start_date: String::new(), start_time: String::new(),
+            end_date: String::new(), end_time: String::new(), producer_app_id: String::new(),
+            producer_app_version: String::new(), user_def: String::new(), level_reference: 0,
+            post_timers: [None; 8], url: String::new(), tag_text: String::new()
+        }
+    }
+}
+
+fn read_text<R: Read>(reader: &mut R, length: usize) -> Result<String, Error> {
+    let mut buf = vec![0u8; length];
+    reader.read_exact(&mut buf)?;
+    Ok( decode_text(&buf) )
+}
+
+fn decode_text(buf: &[u8]) -> String {
+    buf.iter().take_while(|c| **c != 0).filter(|c| c.is_ascii()).map(|c| *c as char).collect()
+}
+
+fn write_text<W: Write>(writer: &mut W, text: &str, length: usize) -> Result<(), Error> {
+    let mut buf = text.as_bytes().to_vec();
+    buf.resize(length, 0);
+    writer.write_all(&buf)?;
+    Ok(())
+}
+
+impl Cart {
+
+    /// The text fields with a fixed length, their names and lengths, in the
+    /// order they appear in the chunk
+    fn fixed_fields(&self) -> [(&str, &'static str, usize); 15] {
+        [
+            (&self.version, "version", VERSION_LENGTH),
+            (&self.title, "title", TEXT_LENGTH),
+            (&self.artist, "artist", TEXT_LENGTH),
+            (&self.cut_id, "cut_id", TEXT_LENGTH),
+            (&self.client_id, "client_id", TEXT_LENGTH),
+            (&self.category, "category", TEXT_LENGTH),
+            (&self.classification, "classification", TEXT_LENGTH),
+            (&self.out_cue, "out_cue", TEXT_LENGTH),
+            (&self.start_date, "start_date", DATE_LENGTH),
+            (&self.start_time, "start_time", TIME_LENGTH),
+            (&self.end_date, "end_date", DATE_LENGTH),
+            (&self.end_time, "end_time", TIME_LENGTH),
+            (&self.producer_app_id, "producer_app_id", TEXT_LENGTH),
+            (&self.producer_app_version, "producer_app_version", TEXT_LENGTH),
+            (&self.user_def, "user_def", TEXT_LENGTH),
+        ]
+    }
+
+    /// The value of the first post timer with `usage`
+    pub fn timer(&self, usage: FourCC) -> Option<u32> {
+        self.post_timers.iter().flatten().find(|t| t.usage == usage).map(|t| t.value)
+    }
+
+    /// Check that every text field fits in its space in a `cart` chunk and
+    /// is ASCII without any NUL characters.
+    pub fn validate(&self) -> Result<(), Error> {
+        for (text, field, limit) in self.fixed_fields().iter() {
+            validate_text_field(CART_SIG, text, (field, *limit))?;
+        }
+        validate_text_field(CART_SIG, &self.url, ("url", URL_LENGTH))?;
+        validate_text_field(CART_SIG, &self.tag_text, ("tag_text", usize::MAX))?;
+        Ok(())
+    }
+
+    /// Parse the content of a `cart` chunk.
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, Error> {
+        let mut cursor = Cursor::new(data);
+        let mut cart = Cart {
+            version: read_text(&mut cursor, VERSION_LENGTH)?,
+            title: read_text(&mut cursor, TEXT_LENGTH)?,
+            artist: read_text(&mut cursor, TEXT_LENGTH)?,
+            cut_id: read_text(&mut cursor, TEXT_LENGTH)?,
+            client_id: read_text(&mut cursor, TEXT_LENGTH)?,
+            category: read_text(&mut cursor, TEXT_LENGTH)?,
+            classification: read_text(&mut cursor, TEXT_LENGTH)?,
+            out_cue: read_text(&mut cursor, TEXT_LENGTH)?,
+            start_date: read_text(&mut cursor, DATE_LENGTH)?,
+            start_time: read_text(&mut cursor, TIME_LENGTH)?,
+            end_date: read_text(&mut cursor, DATE_LENGTH)?,
+            end_time: read_text(&mut cursor, TIME_LENGTH)?,
+            producer_app_id: read_text(&mut cursor, TEXT_LENGTH)?,
+            producer_app_version: read_text(&mut cursor, TEXT_LENGTH)?,
+            user_def: read_text(&mut cursor, TEXT_LENGTH)?,
+            level_reference: cursor.read_i32::<LittleEndian>()?,
+            post_timers: [None; 8],
+            url: String::new(),
+            tag_text: String::new()
+        };
+
+        for timer in cart.post_timers.iter_mut() {
+            let usage = cursor.read_fourcc()?;
+            let value = cursor.read_u32::<LittleEndian>()?;
+            if usage != FourCC::make(&[0; 4]) {
+                *timer = Some(CartTimer { usage, value });
+            }
+        }
+
+        let mut reserved = [0u8; RESERVED_LENGTH];
+        cursor.read_exact(&mut reserved)?;
+        cart.url = read_text(&mut cursor, URL_LENGTH)?;
+
+        let mut tag_text = vec![];
+        cursor.read_to_end(&mut tag_text)?;
+        cart.tag_text = decode_text(&tag_text);
+
+        Ok( cart )
+    }
+
+    /// The content of a `cart` chunk for this metadata.
+    ///
+    /// Text fields are assumed to have been checked with `validate()`.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut cursor = Cursor::new(vec![0u8; 0]);
+        for (text, _, length) in self.fixed_fields().iter() {
+            write_text(&mut cursor, text, *length).unwrap();
+        }
+        cursor.write_i32::<LittleEndian>(self.level_reference).unwrap();
+        for timer in self.post_timers.iter() {
+            let timer = timer.unwrap_or(CartTimer { usage: FourCC::make(&[0; 4]), value: 0 });
+            cursor.write_fourcc(timer.usage).unwrap();
+            cursor.write_u32::<LittleEndian>(timer.value).unwrap();
+        }
+        cursor.write_all(&[0u8; RESERVED_LENGTH]).unwrap();
+        write_text(&mut cursor, &self.url, URL_LENGTH).unwrap();
+        cursor.write_all(self.tag_text.as_bytes()).unwrap();
+        cursor.into_inner()
+    }
+}
+
+#[test]
+fn test_cart_layout() {
+    let mut cart = Cart::default();
+    cart.title = String::from("Title");
+    cart.end_time = String::from("23:59:59");
+    cart.level_reference = 32768;
+    cart.post_timers[1] = Some(CartTimer { usage: FourCC::make(b"SEG1"), value: 0x01020304 });
+    cart.url = String::from("http://example.com/cut");
+
+    let bytes = cart.to_bytes();
+    assert_eq!(bytes.len(), 2048);
+    assert_eq!(&bytes[0..9], b"0101Title");
+    assert_eq!(&bytes[4 + 64 * 7 + 10 + 8 + 10..][..8], b"23:59:59");
+    assert_eq!(&bytes[680..684], &[0x00, 0x80, 0x00, 0x00]);
+    assert_eq!(&bytes[684..692], &[0u8; 8]);
+    assert_eq!(&bytes[692..700], b"SEG1\x04\x03\x02\x01");
+    assert_eq!(&bytes[1024..1047], b"http://example.com/cut\0");
+
+    assert_eq!(Cart::parse(&bytes).unwrap(), cart);
+}
+
+#[test]
+fn test_cart_tag_text_and_validation() {
+    let mut cart = Cart::default();
+    cart.tag_text = String::from("<tag>one</tag>\r\n<tag>two</tag>\r\n");
+    let mut bytes = cart.to_bytes();
+    assert_eq!(bytes.len(), 2048 + 32);
+    bytes.extend_from_slice(&[0, 0]);
+    assert_eq!(Cart::parse(&bytes).unwrap().tag_text, cart.tag_text);
+    assert!(cart.validate().is_ok());
+
+    cart.cut_id = "X".repeat(65);
+    assert!(matches!(cart.validate(), Err(Error::FieldTooLong { chunk: CART_SIG, field: "cut_id", length: 65, limit: 64 })));
+    cart.cut_id = String::from("Ünicode");
+    assert!(matches!(cart.validate(), Err(Error::FieldNotAscii { chunk: CART_SIG, field: "cut_id" })));
+
+    assert!(Cart::parse(&bytes[0..1000]).is_err());
+}
@@ -10,19 +10,35 @@ use byteorder::{ReadBytesExt, WriteBytesExt};
 use uuid::Uuid;
 
 use super::errors::Error as ParserError;
-use super::fmt::{WaveFmt, WaveFmtExtended};
+use super::fmt::{WaveFmt, WaveFmtExtended, ADMAudioID};
 use super::bext::Bext;
+use super::fourcc::FourCC;
+
+/// Check that `text` fits in the `limit` bytes given to `field` in a
+/// `chunk` record, and is ASCII without any NUL characters.
+pub(crate) fn validate_text_field(chunk: FourCC, text: &str, (field, limit): (&'static str, usize))
+    -> Result<(), ParserError> {
+    if !text.bytes().all(|c| c != 0 && c.is_ascii()) {
+        Err(ParserError::FieldNotAscii { chunk, field })
+    } else if text.len() > limit {
+        Err(ParserError::FieldTooLong { chunk, field, length: text.len(), limit })
+    } else {
+        Ok(())
+    }
+}
 
 pub trait ReadBWaveChunks: Read {
     fn read_bext(&mut self) -> Result<Bext, ParserError>;
     fn read_bext_string_field(&mut self, length: usize) -> Result<String,ParserError>;
     fn read_wave_fmt(&mut self) -> Result<WaveFmt, ParserError>;
+    fn read_chna(&mut self) -> Result<Vec<(u16, ADMAudioID)>, ParserError>;
 }
 
 pub trait WriteBWaveChunks: Write {
     fn write_wave_fmt(&mut self, format : &WaveFmt) -> Result<(), ParserError>;
     fn write_bext_string_field(&mut self, string: &String, length: usize) -> Result<(),ParserError>;
     fn write_bext(&mut self, bext: &Bext) -> Result<(),ParserError>;
+    fn write_chna(&mut self, ids: &[(u16, ADMAudioID)]) -> Result<(), ParserError>;
 }
 
 impl<T> WriteBWaveChunks for T where T: Write {
@@ -40,6 +56,9 @@ impl<T> WriteBWaveChunks for T where T: Write {
             self.write_u32::<LittleEndian>(ext.channel_mask)?;
             let uuid = ext.type_guid.as_bytes();
             self.write(uuid)?;
+        } else if format.tag != 0x0001 {
+            // non-PCM formats carry an empty extension, cbSize = 0
+            self.write_u16::<LittleEndian>(0)?;
         }
         Ok(())
     }
@@ -58,6 +77,7 @@ impl<T> WriteBWaveChunks for T where T: Write {
     }
 
     fn write_bext(&mut self, bext: &Bext) -> Result<(),ParserError> {
+        bext.validate()?;
         self.write_bext_string_field(&bext.description, 256)?;
         self.write_bext_string_field(&bext.originator, 32)?;
         self.write_bext_string_field(&bext.originator_reference, 32)?;
@@ -89,6 +109,32 @@ impl<T> WriteBWaveChunks for T where T: Write {
         self.write_all(&coding)?;
         Ok(())
     }
+
+    /// Write a `chna` chunk, each of `ids` is a 1-based track index and the
+    /// audio ID of that track. See BS.2088-1 § 8.
+    fn write_chna(&mut self, ids: &[(u16, ADMAudioID)]) -> Result<(), ParserError> {
+        let mut track_indexes : Vec<u16> = ids.iter().map(|(index, _)| *index).collect();
+        track_indexes.sort_unstable();
+        track_indexes.dedup();
+
+        if let Some((track_index, _)) = ids.iter().find(|(_, id)| !id.is_ascii()) {
+            return Err( ParserError::ADMAudioIDNotAscii { track_index: *track_index } );
+        }
+
+        self.write_u16::<LittleEndian>(track_indexes.len() as u16)?;
+        self.write_u16::<LittleEndian>(ids.len() as u16)?;
+        for (track_index, id) in ids {
+            self.write_u16::<LittleEndian>(*track_index)?;
+            let uid : Vec<u8> = id.track_uid.iter().map(|c| *c as u8).collect();
+            self.write_all(&uid)?;
+            let track_ref : Vec<u8> = id.channel_format_ref.iter().map(|c| *c as u8).collect();
+            self.write_all(&track_ref)?;
+            let pack_ref : Vec<u8> = id.pack_ref.iter().map(|c| *c as u8).collect();
+            self.write_all(&pack_ref)?;
+            self.write_u8(0)?; // pad
+        }
+        Ok(())
+    }
 }
 
 impl<T> ReadBWaveChunks for T where T: Read {
@@ -174,10 +220,63 @@ impl<T> ReadBWaveChunks for T where T: Read {
                     for _ in 0..180 { self.read_u8()?; }
                     let mut buf = vec![];
                     self.read_to_end(&mut buf)?;
-                    ASCII.decode(&buf, DecoderTrap::Ignore).expect("Error decoding text")
+                    // the history is often followed by a NUL terminator or padding
+                    let end = buf.iter().position(|c| *c == 0).unwrap_or(buf.len());
+                    ASCII.decode(&buf[..end], DecoderTrap::Ignore).expect("Error decoding text")
                 }
         })
      }
+
+    fn read_chna(&mut self) -> Result<Vec<(u16, ADMAudioID)>, ParserError> {
+        let _track_count = self.read_u16::<LittleEndian>()?;
+        let uid_count = self.read_u16::<LittleEndian>()?;
+        let mut retval = vec![];
+
+        for _ in 0..uid_count {
+            let track_index = self.read_u16::<LittleEndian>()?;
+            let mut id = ADMAudioID {
+                track_uid: [' '; 12],
+                channel_format_ref: [' '; 14],
+                pack_ref: [' '; 11]
+            };
+            let mut buf = [0u8; 38];
+            self.read_exact(&mut buf)?;
+            for (c, b) in id.track_uid.iter_mut().zip(&buf[0..12]) { *c = *b as char; }
+            for (c, b) in id.channel_format_ref.iter_mut().zip(&buf[12..26]) { *c = *b as char; }
+            for (c, b) in id.pack_ref.iter_mut().zip(&buf[26..37]) { *c = *b as char; }
+
+            // writers may reserve space for IDs with empty entries
+            if track_index > 0 {
+                retval.push( (track_index, id) );
+            }
+        }
+
+        Ok( retval )
+    }
+}
+
+#[test]
+fn test_chna_round_trip() {
+    use std::io::Cursor;
+
+    let ids = vec![
+        (1, ADMAudioID::new("ATU_00000001", "AT_00010001_01", "AP_00010002")),
+        (2, ADMAudioID::new("ATU_00000002", "AT_00010002_01", "AP_00010002")),
+        (2, ADMAudioID::new("ATU_00000003", "AT_00031001_01", "AP_00031001")),
+    ];
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    cursor.write_chna(&ids).unwrap();
+    assert_eq!(cursor.get_ref().len(), 4 + 40 * 3);
+    assert_eq!(&cursor.get_ref()[0..4], [2, 0, 3, 0]);
+
+    cursor.set_position(0);
+    assert_eq!(cursor.read_chna().unwrap(), ids);
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let bad = [(1, ADMAudioID::new("ATU_0000000é", "AT_00010001_01", "AP_00010002"))];
+    assert!(matches!(cursor.write_chna(&bad), Err(ParserError::ADMAudioIDNotAscii { track_index: 1 })));
+    assert!(cursor.get_ref().is_empty());
 }
 
 #[test]
@@ -0,0 +1,152 @@
+use std::fmt;
+
+/// A line of Broadcast-Wave coding history, in the format of EBU R98.
+///
+/// Each line of a `Bext`'s `coding_history` describes one stage of the
+/// signal's history: its coding algorithm, sampling frequency, bit rate,
+/// word length and mode, and free text such as the device used. Read and
+/// write the lines with `Bext::parsed_coding_history()` and
+/// `Bext::set_coding_history()`.
+///
+/// ```
+/// use bwavfile::CodingHistoryEntry;
+///
+/// let entry = CodingHistoryEntry {
+///     algorithm: Some(String::from("PCM")),
+///     sampling_frequency: Some(48000),
+///     word_length: Some(24),
+///     mode: Some(String::from("stereo")),
+///     text: Some(String::from("Recorder, SN 1234")),
+///     ..CodingHistoryEntry::default()
+/// };
+///
+/// assert_eq!(entry.to_string(), "A=PCM,F=48000,W=24,M=stereo,T=Recorder, SN 1234");
+/// assert_eq!(CodingHistoryEntry::parse_history(&format!("{}\r\n", entry)), vec![entry]);
+/// ```
+///
+/// ## Resources
+/// - [EBU Tech R098](https://tech.ebu.ch/docs/r/r098.pdf) (1999) "Format for the &lt;CodingHistory&gt; field in Broadcast Wave Format files, BWF"
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CodingHistoryEntry {
+    /// `A=` Coding algorithm, for example `ANALOGUE`, `PCM` or `MPEG1L2`
+    pub algorithm: Option<String>,
+
+    /// `F=` Sampling frequency in Hz
+    pub sampling_frequency: Option<u32>,
+
+    /// `B=` Bit rate in kbit/s per channel, for compressed formats
+    pub bit_rate: Option<u32>,
+
+    /// `W=` Word length in bits
+    pub word_length: Option<u32>,
+
+    /// `M=` Mode, for example `mono`, `stereo`, `dual-mono` or `joint-stereo`
+    pub mode: Option<String>,
+
+    /// `T=` Free text. This is always last on the line and may contain commas.
+    pub text: Option<String>,
+
+    /// Any other parameters, or parameters with values that aren't numbers
+    /// where one is expected
+    pub other: Vec<(String, String)>
+}
+
+impl CodingHistoryEntry {
+
+    /// Parse a single line of coding history.
+    pub fn parse(line: &str) -> Self {
+        let mut entry = CodingHistoryEntry::default();
+        let mut rest = line.trim_end_matches(['\r', '\n']);
+
+        while !rest.is_empty() {
+            if let Some(text) = rest.strip_prefix("T=") {
+                entry.text = Some(text.to_string());
+                break;
+            }
+
+            let (param, remainder) = match rest.find(',') {
+                Some(i) => (&rest[..i], &rest[i + 1..]),
+                None => (rest, "")
+            };
+            rest = remainder;
+
+            let (key, value) = match param.find('=') {
+                Some(i) => (&param[..i], &param[i + 1..]),
+                None => (param, "")
+            };
+
+            let number = value.trim().parse::<u32>().ok();
+            match (key.trim(), number) {
+                ("A", _) => entry.algorithm = Some(value.to_string()),
+                ("F", Some(n)) => entry.sampling_frequency = Some(n),
+                ("B", Some(n)) => entry.bit_rate = Some(n),
+                ("W", Some(n)) => entry.word_length = Some(n),
+                ("M", _) => entry.mode = Some(value.to_string()),
+                _ => entry.other.push((key.to_string(), value.to_string()))
+            }
+        }
+        entry
+    }
+
+    /// Parse a coding history, with one entry for each non-empty line.
+    pub fn parse_history(history: &str) -> Vec<Self> {
+        history.split(['\r', '\n'])
+            .filter(|line| !line.trim().is_empty())
+            .map(Self::parse)
+            .collect()
+    }
+
+    /// A coding history with a line for each of `entries`, each ending
+    /// with CR LF.
+    pub fn format_history(entries: &[Self]) -> String {
+        entries.iter().map(|e| format!("{}\r\n", e)).collect()
+    }
+}
+
+impl fmt::Display for CodingHistoryEntry {
+
+    /// The entry as a line of coding history, without a line ending.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut params: Vec<String> = vec![];
+        if let Some(a) = &self.algorithm { params.push(format!("A={}", a)); }
+        if let Some(n) = self.sampling_frequency { params.push(format!("F={}", n)); }
+        if let Some(n) = self.bit_rate { params.push(format!("B={}", n)); }
+        if let Some(n) = self.word_length { params.push(format!("W={}", n)); }
+        if let Some(m) = &self.mode { params.push(format!("M={}", m)); }
+        for (key, value) in self.other.iter() {
+            params.push(format!("{}={}", key, value));
+        }
+        if let Some(t) = &self.text { params.push(format!("T={}", t)); }
+        write!(f, "{}", params.join(","))
+    }
+}
+
+#[test]
+fn test_parse_coding_history() {
+    let history = "A=ANALOGUE,M=stereo,T=Studer A816; SN1007; 38; Agfa_PER528\r\n\
+        A=PCM,F=48000,W=18,M=stereo,T=NVision NV1000; A/D\r\n\
+        A=PCM,F=48000,W=24,M=stereo,X=extra,T=\r\n";
+
+    let entries = CodingHistoryEntry::parse_history(history);
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].algorithm.as_deref(), Some("ANALOGUE"));
+    assert_eq!(entries[0].sampling_frequency, None);
+    assert_eq!(entries[0].text.as_deref(), Some("Studer A816; SN1007; 38; Agfa_PER528"));
+    assert_eq!(entries[1].sampling_frequency, Some(48000));
+    assert_eq!(entries[1].word_length, Some(18));
+    assert_eq!(entries[2].other, vec![(String::from("X"), String::from("extra"))]);
+    assert_eq!(entries[2].text.as_deref(), Some(""));
+
+    assert_eq!(CodingHistoryEntry::format_history(&entries), history);
+}
+
+#[test]
+fn test_coding_history_text_with_commas() {
+    let entry = CodingHistoryEntry::parse("A=MPEG1L2,F=48000,B=192,M=joint-stereo,T=Encoder, v2, final");
+    assert_eq!(entry.bit_rate, Some(192));
+    assert_eq!(entry.text.as_deref(), Some("Encoder, v2, final"));
+
+    let odd = CodingHistoryEntry::parse("A=PCM,F=unknown");
+    assert_eq!(odd.sampling_frequency, None);
+    assert_eq!(odd.other, vec![(String::from("F"), String::from("unknown"))]);
+}
@@ -1,12 +1,10 @@
 use super::fourcc::{FourCC,ReadFourCC, WriteFourCC, LABL_SIG, NOTE_SIG, 
-    ADTL_SIG, LTXT_SIG, DATA_SIG};
-use super::list_form::collect_list_form;
+    LTXT_SIG, DATA_SIG};
+use super::list_form::{ListFormItem, collect_list_form, code_page_encoding};
 
 use byteorder::{WriteBytesExt, ReadBytesExt, LittleEndian};
 
-use encoding::{DecoderTrap,EncoderTrap};
-use encoding::{Encoding};
-use encoding::all::ASCII;
+use encoding::{DecoderTrap, EncoderTrap};
 
 use std::io::{Cursor, Error, Read, Write};
 
@@ -160,7 +158,7 @@ impl RawLtxt {
             dialect : rdr.read_u16::<LittleEndian>()?,
             code_page : rdr.read_u16::<LittleEndian>()?,
             text : {
-                if length - 20 > 0 {
+                if length > 20 {
                     let mut buf = vec![0u8; (length - 20) as usize];
                     rdr.read_exact(&mut buf)?;
                     Some( buf )
@@ -181,28 +179,18 @@ enum RawAdtlMember {
 }
 
 impl RawAdtlMember {
-    fn compile_adtl(members : &[Self]) -> Vec<u8> {
-        let mut w = Cursor::new(vec![0u8; 0]);
+    fn compile_adtl(members : &[Self]) -> Vec<ListFormItem> {
         // It seems like all this casing could be done with traits
-        for member in members.iter() {
-            let (fcc, buf) = match member {
-                RawAdtlMember::Label(l) => ( (LABL_SIG, l.write_to()) ),
-                RawAdtlMember::Note(n) => ( (NOTE_SIG, n.write_to()) ),
-                RawAdtlMember::LabeledText(t) => ( (LTXT_SIG, t.write_to()) ),
+        members.iter().map(|member| {
+            let (signature, contents) = match member {
+                RawAdtlMember::Label(l) => (LABL_SIG, l.write_to()),
+                RawAdtlMember::Note(n) => (NOTE_SIG, n.write_to()),
+                RawAdtlMember::LabeledText(t) => (LTXT_SIG, t.write_to()),
                 RawAdtlMember::Unrecognized(f) => (*f, vec![0u8;0] ) // <-- this is a dopey case but here for completeness
             };
-            w.write_fourcc(fcc).unwrap();
-            w.write_u32::<LittleEndian>(buf.len() as u32).unwrap();
-            w.write(&buf).unwrap();
-            if buf.len() % 2 == 1 { w.write_u8(0).unwrap(); }
-        }
-
-        let chunk_content = w.into_inner();
-        let mut writer = Cursor::new(vec![0u8; 0]);
-        writer.write_fourcc(ADTL_SIG).unwrap();
-        writer.write_u32::<LittleEndian>(chunk_content.len() as u32).unwrap();
-        writer.write(&chunk_content).unwrap();
-        writer.into_inner()
+            ListFormItem { signature, contents }
+        })
+        .collect()
     }
 
     fn collect_from(chunk : &[u8]) -> Result<Vec<RawAdtlMember>,Error> {
@@ -223,6 +211,78 @@ impl RawAdtlMember {
     }
 }
 
+/// A marker entry in an RF64 `r64m` chunk, per EBU Tech 3306 (2009)
+#[derive(Clone, Debug)]
+struct RawMarkerEntry {
+    flags : u32,
+    sample_offset : u64,
+    byte_offset : u64,
+    intra_sample_offset : u64,
+    label_text : [u8; 256],
+    label_chunk_identifier : u32,
+    vendor_and_product : [u8; 16],
+    user_data : [u32; 4]
+}
+
+// MarkerEntry flags, EBU Tech 3306 (2009) §2.5 "Marker chunk"
+const MARKER_ENTRY_VALID_FLAG : u32 = 0x1;
+#[allow(dead_code)]
+const MARKER_ENTRY_BYTE_OFFSET_VALID_FLAG : u32 = 0x2;
+#[allow(dead_code)]
+const MARKER_ENTRY_INTRA_SMPL_OFFSET_VALID_FLAG : u32 = 0x4;
+const MARKER_ENTRY_LABEL_TEXT_VALID_FLAG : u32 = 0x8;
+const MARKER_ENTRY_LABEL_CHUNK_IDENTIFIER_VALID_FLAG : u32 = 0x10;
+
+const MARKER_ENTRY_LENGTH : usize = 320;
+
+impl RawMarkerEntry {
+
+    fn write_to(entries : &[Self]) -> Vec<u8> {
+        let mut writer = Cursor::new(vec![0u8; 0]);
+
+        for entry in entries.iter() {
+            writer.write_u32::<LittleEndian>(entry.flags).unwrap();
+            writer.write_u64::<LittleEndian>(entry.sample_offset).unwrap();
+            writer.write_u64::<LittleEndian>(entry.byte_offset).unwrap();
+            writer.write_u64::<LittleEndian>(entry.intra_sample_offset).unwrap();
+            writer.write_all(&entry.label_text).unwrap();
+            writer.write_u32::<LittleEndian>(entry.label_chunk_identifier).unwrap();
+            writer.write_all(&entry.vendor_and_product).unwrap();
+            for user_data in entry.user_data.iter() {
+                writer.write_u32::<LittleEndian>(*user_data).unwrap();
+            }
+        }
+
+        writer.into_inner()
+    }
+
+    fn read_from(data : &[u8]) -> Result<Vec<Self>, Error> {
+        let mut rdr = Cursor::new(data);
+        let mut retval : Vec<Self> = vec![];
+
+        for _ in 0..(data.len() / MARKER_ENTRY_LENGTH) {
+            let flags = rdr.read_u32::<LittleEndian>()?;
+            let sample_offset = rdr.read_u64::<LittleEndian>()?;
+            let byte_offset = rdr.read_u64::<LittleEndian>()?;
+            let intra_sample_offset = rdr.read_u64::<LittleEndian>()?;
+            let mut label_text = [0u8; 256];
+            rdr.read_exact(&mut label_text)?;
+            let label_chunk_identifier = rdr.read_u32::<LittleEndian>()?;
+            let mut vendor_and_product = [0u8; 16];
+            rdr.read_exact(&mut vendor_and_product)?;
+            let mut user_data = [0u32; 4];
+            for field in user_data.iter_mut() {
+                *field = rdr.read_u32::<LittleEndian>()?;
+            }
+
+            retval.push( Self { flags, sample_offset, byte_offset, intra_sample_offset, 
+                label_text, label_chunk_identifier, vendor_and_product, user_data } );
+        }
+
+        Ok( retval )
+    }
+}
+
 trait AdtlMemberSearch {
     fn labels_for_cue_point(&self, id: u32) -> Vec<&RawLabel>;
     fn notes_for_cue_point(&self, id : u32) -> Vec<&RawNote>;
@@ -264,88 +324,332 @@ impl AdtlMemberSearch for Vec<RawAdtlMember> {
 
 /// A cue point recorded in the `cue` and `adtl` metadata.
 /// 
+/// Every field of the cue point record and its associated `adtl` labels,
+/// notes and labeled texts is preserved, so cues read from a file can be 
+/// written back to another without loss.
+/// 
+/// ```
+/// use bwavfile::Cue;
+/// 
+/// let marker = Cue::marker(1, 48000, "Marker 1");
+/// assert_eq!(marker.label(), Some("Marker 1"));
+/// assert_eq!(marker.length(), None);
+/// 
+/// let region = Cue::region(2, 96000, 24000, "Region 1");
+/// assert_eq!(region.length(), Some(24000));
+/// ```
+/// 
 /// ## Resources
 /// - [Cue list, label and other metadata](https://sites.google.com/site/musicgapi/technical-documents/wav-file-format#smpl)
 /// 
 /// ### Not Implemented
 /// - [EBU 3285 Supplement 2](https://tech.ebu.ch/docs/tech/tech3285s2.pdf) (July 2001): Quality chunk and cuesheet
+#[derive(Debug, Clone, PartialEq)]
 pub struct Cue {
 
+    /// The cue point ID, which links the cue to its `adtl` annotations
+    pub ident : u32,
+
     /// The time of this marker
-    pub frame : u32,
+    /// 
+    /// Positions past 2^32 frames are recorded in an RF64 `r64m` chunk, and
+    /// saturate at 0xFFFFFFFF in the `cue ` chunk.
+    pub frame : u64,
+
+    /// The chunk containing the cue point, usually `data`
+    pub chunk_id : FourCC,
+
+    /// Position of the start of the chunk containing the cue point, zero for
+    /// `data`
+    pub chunk_start : u32,
+
+    /// Position of the start of the block containing the cue point, zero for
+    /// uncompressed audio data
+    pub block_start : u32,
+
+    /// Offset of the cue point within the block, for cue points in other
+    /// chunks than `data`
+    ///
+    /// For cue points in `data` this is the same as `frame`, and `frame` is
+    /// written in its place.
+    pub sample_offset : u32,
+
+    /// Text "labels"/names of this marker, in the order they appear
+    pub labels : Vec<String>,
+
+    /// Text "notes"/comments of this marker, in the order they appear
+    pub notes : Vec<String>,
+
+    /// Labeled text records of this marker, including the `rgn ` record
+    /// that gives the length of a range
+    pub labeled_texts : Vec<LabeledText>
+}
 
-    /// The length of this marker, if it is a range
-    pub length : Option<u32>,
+/// A labeled text (`ltxt`) record attached to a cue point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabeledText {
+
+    /// The length of the labeled section, in frames
+    pub frame_length : u32,
+
+    /// The purpose of the text, `rgn ` for a region
+    pub purpose : FourCC,
+
+    /// Country code
+    pub country : u16,
+
+    /// Language code
+    pub language : u16,
 
-    /// The text "label"/name of this marker if provided
-    pub label : Option<String>,
+    /// Dialect code
+    pub dialect : u16,
 
-    /// The text "note"/comment of this marker if provided
-    pub note : Option<String>
+    /// Windows code page of the text. Zero and 65001 (UTF-8) are both
+    /// read and written as UTF-8.
+    pub code_page : u16,
+
+    /// The text, if present
+    pub text : Option<String>
 }
 
+/// The labeled text purpose of a region
+pub const REGION_PURPOSE : FourCC = FourCC::make(b"rgn ");
+
+impl LabeledText {
+
+    /// A `rgn ` labeled text with the given length
+    pub fn region(frame_length: u32) -> Self {
+        LabeledText {
+            frame_length,
+            purpose : REGION_PURPOSE,
+            country : 0,
+            language : 0,
+            dialect : 0,
+            code_page : 0,
+            text : None
+        }
+    }
+}
 
-fn convert_to_cue_string(buffer : &[u8]) -> String {
-    let trimmed : Vec<u8> = buffer.iter().take_while(|c| **c != 0 as u8).cloned().collect();
-    ASCII.decode(&trimmed, DecoderTrap::Ignore).expect("Error decoding text")
+fn trim_cue_string(buffer : &[u8]) -> &[u8] {
+    let end = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+    &buffer[..end]
+}
+
+fn convert_to_cue_string(buffer : &[u8], code_page : u16) -> String {
+    let trimmed = trim_cue_string(buffer);
+    match code_page_encoding(code_page) {
+        Some(encoding) => encoding.decode(trimmed, DecoderTrap::Replace)
+            .unwrap_or_else(|_| String::from_utf8_lossy(trimmed).into_owned()),
+        None => String::from_utf8_lossy(trimmed).into_owned()
+    }
 }
 
-fn convert_from_cue_string(val : &str) -> Vec<u8> {
-    ASCII.encode(&val, EncoderTrap::Ignore).expect("Error encoding text")
+fn convert_from_cue_string(val : &str, code_page : u16) -> Vec<u8> {
+    let mut buf = match code_page_encoding(code_page) {
+        Some(encoding) => encoding.encode(val, EncoderTrap::Replace)
+            .unwrap_or_else(|_| val.as_bytes().to_vec()),
+        None => val.as_bytes().to_vec()
+    };
+    buf.push(0);
+    buf
 }
 
 impl Cue {
 
+    /// A new cue point at `frame` with no annotations
+    pub fn new(ident: u32, frame: u64) -> Self {
+        Cue {
+            ident,
+            frame,
+            chunk_id : DATA_SIG,
+            chunk_start : 0,
+            block_start : 0,
+            sample_offset : frame.min(u32::MAX as u64) as u32,
+            labels : vec![],
+            notes : vec![],
+            labeled_texts : vec![]
+        }
+    }
+
+    /// A new cue point at `frame` with a label
+    pub fn marker(ident: u32, frame: u64, label: &str) -> Self {
+        let mut cue = Self::new(ident, frame);
+        cue.labels.push(String::from(label));
+        cue
+    }
+
+    /// A new region at `frame`, `length` frames long, with a label
+    pub fn region(ident: u32, frame: u64, length: u32, label: &str) -> Self {
+        let mut cue = Self::marker(ident, frame, label);
+        cue.labeled_texts.push(LabeledText::region(length));
+        cue
+    }
+
+    /// The first label of this marker, if provided
+    pub fn label(&self) -> Option<&str> {
+        self.labels.first().map(|s| s.as_str())
+    }
+
+    /// The first note of this marker, if provided
+    pub fn note(&self) -> Option<&str> {
+        self.notes.first().map(|s| s.as_str())
+    }
+
+    /// The length of this marker, if it is a range
+    /// 
+    /// This is the length given by the first `rgn ` labeled text.
+    pub fn length(&self) -> Option<u64> {
+        self.labeled_texts.iter()
+            .find(|t| t.purpose == REGION_PURPOSE)
+            .map(|t| t.frame_length as u64)
+    }
+
+    /// Compile a list of `Cue`s into the content of a `cue ` chunk and the
+    /// sub-chunks of an `adtl` `LIST`.
+    pub(crate) fn compile_to_chunks(cues : &[Cue]) -> (Vec<u8>, Vec<ListFormItem>) {
+        let (raw_cues, raw_adtl) = Self::compile_to(cues);
+        (RawCue::write_to(raw_cues), RawAdtlMember::compile_adtl(&raw_adtl))
+    }
+
     /// Take a list of `Cue`s and convert it into `RawCue` and `RawAdtlMember`s
     fn compile_to(cues : &[Cue]) -> (Vec<RawCue>, Vec<RawAdtlMember>) {
-        cues.iter().enumerate()
-            .map(|(n, cue)| {
-                let raw_cue = RawCue {
-                    cue_point_id: n as u32,
-                    frame : cue.frame,
-                    chunk_id: DATA_SIG,
-                    chunk_start: 0,
-                    block_start: 0,
-                    frame_offset: cue.frame
-                };
+        let mut raw_cues : Vec<RawCue> = vec![];
+        let mut raw_adtl : Vec<RawAdtlMember> = vec![];
+
+        for cue in cues {
+            let frame = cue.frame.min(u32::MAX as u64) as u32;
+            raw_cues.push( RawCue {
+                cue_point_id : cue.ident,
+                frame,
+                chunk_id : cue.chunk_id,
+                chunk_start : cue.chunk_start,
+                block_start : cue.block_start,
+                frame_offset : if cue.chunk_id == DATA_SIG { frame } else { cue.sample_offset }
+            });
+
+            for label in cue.labels.iter() {
+                raw_adtl.push( RawAdtlMember::Label( RawLabel {
+                    cue_point_id : cue.ident,
+                    text : convert_from_cue_string(label, 0)
+                }));
+            }
 
-                let raw_label = cue.label.as_ref().map(|val| {
-                    RawLabel {
-                        cue_point_id : n as u32,
-                        text: convert_from_cue_string(&val)
-                    }
-                });
-
-                let raw_note = cue.note.as_ref().map(|val| {
-                    RawNote { 
-                        cue_point_id: n as u32,
-                        text : convert_from_cue_string(&val)
-                    }
-                });
-
-                let raw_ltxt = cue.length.map(|val| {
-                    RawLtxt {
-                        cue_point_id : n as u32,
-                        frame_length : val,
-                        purpose : FourCC::make(b"rgn "),
-                        country : 0,
-                        language : 0,
-                        dialect : 0,
-                        code_page : 0,
-                        text : None
-                    }
-                });
-
-                (raw_cue, raw_label, raw_note, raw_ltxt)
-            })
-            .fold((Vec::<RawCue>::new(), Vec::<RawAdtlMember>::new()),
-            |(mut cues, mut adtls) , (cue, label, note, ltxt)| {
+            for note in cue.notes.iter() {
+                raw_adtl.push( RawAdtlMember::Note( RawNote {
+                    cue_point_id : cue.ident,
+                    text : convert_from_cue_string(note, 0)
+                }));
+            }
+
+            for ltxt in cue.labeled_texts.iter() {
+                raw_adtl.push( RawAdtlMember::LabeledText( RawLtxt {
+                    cue_point_id : cue.ident,
+                    frame_length : ltxt.frame_length,
+                    purpose : ltxt.purpose,
+                    country : ltxt.country,
+                    language : ltxt.language,
+                    dialect : ltxt.dialect,
+                    code_page : ltxt.code_page,
+                    text : ltxt.text.as_ref().map(|t| convert_from_cue_string(t, ltxt.code_page))
+                }));
+            }
+        }
+
+        (raw_cues, raw_adtl)
+    }
+
+    /// True if any of `cues` is past the range of a `cue ` chunk, and must
+    /// also be recorded in an `r64m` chunk.
+    pub(crate) fn requires_rf64_markers(cues : &[Cue]) -> bool {
+        cues.iter().any(|cue| cue.frame > u32::MAX as u64)
+    }
+
+    /// Compile a list of `Cue`s into the content of an `r64m` chunk.
+    /// 
+    /// Each entry has the 64-bit position of its cue, the cue's first label 
+    /// and the cue's ident, which links it to the `cue ` and `adtl` records.
+    pub(crate) fn compile_to_rf64_markers(cues : &[Cue]) -> Vec<u8> {
+        let entries : Vec<RawMarkerEntry> = cues.iter().map(|cue| {
+            let mut flags = MARKER_ENTRY_VALID_FLAG | MARKER_ENTRY_LABEL_CHUNK_IDENTIFIER_VALID_FLAG;
+            let mut label_text = [0u8; 256];
+
+            if let Some(label) = cue.label() {
+                // truncate to 255 bytes, on a character boundary
+                let mut end = label.len().min(255);
+                while !label.is_char_boundary(end) { end -= 1; }
+                label_text[..end].copy_from_slice(&label.as_bytes()[..end]);
+                flags |= MARKER_ENTRY_LABEL_TEXT_VALID_FLAG;
+            }
+
+            RawMarkerEntry {
+                flags,
+                sample_offset : cue.frame,
+                byte_offset : 0,
+                intra_sample_offset : 0,
+                label_text,
+                label_chunk_identifier : cue.ident,
+                vendor_and_product : [0u8; 16],
+                user_data : [0u32; 4]
+            }
+        }).collect();
+
+        RawMarkerEntry::write_to(&entries)
+    }
+
+    /// Merge the markers of an `r64m` chunk into `cues`.
+    /// 
+    /// Markers linked to a cue by its ident give the cue its 64-bit position,
+    /// and any others are added to `cues` as new cue points.
+    pub(crate) fn merge_rf64_markers(cues : &mut Vec<Cue>, r64m_chunk : &[u8]) -> Result<(), Error> {
+        let entries = RawMarkerEntry::read_from(r64m_chunk)?;
+
+        for entry in entries.iter() {
+            if entry.flags & MARKER_ENTRY_VALID_FLAG == 0 {
+                continue;
+            }
+
+            let label = if entry.flags & MARKER_ENTRY_LABEL_TEXT_VALID_FLAG != 0 {
+                Some( convert_to_cue_string(&entry.label_text, 0) )
+            } else {
+                None
+            };
+
+            let linked = if entry.flags & MARKER_ENTRY_LABEL_CHUNK_IDENTIFIER_VALID_FLAG != 0 {
+                cues.iter_mut().find(|cue| cue.ident == entry.label_chunk_identifier)
+            } else {
+                None
+            };
+
+            if let Some(cue) = linked {
+                cue.frame = entry.sample_offset;
+                if cue.labels.is_empty() {
+                    cue.labels.extend(label);
+                }
+            } else {
+                let ident = if entry.flags & MARKER_ENTRY_LABEL_CHUNK_IDENTIFIER_VALID_FLAG != 0 {
+                    entry.label_chunk_identifier
+                } else {
+                    Self::unused_ident(cues)
+                };
+                let mut cue = Cue::new(ident, entry.sample_offset);
+                cue.labels.extend(label);
                 cues.push(cue);
-                label.map(|l| adtls.push( RawAdtlMember::Label(l)));
-                note.map(|n| adtls.push(RawAdtlMember::Note(n)));
-                ltxt.map(|m| adtls.push(RawAdtlMember::LabeledText(m)));
-                (cues, adtls)
+            }
+        }
+
+        Ok(())
+    }
+
+    /// An ident not used by any of `cues`, one more than the largest if
+    /// possible.
+    fn unused_ident(cues : &[Cue]) -> u32 {
+        match cues.iter().map(|cue| cue.ident).max() {
+            None => 1,
+            Some(max) => max.checked_add(1).unwrap_or_else(|| {
+                (1..u32::MAX).find(|i| cues.iter().all(|cue| cue.ident != *i)).unwrap_or(0)
             })
+        }
     }
 
     pub fn collect_from(cue_chunk : &[u8], adtl_chunk : Option<&[u8]>) -> Result<Vec<Cue>, Error> {
@@ -357,33 +661,151 @@ impl Cue {
         } else {
             raw_adtl = vec![];
         }
-        
 
         Ok( 
             raw_cues.iter()
             .map(|i| {
                 Cue {
-                    //ident : i.cue_point_id,
-                    frame : i.frame,
-                    length: {
-                        raw_adtl.ltxt_for_cue_point(i.cue_point_id).first()
-                        .filter(|x| x.purpose == FourCC::make(b"rgn "))
-                        .map(|x| x.frame_length)
-                    },
-                    label: {
-                        raw_adtl.labels_for_cue_point(i.cue_point_id).iter()
-                            .map(|s| convert_to_cue_string(&s.text))
-                            .next()
-                    },
-                    note : {
-                        raw_adtl.notes_for_cue_point(i.cue_point_id).iter()
-                            //.filter_map(|x| str::from_utf8(&x.text).ok())
-                            .map(|s| convert_to_cue_string(&s.text))
-                            .next()
-                    }
+                    ident : i.cue_point_id,
+                    frame : i.frame as u64,
+                    chunk_id : i.chunk_id,
+                    chunk_start : i.chunk_start,
+                    block_start : i.block_start,
+                    sample_offset : i.frame_offset,
+                    labels : raw_adtl.labels_for_cue_point(i.cue_point_id).iter()
+                        .map(|s| convert_to_cue_string(&s.text, 0))
+                        .collect(),
+                    notes : raw_adtl.notes_for_cue_point(i.cue_point_id).iter()
+                        .map(|s| convert_to_cue_string(&s.text, 0))
+                        .collect(),
+                    labeled_texts : raw_adtl.ltxt_for_cue_point(i.cue_point_id).iter()
+                        .map(|x| LabeledText {
+                            frame_length : x.frame_length,
+                            purpose : x.purpose,
+                            country : x.country,
+                            language : x.language,
+                            dialect : x.dialect,
+                            code_page : x.code_page,
+                            text : x.text.as_ref().map(|t| convert_to_cue_string(t, x.code_page))
+                        })
+                        .collect()
                 }
             }).collect() 
         )
     }
 
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_cue_round_trip() {
+    let mut region = Cue::region(2, 200, 50, "Region");
+    region.notes.push(String::from("Region comment"));
+    region.notes.push(String::from("Another comment"));
+    region.labeled_texts.push(LabeledText {
+        frame_length : 0,
+        purpose : FourCC::make(b"tran"),
+        country : 49,
+        language : 7,
+        dialect : 1,
+        code_page : 1252,
+        text : Some(String::from("Übersetzung"))
+    });
+
+    let cues = vec![
+        Cue::marker(1, 100, "Marker 1"),
+        region,
+        Cue::new(7, 300),
+    ];
+
+    let (cue_chunk, adtl) = Cue::compile_to_chunks(&cues);
+    assert_eq!(cue_chunk.len(), 4 + 24 * 3);
+    assert_eq!(adtl.len(), 6);
+
+    let adtl_chunk = super::list_form::compile_list_form(super::fourcc::ADTL_SIG, &adtl).unwrap();
+    let read = Cue::collect_from(&cue_chunk, Some(&adtl_chunk)).unwrap();
+    assert_eq!(read, cues);
+}
+
+#[test]
+fn test_cue_sample_offset_follows_frame() {
+    let mut moved = Cue::marker(1, 1000, "Moved");
+    moved.frame = 5000;
+    let mut other = Cue::new(2, 100);
+    other.chunk_id = FourCC::make(b"slnt");
+    other.sample_offset = 20;
+
+    let (cue_chunk, _) = Cue::compile_to_chunks(&[moved, other]);
+    let read = Cue::collect_from(&cue_chunk, None).unwrap();
+    assert_eq!(read[0].sample_offset, 5000);
+    assert_eq!(read[1].frame, 100);
+    assert_eq!(read[1].sample_offset, 20);
+}
+
+#[test]
+fn test_cue_string_code_pages() {
+    assert_eq!(convert_from_cue_string("é", 1252), [0xE9, 0]);
+    assert_eq!(convert_from_cue_string("é", 0), [0xC3, 0xA9, 0]);
+    assert_eq!(convert_to_cue_string(&[0xE9, 0, 0x41], 1252), "é");
+    assert_eq!(convert_to_cue_string(&[0xC3, 0xA9], 65001), "é");
+}
+
+#[test]
+fn test_rf64_markers() {
+    let far = 0x1_2345_6789u64;
+    let cues = vec![
+        Cue::marker(1, 100, "Near"),
+        Cue::marker(2, far, "Far"),
+    ];
+    assert!(Cue::requires_rf64_markers(&cues));
+
+    let (cue_chunk, adtl) = Cue::compile_to_chunks(&cues);
+    let r64m_chunk = Cue::compile_to_rf64_markers(&cues);
+    assert_eq!(r64m_chunk.len(), MARKER_ENTRY_LENGTH * 2);
+
+    let adtl_chunk = super::list_form::compile_list_form(super::fourcc::ADTL_SIG, &adtl).unwrap();
+    let mut read = Cue::collect_from(&cue_chunk, Some(&adtl_chunk)).unwrap();
+    assert_eq!(read[1].frame, u32::MAX as u64);
+
+    Cue::merge_rf64_markers(&mut read, &r64m_chunk).unwrap();
+    assert_eq!(read.len(), 2);
+    assert_eq!(read[1].frame, far);
+    assert_eq!(read[1].labels, ["Far"]);
+
+    let unlinked = Cue::compile_to_rf64_markers(&[Cue::marker(9, far, "Unlinked")]);
+    let mut only_r64m = vec![];
+    Cue::merge_rf64_markers(&mut only_r64m, &unlinked).unwrap();
+    assert_eq!(only_r64m, [Cue::marker(9, far, "Unlinked")]);
+}
+
+#[test]
+fn test_rf64_markers_from_other_writers() {
+    let entry = |flags: u32, sample_offset: u64, label: &[u8], ident: u32| {
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&flags.to_le_bytes());
+        bytes.extend_from_slice(&sample_offset.to_le_bytes());
+        bytes.extend_from_slice(&0xFFFF_FFFF_FFFF_FFFFu64.to_le_bytes()); // byte offset, not valid
+        bytes.extend_from_slice(&[0u8; 8]);
+        let mut label_text = [0u8; 256];
+        label_text[..label.len()].copy_from_slice(label);
+        bytes.extend_from_slice(&label_text);
+        bytes.extend_from_slice(&ident.to_le_bytes());
+        bytes.extend_from_slice(&[0u8; 16 + 16]);
+        assert_eq!(bytes.len(), MARKER_ENTRY_LENGTH);
+        bytes
+    };
+
+    let mut r64m_chunk = vec![];
+    r64m_chunk.extend(entry(0x1 | 0x8 | 0x10, 0x2_0000_0000, b"Linked", 4));
+    r64m_chunk.extend(entry(0x1 | 0x8, 0x3_0000_0000, b"Unlinked", 0));
+    r64m_chunk.extend(entry(0x8, 0x4_0000_0000, b"Not valid", 0));
+
+    let mut cues = vec![Cue::new(4, u32::MAX as u64), Cue::new(u32::MAX, 10)];
+    Cue::merge_rf64_markers(&mut cues, &r64m_chunk).unwrap();
+
+    assert_eq!(cues.len(), 3);
+    assert_eq!(cues[0].frame, 0x2_0000_0000);
+    assert_eq!(cues[0].labels, ["Linked"]);
+    assert_eq!(cues[2].ident, 1);
+    assert_eq!(cues[2].frame, 0x3_0000_0000);
+    assert_eq!(cues[2].labels, ["Unlinked"]);
+}
@@ -0,0 +1,325 @@
+use super::errors::Error;
+
+use byteorder::{WriteBytesExt, ReadBytesExt, LittleEndian};
+
+use std::io::{Cursor, Read, Write};
+
+/// Dolby metadata, the content of a `dbmd` chunk.
+///
+/// The chunk is a version number followed by a sequence of metadata
+/// segments, each identified by a segment ID. The Dolby E and Dolby Atmos
+/// segments are decoded. Any other segment, including Dolby Digital Plus, is
+/// kept as-is, so metadata read from a file can be written back unaltered.
+///
+/// ```
+/// use bwavfile::{DolbyMetadata, DolbyMetadataSegment, DolbyAtmosMetadata};
+///
+/// let mut atmos = DolbyAtmosMetadata::default();
+/// atmos.content_creation_tool = String::from("Renderer");
+/// atmos.content_creation_tool_version = (1, 2, 3);
+///
+/// let dbmd = DolbyMetadata {
+///     version: 0x01000006,
+///     segments: vec![
+///         DolbyMetadataSegment::DolbyAtmos(atmos),
+///         DolbyMetadataSegment::Other { id: 10, payload: vec![0u8; 4] }
+///     ]
+/// };
+///
+/// let read = DolbyMetadata::parse(&dbmd.to_bytes()).unwrap();
+/// assert_eq!(read, dbmd);
+/// ```
+///
+/// ## Resources
+/// - [SMPTE RDD 6:2008](https://ieeexplore.ieee.org/document/7290514), "Description and Guide to the Use of the Dolby E Audio Metadata Serial Bitstream"
+/// - [Dolby Atmos Master ADM Profile](https://professional.dolby.com/siteassets/pdfs/dolby-atmos-master-adm-profile_v1.0.pdf)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DolbyMetadata {
+    /// Metadata version, one byte each for the major, minor, revision and
+    /// build numbers, most significant first, e.g. `0x01000006` is 1.0.0.6
+    pub version: u32,
+
+    /// Metadata segments, in the order they appear in the chunk
+    pub segments: Vec<DolbyMetadataSegment>
+}
+
+/// A `dbmd` metadata segment.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DolbyMetadataSegment {
+
+    /// Dolby E metadata, segment ID 1
+    DolbyE(DolbyEMetadata),
+
+    /// Dolby Atmos metadata, segment ID 9
+    DolbyAtmos(DolbyAtmosMetadata),
+
+    /// Any other segment, like Dolby Digital Plus metadata (ID 7), Dolby
+    /// Atmos supplemental metadata (ID 10) or audio info (ID 8)
+    Other { id: u8, payload: Vec<u8> }
+}
+
+/// Dolby E metadata segment.
+///
+/// Fields not decoded are kept, and written back unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DolbyEMetadata {
+    /// The Dolby E program configuration, the arrangement of programs and
+    /// channels in the Dolby E stream. e.g. 0 is "5.1+2", 2 is "4x2"
+    pub program_config: u8,
+
+    /// The Dolby E video frame rate code, 1 through 5 for 23.98, 24, 25,
+    /// 29.97 and 30 frames per second
+    pub frame_rate_code: u8,
+
+    payload: Vec<u8>
+}
+
+/// Dolby Atmos metadata segment.
+///
+/// Fields not decoded are kept, and written back unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DolbyAtmosMetadata {
+    /// The application that created the Dolby Atmos content
+    pub content_creation_tool: String,
+
+    /// The content creation tool's major, minor and micro version
+    pub content_creation_tool_version: (u8, u8, u8),
+
+    /// The warp mode used for rendering the content for home theater
+    /// layouts: 0 is normal, 1 is warping, 2 is downmix Pro Logic IIx, 3 is
+    /// downmix Lo/Ro, 4 is not indicated.
+    pub warp_mode: u8,
+
+    payload: Vec<u8>
+}
+
+const DOLBY_E_SEGMENT_ID: u8 = 1;
+const DOLBY_ATMOS_SEGMENT_ID: u8 = 9;
+
+const DOLBY_E_MIN_LENGTH: usize = 2;
+
+const ATMOS_TOOL_OFFSET: usize = 32;
+const ATMOS_TOOL_LENGTH: usize = 64;
+const ATMOS_VERSION_OFFSET: usize = 96;
+const ATMOS_WARP_MODE_OFFSET: usize = 152;
+const ATMOS_SEGMENT_LENGTH: usize = 248;
+
+impl Default for DolbyEMetadata {
+    fn default() -> Self {
+        DolbyEMetadata { program_config: 0, frame_rate_code: 0, payload: vec![0u8; DOLBY_E_MIN_LENGTH] }
+    }
+}
+
+impl DolbyEMetadata {
+
+    fn from_payload(mut payload: Vec<u8>) -> Self {
+        let program_config = payload[0] >> 2;
+        let frame_rate_code = ((payload[0] & 0x03) << 2) | (payload[1] >> 6);
+
+        // only the fields that aren't decoded are kept
+        payload[0] = 0;
+        payload[1] &= 0x3F;
+        DolbyEMetadata { program_config, frame_rate_code, payload }
+    }
+
+    fn to_payload(&self) -> Vec<u8> {
+        let mut payload = self.payload.clone();
+        payload[0] = (self.program_config << 2) | ((self.frame_rate_code >> 2) & 0x03);
+        payload[1] = ((self.frame_rate_code & 0x03) << 6) | (payload[1] & 0x3F);
+        payload
+    }
+}
+
+impl Default for DolbyAtmosMetadata {
+    fn default() -> Self {
+        DolbyAtmosMetadata {
+            content_creation_tool: String::new(),
+            content_creation_tool_version: (0, 0, 0),
+            warp_mode: 0,
+            payload: vec![0u8; ATMOS_SEGMENT_LENGTH]
+        }
+    }
+}
+
+impl DolbyAtmosMetadata {
+
+    fn from_payload(mut payload: Vec<u8>) -> Self {
+        let tool = &payload[ATMOS_TOOL_OFFSET..ATMOS_TOOL_OFFSET + ATMOS_TOOL_LENGTH];
+        let tool_length = tool.iter().position(|b| *b == 0).unwrap_or(ATMOS_TOOL_LENGTH);
+        let content_creation_tool = String::from_utf8_lossy(&tool[..tool_length]).into_owned();
+        let version = &payload[ATMOS_VERSION_OFFSET..ATMOS_VERSION_OFFSET + 3];
+        let content_creation_tool_version = (version[0], version[1], version[2]);
+        let warp_mode = payload[ATMOS_WARP_MODE_OFFSET] & 0x07;
+
+        // only the fields that aren't decoded are kept
+        payload[ATMOS_TOOL_OFFSET..ATMOS_VERSION_OFFSET + 3].iter_mut().for_each(|b| *b = 0);
+        payload[ATMOS_WARP_MODE_OFFSET] &= 0xF8;
+        DolbyAtmosMetadata { content_creation_tool, content_creation_tool_version, warp_mode, payload }
+    }
+
+    fn to_payload(&self) -> Vec<u8> {
+        let mut payload = self.payload.clone();
+
+        let mut tool = self.content_creation_tool.as_bytes().to_vec();
+        tool.truncate(ATMOS_TOOL_LENGTH - 1); // always NUL-terminated
+        tool.resize(ATMOS_TOOL_LENGTH, 0);
+        payload[ATMOS_TOOL_OFFSET..ATMOS_TOOL_OFFSET + ATMOS_TOOL_LENGTH].copy_from_slice(&tool);
+
+        let (major, minor, micro) = self.content_creation_tool_version;
+        payload[ATMOS_VERSION_OFFSET] = major;
+        payload[ATMOS_VERSION_OFFSET + 1] = minor;
+        payload[ATMOS_VERSION_OFFSET + 2] = micro;
+        payload[ATMOS_WARP_MODE_OFFSET] = (payload[ATMOS_WARP_MODE_OFFSET] & 0xF8) | (self.warp_mode & 0x07);
+        payload
+    }
+}
+
+/// The two's complement of the sum of the size and payload bytes.
+fn segment_checksum(payload: &[u8]) -> u8 {
+    let size = (payload.len() as u16).to_le_bytes();
+    let sum = size.iter().chain(payload.iter())
+        .fold(0u8, |acc, b| acc.wrapping_add(*b));
+    (!sum).wrapping_add(1)
+}
+
+impl DolbyMetadataSegment {
+
+    /// The segment ID
+    pub fn id(&self) -> u8 {
+        match self {
+            DolbyMetadataSegment::DolbyE(_) => DOLBY_E_SEGMENT_ID,
+            DolbyMetadataSegment::DolbyAtmos(_) => DOLBY_ATMOS_SEGMENT_ID,
+            DolbyMetadataSegment::Other { id, .. } => *id
+        }
+    }
+
+    fn from_payload(id: u8, payload: Vec<u8>) -> Self {
+        match id {
+            DOLBY_E_SEGMENT_ID if payload.len() >= DOLBY_E_MIN_LENGTH =>
+                DolbyMetadataSegment::DolbyE(DolbyEMetadata::from_payload(payload)),
+            DOLBY_ATMOS_SEGMENT_ID if payload.len() >= ATMOS_SEGMENT_LENGTH =>
+                DolbyMetadataSegment::DolbyAtmos(DolbyAtmosMetadata::from_payload(payload)),
+            _ => DolbyMetadataSegment::Other { id, payload }
+        }
+    }
+
+    fn to_payload(&self) -> Vec<u8> {
+        match self {
+            DolbyMetadataSegment::DolbyE(e) => e.to_payload(),
+            DolbyMetadataSegment::DolbyAtmos(atmos) => atmos.to_payload(),
+            DolbyMetadataSegment::Other { payload, .. } => payload.clone()
+        }
+    }
+}
+
+impl DolbyMetadata {
+
+    /// Parse the content of a `dbmd` chunk.
+    ///
+    /// Returns `Error::DolbyMetadataChecksumMismatch` if a segment's
+    /// checksum is incorrect.
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let mut cursor = Cursor::new(data);
+        let version = cursor.read_u32::<LittleEndian>()?;
+        let mut segments = vec![];
+
+        loop {
+            let id = cursor.read_u8()?;
+            if id == 0 { break; }
+
+            let size = cursor.read_u16::<LittleEndian>()? as usize;
+            let mut payload = vec![0u8; size];
+            cursor.read_exact(&mut payload)?;
+            let checksum = cursor.read_u8()?;
+            if checksum != segment_checksum(&payload) {
+                return Err(Error::DolbyMetadataChecksumMismatch { segment_id: id });
+            }
+
+            segments.push(DolbyMetadataSegment::from_payload(id, payload));
+        }
+
+        Ok( DolbyMetadata { version, segments } )
+    }
+
+    /// The content of a `dbmd` chunk for this metadata.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut cursor = Cursor::new(vec![0u8; 0]);
+        cursor.write_u32::<LittleEndian>(self.version).unwrap();
+        for segment in self.segments.iter() {
+            let payload = segment.to_payload();
+            cursor.write_u8(segment.id()).unwrap();
+            cursor.write_u16::<LittleEndian>(payload.len() as u16).unwrap();
+            cursor.write_all(&payload).unwrap();
+            cursor.write_u8(segment_checksum(&payload)).unwrap();
+        }
+        cursor.write_u8(0).unwrap();
+        cursor.into_inner()
+    }
+
+    /// The Dolby Atmos segment, if present.
+    pub fn dolby_atmos(&self) -> Option<&DolbyAtmosMetadata> {
+        self.segments.iter().find_map(|s| match s {
+            DolbyMetadataSegment::DolbyAtmos(atmos) => Some(atmos),
+            _ => None
+        })
+    }
+
+    /// The Dolby E segment, if present.
+    pub fn dolby_e(&self) -> Option<&DolbyEMetadata> {
+        self.segments.iter().find_map(|s| match s {
+            DolbyMetadataSegment::DolbyE(e) => Some(e),
+            _ => None
+        })
+    }
+}
+
+#[test]
+fn test_dbmd_round_trip_preserves_payload() {
+    let mut atmos_payload = vec![0u8; ATMOS_SEGMENT_LENGTH];
+    atmos_payload[ATMOS_TOOL_OFFSET..ATMOS_TOOL_OFFSET + 4].copy_from_slice(b"Tool");
+    atmos_payload[ATMOS_VERSION_OFFSET..ATMOS_VERSION_OFFSET + 3].copy_from_slice(&[2, 4, 1]);
+    atmos_payload[ATMOS_WARP_MODE_OFFSET] = 0xA9; // reserved high bits, warp mode 1
+    atmos_payload[200] = 0x55;
+
+    let dolby_e_payload = vec![0x02 << 2, 3 << 6 | 0x15, 0xEE];
+
+    let mut data = vec![0x06, 0x00, 0x00, 0x01];
+    for (id, payload) in [(DOLBY_E_SEGMENT_ID, &dolby_e_payload), (DOLBY_ATMOS_SEGMENT_ID, &atmos_payload),
+        (7, &vec![0x2A, 0xC0, 0x01, 0xF1, 0xBB, 0x91, 0x7A, 0x33]), (10, &vec![1u8, 2, 3])].iter() {
+        data.push(*id);
+        data.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        data.extend_from_slice(payload);
+        data.push(segment_checksum(payload));
+    }
+    data.push(0);
+
+    let dbmd = DolbyMetadata::parse(&data).unwrap();
+    assert_eq!(dbmd.version, 0x01000006);
+    assert_eq!(dbmd.segments.len(), 4);
+
+    let e = dbmd.dolby_e().unwrap();
+    assert_eq!(e.program_config, 2);
+    assert_eq!(e.frame_rate_code, 3);
+
+    let atmos = dbmd.dolby_atmos().unwrap();
+    assert_eq!(atmos.content_creation_tool, "Tool");
+    assert_eq!(atmos.content_creation_tool_version, (2, 4, 1));
+    assert_eq!(atmos.warp_mode, 1);
+
+    assert_eq!(dbmd.segments[2].id(), 7);
+    assert!(matches!(dbmd.segments[2], DolbyMetadataSegment::Other { .. }));
+    assert_eq!(dbmd.segments[3], DolbyMetadataSegment::Other { id: 10, payload: vec![1, 2, 3] });
+    assert_eq!(dbmd.to_bytes(), data);
+}
+
+#[test]
+fn test_dbmd_checksum() {
+    // size 0x0002, payload 0x01 0x02: sum is 5, checksum is 0xFB
+    assert_eq!(segment_checksum(&[1, 2]), 0xFB);
+
+    let data = vec![0, 0, 0, 1, 8, 2, 0, 1, 2, 0xFA, 0];
+    match DolbyMetadata::parse(&data) {
+        Err(Error::DolbyMetadataChecksumMismatch { segment_id: 8 }) => (),
+        other => panic!("Expected checksum mismatch, got {:?}", other)
+    }
+}
@@ -0,0 +1,474 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write, Seek, SeekFrom, Cursor};
+
+use super::errors::Error;
+use super::fourcc::{FourCC, ReadFourCC, WriteFourCC, RIFF_SIG, RF64_SIG, DS64_SIG,
+    FMT__SIG, FACT_SIG, DATA_SIG, JUNK_SIG, FLLR_SIG, BEXT_SIG, IXML_SIG, AXML_SIG, LIST_SIG};
+use super::parser::{Parser, ChunkIteratorItem};
+use super::list_form::{ListFormItem, compile_list_form};
+use super::chunks::WriteBWaveChunks;
+use super::bext::Bext;
+use super::wavereader::WaveReader;
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+/// Length of the content of a `ds64` record with an empty chunk size table
+const DS64_MINIMUM_LENGTH: u64 = 28;
+
+/// A span of the file that a chunk can be written into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Extent {
+    /// File offset of the first byte of the span
+    start: u64,
+
+    /// Length of the span in bytes
+    length: u64,
+
+    /// The span runs to the end of the form, so it can grow or shrink
+    at_end: bool
+}
+
+fn padded(length: u64) -> u64 {
+    length + length % 2
+}
+
+fn is_filler(signature: FourCC) -> bool {
+    signature == JUNK_SIG || signature == FLLR_SIG
+}
+
+/// Edit the metadata of an existing Wave, Broadcast-WAV or RF64/BW64 file.
+///
+/// A `WaveEditor` replaces metadata chunks in a file without rewriting its
+/// audio data, so descriptions, iXML or ADM can be corrected on existing
+/// recordings in place.
+///
+/// When a chunk is written, the first chunk in the file with the same
+/// signature is replaced:
+///
+/// 1. If the new content fits in the old chunk, along with any `JUNK` or
+///    `FLLR` filler chunks that immediately follow it, it is written in place
+///    and any space left over is marked as `JUNK`.
+/// 2. Otherwise the old chunk is marked as `JUNK`, and the new chunk is
+///    written to the first filler chunk elsewhere in the file that can hold
+///    it, or to the end of the file.
+///
+/// If the file has no such chunk the new chunk is added the same way as in
+/// (2). The RIFF form length, or the `ds64` record of an RF64 file, is
+/// updated if the file grows. A RIFF file that grows past 4 GB is upgraded
+/// to RF64 if it has a `JUNK` reservation for a `ds64` record at its start,
+/// as `WaveWriter` creates.
+///
+/// The `fmt `, `fact`, `data` and `ds64` chunks describe the structure of the
+/// file and can't be edited.
+///
+/// ```
+/// use bwavfile::{WaveWriter, WaveReader, WaveEditor, WaveFmt};
+/// # use std::io::Cursor;
+///
+/// let mut cursor = Cursor::new(vec![0u8;0]);
+/// let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 24)).unwrap();
+/// w.write_ixml(b"<BWFXML><SCENE>1</SCENE></BWFXML>").unwrap();
+/// w.write_junk(256).unwrap();
+/// let mut frame_writer = w.audio_frame_writer().unwrap();
+/// frame_writer.write_integer_frames(&[0i32, 0i32]).unwrap();
+/// frame_writer.end().unwrap();
+///
+/// let mut editor = WaveEditor::new(&mut cursor).unwrap();
+/// editor.write_ixml(b"<BWFXML><SCENE>12A</SCENE><TAKE>3</TAKE></BWFXML>").unwrap();
+///
+/// let mut r = WaveReader::new(&mut cursor).unwrap();
+/// let mut buf = vec![];
+/// r.read_ixml(&mut buf).unwrap();
+/// assert_eq!(buf, b"<BWFXML><SCENE>12A</SCENE><TAKE>3</TAKE></BWFXML>");
+/// ```
+pub struct WaveEditor<F> where F: Read + Write + Seek {
+    inner: F,
+    chunks: Vec<ChunkIteratorItem>,
+    form_length: u64,
+    is_rf64: bool
+}
+
+impl WaveEditor<File> {
+
+    /// Open the file at `path` for editing.
+    pub fn open(path: &str) -> Result<Self, Error> {
+        let f = OpenOptions::new().read(true).write(true).open(path)?;
+        Self::new(f)
+    }
+}
+
+impl<F> WaveEditor<F> where F: Read + Write + Seek {
+
+    /// Wrap a stream in a new `WaveEditor`.
+    ///
+    /// The stream must contain a complete wave file, starting at the
+    /// beginning of its header.
+    pub fn new(mut inner: F) -> Result<Self, Error> {
+        let chunks = Parser::make(&mut inner)?.into_chunk_list()?;
+        inner.seek(SeekFrom::Start(0))?;
+        let is_rf64 = inner.read_fourcc()? != RIFF_SIG;
+        let form_length = if is_rf64 {
+            inner.seek(SeekFrom::Start(8 + 4 + 8))?;
+            inner.read_u64::<LittleEndian>()?
+        } else {
+            inner.read_u32::<LittleEndian>()? as u64
+        };
+        Ok( WaveEditor { inner, chunks, form_length, is_rf64 } )
+    }
+
+    /// Unwrap the inner stream.
+    pub fn into_inner(self) -> F {
+        self.inner
+    }
+
+    /// True if the file is RF64
+    pub fn is_rf64(&self) -> bool {
+        self.is_rf64
+    }
+
+    /// The chunks in the file, in the order they appear.
+    pub fn chunks(&self) -> &[ChunkIteratorItem] {
+        &self.chunks
+    }
+
+    /// A `WaveReader` over the file, to read its current metadata.
+    pub fn reader(&mut self) -> Result<WaveReader<&mut F>, Error> {
+        WaveReader::new(&mut self.inner)
+    }
+
+    /// Replace the chunk with signature `ident` with one with content
+    /// `data`, or add one if the file doesn't have one.
+    pub fn write_chunk(&mut self, ident: FourCC, data: &[u8]) -> Result<(), Error> {
+        self.replace(ident, None, data)
+    }
+
+    /// Replace the `LIST` chunk with form type `form` with one containing
+    /// `items`, or add one if the file doesn't have one.
+    pub fn write_list(&mut self, form: FourCC, items: &[ListFormItem]) -> Result<(), Error> {
+        let buf = compile_list_form(form, items)?;
+        self.replace(LIST_SIG, Some(form), &buf)
+    }
+
+    /// Replace the Broadcast-Wave metadata of the file.
+    pub fn write_broadcast_metadata(&mut self, bext: &Bext) -> Result<(), Error> {
+        let mut c = Cursor::new(vec![0u8; 0]);
+        c.write_bext(bext)?;
+        self.write_chunk(BEXT_SIG, &c.into_inner())
+    }
+
+    /// Replace the iXML metadata of the file.
+    pub fn write_ixml(&mut self, ixml: &[u8]) -> Result<(), Error> {
+        self.write_chunk(IXML_SIG, ixml)
+    }
+
+    /// Replace the axml/ADM metadata of the file.
+    pub fn write_axml(&mut self, axml: &[u8]) -> Result<(), Error> {
+        self.write_chunk(AXML_SIG, axml)
+    }
+
+    /// Remove the chunk with signature `ident`, by marking it as `JUNK`.
+    pub fn remove_chunk(&mut self, ident: FourCC) -> Result<(), Error> {
+        Self::check_editable(ident)?;
+        let chunk = self.chunks.iter()
+            .find(|c| c.signature == ident)
+            .ok_or(Error::ChunkMissing { signature: ident })?;
+        let extent = Extent { start: chunk.start - 8, length: 8 + padded(chunk.length), at_end: false };
+        self.write_filler(extent.start, extent.length)?;
+        self.reparse()
+    }
+
+    fn check_editable(ident: FourCC) -> Result<(), Error> {
+        if [FMT__SIG, FACT_SIG, DATA_SIG, DS64_SIG, JUNK_SIG, FLLR_SIG].contains(&ident) {
+            Err(Error::ChunkNotEditable { signature: ident })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn replace(&mut self, ident: FourCC, form: Option<FourCC>, data: &[u8]) -> Result<(), Error> {
+        Self::check_editable(ident)?;
+        assert!(data.len() < u32::MAX as usize);
+        let needed = 8 + padded(data.len() as u64);
+
+        let existing = self.chunks.iter()
+            .position(|c| c.signature == ident && (form.is_none() || c.form == form))
+            .map(|index| self.extent(index));
+
+        let extent = match existing {
+            Some(extent) if Self::fits(extent, needed) => extent,
+            Some(extent) => {
+                self.write_filler(extent.start, extent.length)?;
+                self.free_extent(needed)
+            },
+            None => self.free_extent(needed)
+        };
+
+        self.write_chunk_at(extent, ident, data)?;
+        self.reparse()
+    }
+
+    /// The extent of the chunk at `index` and any filler chunks following it
+    fn extent(&self, index: usize) -> Extent {
+        let start = self.chunks[index].start - 8;
+        let mut end = self.chunks[index].start + padded(self.chunks[index].length);
+        for chunk in self.chunks[index + 1..].iter() {
+            if is_filler(chunk.signature) && chunk.start - 8 == end {
+                end = chunk.start + padded(chunk.length);
+            } else {
+                break;
+            }
+        }
+        Extent { start, length: end - start, at_end: end >= self.form_end() }
+    }
+
+    /// The first filler extent that can hold a chunk `needed` bytes long,
+    /// or the end of the form if there isn't one.
+    ///
+    /// A `JUNK` chunk at the start of a RIFF file is a reservation for a
+    /// `ds64` record and is never used.
+    fn free_extent(&self, needed: u64) -> Extent {
+        (0..self.chunks.len())
+            .filter(|i| is_filler(self.chunks[*i].signature))
+            .filter(|i| self.is_rf64 || self.chunks[*i].start != 12 + 8)
+            .map(|i| self.extent(i))
+            .find(|extent| Self::fits(*extent, needed))
+            .unwrap_or(Extent { start: padded(self.form_end()), length: 0, at_end: true })
+    }
+
+    fn fits(extent: Extent, needed: u64) -> bool {
+        extent.at_end || extent.length == needed || extent.length >= needed + 8
+    }
+
+    fn form_end(&self) -> u64 {
+        8 + self.form_length
+    }
+
+    fn write_chunk_at(&mut self, extent: Extent, ident: FourCC, data: &[u8]) -> Result<(), Error> {
+        let needed = 8 + padded(data.len() as u64);
+
+        self.inner.seek(SeekFrom::Start(extent.start))?;
+        self.inner.write_fourcc(ident)?;
+        self.inner.write_u32::<LittleEndian>(data.len() as u32)?;
+        self.inner.write_all(data)?;
+        if data.len() % 2 == 1 {
+            self.inner.write_all(&[0u8])?;
+        }
+
+        if extent.length >= needed + 8 {
+            self.write_filler(extent.start + needed, extent.length - needed)?;
+        } else if extent.at_end && extent.length != needed {
+            self.set_form_length(extent.start + needed - 8)?;
+        }
+        Ok(())
+    }
+
+    /// Write a `JUNK` chunk over `length` bytes at `start`
+    fn write_filler(&mut self, start: u64, length: u64) -> Result<(), Error> {
+        self.inner.seek(SeekFrom::Start(start))?;
+        self.inner.write_fourcc(JUNK_SIG)?;
+        self.inner.write_u32::<LittleEndian>((length - 8) as u32)?;
+        self.inner.write_all(&vec![0u8; (length - 8) as usize])?;
+        Ok(())
+    }
+
+    fn set_form_length(&mut self, form_length: u64) -> Result<(), Error> {
+        self.form_length = form_length;
+        if self.is_rf64 {
+            self.inner.seek(SeekFrom::Start(8 + 4 + 8))?;
+            self.inner.write_u64::<LittleEndian>(form_length)?;
+        } else if form_length < u32::MAX as u64 {
+            self.inner.seek(SeekFrom::Start(4))?;
+            self.inner.write_u32::<LittleEndian>(form_length as u32)?;
+        } else {
+            self.promote_to_rf64()?;
+        }
+        Ok(())
+    }
+
+    /// Upgrade this file to RF64, writing a `ds64` record over the `JUNK`
+    /// reservation at the start of the file
+    fn promote_to_rf64(&mut self) -> Result<(), Error> {
+        let reservation = self.chunks.first()
+            .filter(|c| c.signature == JUNK_SIG && c.start == 12 + 8)
+            .map(|c| c.length)
+            .unwrap_or(0);
+
+        if reservation < DS64_MINIMUM_LENGTH {
+            return Err(Error::InsufficientDS64Reservation {
+                expected: DS64_MINIMUM_LENGTH, actual: reservation
+            });
+        }
+
+        let data = self.chunks.iter().find(|c| c.signature == DATA_SIG)
+            .map(|c| (c.start, c.length))
+            .ok_or(Error::ChunkMissing { signature: DATA_SIG })?;
+
+        let frame_count = match self.chunks.iter().find(|c| c.signature == FACT_SIG) {
+            Some(fact) => {
+                self.inner.seek(SeekFrom::Start(fact.start))?;
+                self.inner.read_u32::<LittleEndian>()? as u64
+            },
+            None => 0
+        };
+
+        self.inner.seek(SeekFrom::Start(0))?;
+        self.inner.write_fourcc(RF64_SIG)?;
+        self.inner.write_u32::<LittleEndian>(0xFFFF_FFFF)?;
+        self.inner.seek(SeekFrom::Start(12))?;
+        self.inner.write_fourcc(DS64_SIG)?;
+        self.inner.seek(SeekFrom::Current(4))?;
+        self.inner.write_u64::<LittleEndian>(self.form_length)?;
+        self.inner.write_u64::<LittleEndian>(data.1)?;
+        self.inner.write_u64::<LittleEndian>(frame_count)?;
+        self.inner.write_u32::<LittleEndian>(0)?;
+
+        self.inner.seek(SeekFrom::Start(data.0 - 4))?;
+        self.inner.write_u32::<LittleEndian>(0xFFFF_FFFF)?;
+
+        self.is_rf64 = true;
+        Ok(())
+    }
+
+    fn reparse(&mut self) -> Result<(), Error> {
+        self.chunks = Parser::make(&mut self.inner)?.into_chunk_list()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn test_file(ixml: &[u8], junk: u32) -> Cursor<Vec<u8>> {
+    use super::{WaveWriter, WaveFmt};
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_stereo(48000, 16)).unwrap();
+    w.write_ixml(ixml).unwrap();
+    if junk > 0 {
+        w.write_junk(junk).unwrap();
+    }
+    w.write_chunk(FourCC::make(b"abcd"), b"next").unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.write_integer_frames(&[1i32, -1, 2, -2, 3, -3]).unwrap();
+    frame_writer.end().unwrap();
+    cursor
+}
+
+#[cfg(test)]
+fn read_back(cursor: &mut Cursor<Vec<u8>>, ident: FourCC) -> Vec<u8> {
+    let mut r = WaveReader::new(cursor).unwrap();
+    let mut buf = vec![];
+    r.read_chunk(ident, 0, &mut buf).unwrap();
+    let mut frames = vec![0i32; 6];
+    r.audio_frame_reader().unwrap().read_integer_frames(&mut frames).unwrap();
+    assert_eq!(frames, [1, -1, 2, -2, 3, -3]);
+    buf
+}
+
+#[cfg(test)]
+fn chunk_start(cursor: &mut Cursor<Vec<u8>>, ident: FourCC) -> u64 {
+    WaveReader::new(cursor).unwrap().chunks().iter()
+        .find(|c| c.signature == ident).unwrap().start
+}
+
+#[test]
+fn test_edit_in_place() {
+    let mut cursor = test_file(&[b'a'; 100], 0);
+    let start = chunk_start(&mut cursor, IXML_SIG);
+    let file_length = cursor.get_ref().len();
+
+    WaveEditor::new(&mut cursor).unwrap().write_ixml(&[b'b'; 100]).unwrap();
+    assert_eq!(read_back(&mut cursor, IXML_SIG), [b'b'; 100]);
+
+    WaveEditor::new(&mut cursor).unwrap().write_ixml(&[b'c'; 51]).unwrap();
+    assert_eq!(read_back(&mut cursor, IXML_SIG), [b'c'; 51]);
+    assert_eq!(chunk_start(&mut cursor, IXML_SIG), start);
+    assert_eq!(cursor.get_ref().len(), file_length);
+
+    let editor = WaveEditor::new(&mut cursor).unwrap();
+    let junk = editor.chunks().iter().find(|c| c.signature == JUNK_SIG && c.start > start).unwrap();
+    assert_eq!(junk.start, start + 52 + 8);
+    assert_eq!(junk.length, 100 - 52 - 8);
+}
+
+#[test]
+fn test_edit_consumes_filler() {
+    let mut cursor = test_file(&[b'a'; 100], 200);
+    let start = chunk_start(&mut cursor, IXML_SIG);
+    let file_length = cursor.get_ref().len();
+
+    WaveEditor::new(&mut cursor).unwrap().write_ixml(&[b'b'; 250]).unwrap();
+    assert_eq!(read_back(&mut cursor, IXML_SIG), [b'b'; 250]);
+    assert_eq!(chunk_start(&mut cursor, IXML_SIG), start);
+    assert_eq!(read_back(&mut cursor, FourCC::make(b"abcd")), b"next");
+    assert_eq!(cursor.get_ref().len(), file_length);
+
+    // Exactly fills the chunk and the filler
+    WaveEditor::new(&mut cursor).unwrap().write_ixml(&[b'c'; 308]).unwrap();
+    assert_eq!(read_back(&mut cursor, IXML_SIG), [b'c'; 308]);
+    assert_eq!(chunk_start(&mut cursor, IXML_SIG), start);
+    assert_eq!(cursor.get_ref().len(), file_length);
+}
+
+#[test]
+fn test_edit_relocates() {
+    let mut cursor = test_file(&[b'a'; 100], 0);
+    let start = chunk_start(&mut cursor, IXML_SIG);
+    let file_length = cursor.get_ref().len() as u64;
+
+    // Shrinking by less than a chunk header can't leave a filler behind
+    WaveEditor::new(&mut cursor).unwrap().write_ixml(&[b'b'; 94]).unwrap();
+    assert_eq!(read_back(&mut cursor, IXML_SIG), [b'b'; 94]);
+    assert_eq!(chunk_start(&mut cursor, IXML_SIG), file_length + 8);
+
+    let editor = WaveEditor::new(&mut cursor).unwrap();
+    let junk = editor.chunks().iter().find(|c| c.start == start).unwrap();
+    assert_eq!(junk.signature, JUNK_SIG);
+    assert_eq!(junk.length, 100);
+    assert_eq!(read_back(&mut cursor, FourCC::make(b"abcd")), b"next");
+
+    let mut header = Cursor::new(&cursor.get_ref()[4..8]);
+    assert_eq!(header.read_u32::<LittleEndian>().unwrap() as usize, cursor.get_ref().len() - 8);
+
+    // The chunk at the end grows in place, and the old chunk's space is reused
+    WaveEditor::new(&mut cursor).unwrap().write_ixml(&[b'c'; 1000]).unwrap();
+    assert_eq!(chunk_start(&mut cursor, IXML_SIG), file_length + 8);
+    assert_eq!(cursor.get_ref().len() as u64, file_length + 1008);
+
+    WaveEditor::new(&mut cursor).unwrap().write_axml(&[b'd'; 80]).unwrap();
+    assert_eq!(chunk_start(&mut cursor, AXML_SIG), start);
+    assert_eq!(read_back(&mut cursor, AXML_SIG), [b'd'; 80]);
+}
+
+#[test]
+fn test_edit_rf64() {
+    let mut cursor = test_file(&[b'a'; 100], 0);
+    let file_length = cursor.get_ref().len() as u64;
+
+    let mut editor = WaveEditor::new(&mut cursor).unwrap();
+    editor.promote_to_rf64().unwrap();
+    let mut editor = WaveEditor::new(&mut cursor).unwrap();
+    assert!(editor.is_rf64());
+    editor.write_ixml(&[b'b'; 200]).unwrap();
+    // Goes where the iXML was
+    editor.write_chunk(FourCC::make(b"wxyz"), b"end").unwrap();
+
+    assert_eq!(read_back(&mut cursor, IXML_SIG), [b'b'; 200]);
+    assert_eq!(read_back(&mut cursor, FourCC::make(b"wxyz")), b"end");
+
+    let mut header = Cursor::new(&cursor.get_ref()[..]);
+    assert_eq!(header.read_fourcc().unwrap(), RF64_SIG);
+    header.seek(SeekFrom::Start(20)).unwrap();
+    assert_eq!(header.read_u64::<LittleEndian>().unwrap(), file_length + 208 - 8);
+    assert_eq!(cursor.get_ref().len() as u64, file_length + 208);
+}
+
+#[test]
+fn test_edit_refuses_structure() {
+    let mut cursor = test_file(b"", 0);
+    let mut editor = WaveEditor::new(&mut cursor).unwrap();
+    assert!(matches!(editor.write_chunk(DATA_SIG, &[0u8; 4]),
+        Err(Error::ChunkNotEditable { signature: DATA_SIG })));
+    assert!(matches!(editor.remove_chunk(BEXT_SIG),
+        Err(Error::ChunkMissing { signature: BEXT_SIG })));
+    editor.remove_chunk(IXML_SIG).unwrap();
+    assert!(editor.chunks().iter().all(|c| c.signature != IXML_SIG));
+}
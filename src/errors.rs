@@ -13,6 +13,17 @@ pub enum Error {
     /// An error occured reading a tag UUID
     UuidError(uuid::Error),
 
+    /// An error occured parsing iXML or ADM metadata, with the parser's
+    /// message
+    XmlError(String),
+
+    /// An `ADMAudioID` for the track at `track_index` contains a character
+    /// that isn't ASCII and can't be written to a `chna` chunk
+    ADMAudioIDNotAscii { track_index: u16 },
+
+    /// The root element of iXML metadata is not `BWFXML`
+    IXmlRootNotRecognized { name: String },
+
     /// The file does not begin with a recognized WAVE header
     HeaderNotRecognized,
     
@@ -41,6 +52,33 @@ pub enum Error {
     /// The file is not optimized for writing new data
     DataChunkNotPreparedForAppend,
 
+    /// A `dbmd` metadata segment's checksum does not match its content
+    DolbyMetadataChecksumMismatch { segment_id: u8 },
+
+    /// A `levl` chunk has a peak format other than 8- or 16-bit
+    PeakFormatNotRecognized { format: u32 },
+
+    /// The chunk describes the structure or audio data of the file and
+    /// can't be replaced by a metadata editor
+    ChunkNotEditable { signature: FourCC },
+
+    /// A field of an EBU R99 USID is malformed
+    UsidFieldInvalid { field: &'static str },
+
+    /// Text can't be written with the Windows code page because it isn't
+    /// supported
+    CodePageNotSupported { code_page: u16 },
+
+    /// The text of the `tag` can't be represented in the Windows code page
+    TextNotEncodable { tag: FourCC, code_page: u16 },
+
+    /// A text field of the `chunk` record is longer than the space for it
+    FieldTooLong { chunk: FourCC, field: &'static str, length: usize, limit: usize },
+
+    /// A text field of the `chunk` record contains a NUL or a character
+    /// other than ASCII
+    FieldNotAscii { chunk: FourCC, field: &'static str },
+
 }
 
 
@@ -54,4 +92,11 @@ impl From <uuid::Error> for Error {
     fn from(error: uuid::Error) -> Error {
         Error::UuidError(error)
     }  
-}
\ No newline at end of file
+}
+
+#[cfg(any(feature = "ixml", feature = "adm"))]
+impl From <xmltree::ParseError> for Error {
+    fn from(error: xmltree::ParseError) -> Error {
+        Error::XmlError(error.to_string())
+    }
+}
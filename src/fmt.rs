@@ -1,12 +1,6 @@
 use uuid::Uuid;
-use super::common_format::{CommonFormat, UUID_PCM,UUID_BFORMAT_PCM};
-use std::io::Cursor;
-
-use byteorder::LittleEndian;
-use byteorder::{WriteBytesExt, ReadBytesExt};
-
-// Need more test cases for ADMAudioID
-#[allow(dead_code)]
+use super::common_format::{CommonFormat, UUID_PCM, UUID_FLOAT, UUID_BFORMAT_PCM, UUID_BFORMAT_FLOAT};
+use super::sample::{Sample, SampleEncoding};
 
 /// ADM Audio ID record.
 /// 
@@ -18,16 +12,67 @@ use byteorder::{WriteBytesExt, ReadBytesExt};
 /// `AudioProgramme`.
 /// 
 /// See BS.2088-1 § 8, also BS.2094, also blahblahblah...
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ADMAudioID {
+    /// The `audioTrackUID`, e.g. "ATU_00000001"
     pub track_uid: [char; 12],
+
+    /// The `audioTrackFormatID` or `audioChannelFormatID` of the track, 
+    /// e.g. "AT_00010001_01"
     pub channel_format_ref: [char; 14],
+
+    /// The `audioPackFormatID` of the track, e.g. "AP_00010002"
     pub pack_ref: [char; 11]
 }
 
+fn fixed_chars<const N: usize>(s: &str) -> [char; N] {
+    let mut retval = ['\0'; N];
+    for (c, s) in retval.iter_mut().zip(s.chars()) { *c = s; }
+    retval
+}
+
+impl ADMAudioID {
+
+    /// Create a new Audio ID record.
+    /// 
+    /// Each field is truncated, or padded with NULs, to its fixed length.
+    /// The IDs must be ASCII, `WaveWriter::write_chna()` returns an error 
+    /// if they are not.
+    pub fn new(track_uid: &str, channel_format_ref: &str, pack_ref: &str) -> Self {
+        ADMAudioID {
+            track_uid: fixed_chars(track_uid),
+            channel_format_ref: fixed_chars(channel_format_ref),
+            pack_ref: fixed_chars(pack_ref)
+        }
+    }
+
+    /// The `audioTrackUID` as a `String`
+    pub fn track_uid(&self) -> String {
+        self.track_uid.iter().take_while(|c| **c != '\0').collect()
+    }
+
+    /// The track or channel format reference as a `String`
+    pub fn channel_format_ref(&self) -> String {
+        self.channel_format_ref.iter().take_while(|c| **c != '\0').collect()
+    }
+
+    /// The pack format reference as a `String`
+    pub fn pack_ref(&self) -> String {
+        self.pack_ref.iter().take_while(|c| **c != '\0').collect()
+    }
+
+    /// True if every field is ASCII and can be written to a `chna` chunk
+    pub fn is_ascii(&self) -> bool {
+        self.track_uid.iter().chain(self.channel_format_ref.iter()).chain(self.pack_ref.iter())
+            .all(|c| c.is_ascii())
+    }
+}
+
 /// Describes a single channel in a WAV file.
 /// 
 /// This information is correlated from the Wave format ChannelMap field and
 /// the `chna` chunk, if present.
+#[derive(Debug, Clone, PartialEq)]
 pub struct ChannelDescriptor {
     /// Index, the offset of this channel's samples in one frame.
     pub index: u16,
@@ -282,6 +327,77 @@ impl WaveFmt {
         }
     }
 
+    /// Create a new IEEE float format for a monoaural audio stream.
+    /// 
+    /// `bits_per_sample` must be 32 or 64.
+    pub fn new_float_mono(sample_rate: u32, bits_per_sample: u16) -> Self {
+        Self::new_float_multichannel(sample_rate, bits_per_sample, 0x4)
+    }
+
+    /// Create a new IEEE float format for a standard Left-Right stereo audio 
+    /// stream.
+    /// 
+    /// `bits_per_sample` must be 32 or 64.
+    pub fn new_float_stereo(sample_rate: u32, bits_per_sample: u16) -> Self {
+        Self::new_float_multichannel(sample_rate, bits_per_sample, 0x3)
+    }
+
+    /// Create a new IEEE float format for ambisonic b-format.
+    /// 
+    /// `bits_per_sample` must be 32 or 64.
+    pub fn new_float_ambisonic(sample_rate: u32, bits_per_sample: u16, channel_count: u16) -> Self {
+        assert!(bits_per_sample == 32 || bits_per_sample == 64, 
+            "IEEE float formats must have 32 or 64 bits per sample, was {}", bits_per_sample);
+
+        let bytes_per_sample = bits_per_sample / 8;
+
+        WaveFmt {
+            tag : 0xFFFE,
+            channel_count,
+            sample_rate,
+            bytes_per_second: bytes_per_sample as u32 * sample_rate * channel_count as u32,
+            block_alignment: bytes_per_sample * channel_count,
+            bits_per_sample,
+            extended_format: Some(WaveFmtExtended {
+                valid_bits_per_sample: bits_per_sample,
+                channel_mask: ChannelMask::DirectOut as u32,
+                type_guid: UUID_BFORMAT_FLOAT
+            })
+        }
+    }
+
+    /// Create a new IEEE float format `WaveFmt` with a custom channel bitmap.
+    /// 
+    /// Mono and stereo formats will use the basic float format tag 0x0003, 
+    /// all others will use an extended format.
+    /// 
+    /// `bits_per_sample` must be 32 or 64. The order of `channels` is not 
+    /// important, see `new_pcm_multichannel()`.
+    pub fn new_float_multichannel(sample_rate: u32, bits_per_sample: u16, channel_bitmap: u32) -> Self {
+        assert!(bits_per_sample == 32 || bits_per_sample == 64, 
+            "IEEE float formats must have 32 or 64 bits per sample, was {}", bits_per_sample);
+
+        let bytes_per_sample = bits_per_sample / 8;
+
+        let channel_count: u16 = (0..=31).fold(0u16, |accum, n| accum + (0x1 & (channel_bitmap >> n) as u16) );
+
+        let (tag, extformat) = match channel_bitmap {
+            0b0100 | 0b0011 => (0x0003, None),
+            ch => (0xFFFE, Some( WaveFmtExtended { valid_bits_per_sample: bits_per_sample, channel_mask: ch, 
+                    type_guid: UUID_FLOAT }))
+        };
+
+        WaveFmt {
+            tag,
+            channel_count,
+            sample_rate,
+            bytes_per_second: bytes_per_sample as u32 * sample_rate * channel_count as u32,
+            block_alignment: bytes_per_sample * channel_count,
+            bits_per_sample,
+            extended_format: extformat
+        }
+    }
+
     /// Format or codec of the file's audio data.
     /// 
     /// The `CommonFormat` unifies the format tag and the format extension GUID. Use this
@@ -293,10 +409,10 @@ impl WaveFmt {
     /// Create a frame buffer sized to hold `length` frames for a reader or 
     /// writer
     /// 
-    /// This is a conveneince method that creates a `Vec<i32>` with
+    /// This is a conveneince method that creates a `Vec` of samples with
     /// as many elements as there are channels in the underlying stream. 
-    pub fn create_frame_buffer(&self, length : usize) -> Vec<i32> {
-        vec![0i32; self.channel_count as usize * length]
+    pub fn create_frame_buffer<S: Sample>(&self, length : usize) -> Vec<S> {
+        vec![S::default(); self.channel_count as usize * length]
     }
 
     /// Create a raw byte buffer to hold `length` blocks from a reader or 
@@ -306,40 +422,75 @@ impl WaveFmt {
     }
 
     /// Write frames into a byte vector
-    pub fn pack_frames(&self, from_frames: &[i32], into_bytes: &mut [u8]) -> () {
-        let mut write_cursor = Cursor::new(into_bytes);
-
-        assert!(from_frames.len() % self.channel_count as usize == 0, 
+    /// 
+    /// Samples are "right-aligned" integers with the sample size of the
+    /// format, as returned by `unpack_frames()`.
+    /// 
+    /// ### Panics
+    /// 
+    /// This method will panic if the format is IEEE float, use 
+    /// `pack_samples()` to write float audio data.
+    pub fn pack_frames(&self, from_frames: &[i32], into_bytes: &mut [u8]) {
+        assert_eq!(from_frames.len() % self.channel_count as usize, 0, 
             "frames buffer does not contain a number of samples % channel_count == 0");
 
-            for n in 0..from_frames.len() {
-                match (self.valid_bits_per_sample(), self.bits_per_sample) {
-                    (0..=8,8) => write_cursor.write_u8((from_frames[n] + 0x80) as u8 ).unwrap(), // EBU 3285 §A2.2
-                    (9..=16,16) => write_cursor.write_i16::<LittleEndian>(from_frames[n] as i16).unwrap(),
-                    (10..=24,24) => write_cursor.write_i24::<LittleEndian>(from_frames[n]).unwrap(),
-                    (25..=32,32) => write_cursor.write_i32::<LittleEndian>(from_frames[n]).unwrap(),
-                    (b,_)=> panic!("Unrecognized integer format, bits per sample {}, channels {}, block_alignment {}", 
-                        b, self.channel_count, self.block_alignment)
-                }
-            }
-        ()
+        let encoding = SampleEncoding::for_format(self);
+        assert!(!encoding.is_float(), "pack_frames was called on an IEEE float format, use pack_samples");
+        let shift = 32 - encoding.bit_count();
+
+        for (sample, bytes) in from_frames.iter().zip(into_bytes.chunks_exact_mut(encoding.byte_count())) {
+            encoding.encode(*sample << shift, bytes);
+        }
     }
 
     /// Read bytes into frames
-    pub fn unpack_frames(&self, from_bytes: &[u8], into_frames: &mut [i32]) -> () {
-        let mut rdr = Cursor::new(from_bytes);
-        for n in 0..(into_frames.len()) {
-            into_frames[n] = match (self.valid_bits_per_sample(), self.bits_per_sample) {
-                (0..=8,8) => rdr.read_u8().unwrap() as i32 - 0x80_i32, // EBU 3285 §A2.2
-                (9..=16,16) => rdr.read_i16::<LittleEndian>().unwrap() as i32,
-                (10..=24,24) => rdr.read_i24::<LittleEndian>().unwrap(),
-                (25..=32,32) => rdr.read_i32::<LittleEndian>().unwrap(),
-                (b,_)=> panic!("Unrecognized integer format, bits per sample {}, channels {}, block_alignment {}", 
-                    b, self.channel_count, self.block_alignment)
-            }
+    /// 
+    /// Samples are written back "right-aligned", so samples shorter than 
+    /// `i32` are sign-extended and not scaled; a 24-bit sample will be in
+    /// the range `-0x80_0000..0x80_0000`.
+    /// 
+    /// ### Panics
+    /// 
+    /// This method will panic if the format is IEEE float, use 
+    /// `unpack_samples()` to read float audio data.
+    pub fn unpack_frames(&self, from_bytes: &[u8], into_frames: &mut [i32]) {
+        let encoding = SampleEncoding::for_format(self);
+        assert!(!encoding.is_float(), "unpack_frames was called on an IEEE float format, use unpack_samples");
+        let shift = 32 - encoding.bit_count();
+
+        for (sample, bytes) in into_frames.iter_mut().zip(from_bytes.chunks_exact(encoding.byte_count())) {
+            *sample = encoding.decode::<i32>(bytes) >> shift;
+        }
+    }
+
+    /// Write samples of any `Sample` type into a byte vector
+    /// 
+    /// Samples are converted to the format's sample type as described by 
+    /// `Sample`; `i32` samples are full-scale, not right-aligned as they are
+    /// for `pack_frames()`.
+    pub fn pack_samples<S: Sample>(&self, from_frames: &[S], into_bytes: &mut [u8]) {
+        assert_eq!(from_frames.len() % self.channel_count as usize, 0, 
+            "frames buffer does not contain a number of samples % channel_count == 0");
+
+        let encoding = SampleEncoding::for_format(self);
+
+        for (sample, bytes) in from_frames.iter().zip(into_bytes.chunks_exact_mut(encoding.byte_count())) {
+            encoding.encode(*sample, bytes);
         }
     }
 
+    /// Read bytes into samples of any `Sample` type
+    /// 
+    /// Samples are converted from the format's sample type as described by
+    /// `Sample`; `i32` samples are full-scale, not right-aligned as they are
+    /// for `unpack_frames()`.
+    pub fn unpack_samples<S: Sample>(&self, from_bytes: &[u8], into_frames: &mut [S]) {
+        let encoding = SampleEncoding::for_format(self);
+
+        for (sample, bytes) in into_frames.iter_mut().zip(from_bytes.chunks_exact(encoding.byte_count())) {
+            *sample = encoding.decode(bytes);
+        }
+    }
 
     /// Channel descriptors for each channel.
     pub fn channels(&self) -> Vec<ChannelDescriptor> {
@@ -381,47 +532,19 @@ impl WaveFmt {
     }
 }
 
-trait ReadWavAudioData {
-    fn read_i32_frames(&mut self, format: WaveFmt, into: &mut [i32]) -> Result<usize,std::io::Error>;
-    fn read_f32_frames(&mut self, format: WaveFmt, into: &mut [f32]) -> Result<usize,std::io::Error>;
+#[test]
+fn test_pack_frames_right_aligned() {
+    let format = WaveFmt::new_pcm_stereo(48000, 24);
+    let frames = [0x7F_FFFFi32, -0x80_0000];
+    let mut bytes = format.create_raw_buffer(1);
+    format.pack_frames(&frames, &mut bytes);
+    assert_eq!(bytes, [0xFF, 0xFF, 0x7F, 0x00, 0x00, 0x80]);
+
+    let mut read = [0i32; 2];
+    format.unpack_frames(&bytes, &mut read);
+    assert_eq!(read, frames);
+
+    let mut samples = [0i32; 2];
+    format.unpack_samples(&bytes, &mut samples);
+    assert_eq!(samples, [0x7FFF_FF00, i32::MIN]);
 }
-
-impl<T> ReadWavAudioData for T where T: std::io::Read {
-
-    fn read_i32_frames(&mut self, format: WaveFmt, into: &mut [i32]) -> Result<usize, std::io::Error> { 
-        assert!(into.len() % format.channel_count as usize == 0);
-
-        for n in 0..(into.len()) {
-            into[n] = match (format.valid_bits_per_sample(), format.bits_per_sample) {
-                (0..=8,8) => self.read_u8().unwrap() as i32 - 0x80_i32, // EBU 3285 §A2.2
-                (9..=16,16) => self.read_i16::<LittleEndian>().unwrap() as i32,
-                (10..=24,24) => self.read_i24::<LittleEndian>().unwrap(),
-                (25..=32,32) => self.read_i32::<LittleEndian>().unwrap(),
-                (b,_)=> panic!("Unrecognized integer format, bits per sample {}, channels {}, block_alignment {}", 
-                    b, format.channel_count, format.block_alignment)
-            }
-        }
-
-        todo!()
-    }
-    fn read_f32_frames(&mut self, format: WaveFmt, into: &mut [f32]) -> Result<usize, std::io::Error> { 
-        assert!(into.len() % format.channel_count as usize == 0);
-        todo!()
-    }
-
-}
-
-trait WriteWavAudioData {
-    fn write_i32_frames(&mut self, format: WaveFmt, from: &[i32]) -> Result<usize, std::io::Error>;
-    fn write_f32_frames(&mut self, format: WaveFmt, from: &[f32]) -> Result<usize, std::io::Error>;
-}
-
-impl<T> WriteWavAudioData for T where T: std::io::Write {
-
-    fn write_i32_frames(&mut self, format: WaveFmt, _: &[i32]) -> Result<usize, std::io::Error> { 
-        todo!() 
-    }
-    fn write_f32_frames(&mut self, format: WaveFmt, _: &[f32]) -> Result<usize, std::io::Error> { 
-        todo!() 
-    }
-}
\ No newline at end of file
@@ -9,6 +9,14 @@ use std::io;
 pub struct FourCC([u8; 4]);
 
 impl FourCC {
+    /// Make a `FourCC` from four bytes.
+    ///
+    /// ```
+    /// use bwavfile::FourCC;
+    ///
+    /// let sig = FourCC::make(b"iXML");
+    /// assert_eq!(String::from(sig), "iXML");
+    /// ```
     pub const fn make(s: &[u8; 4]) -> Self {
         Self(*s)
     }
@@ -102,20 +110,30 @@ pub const DATA_SIG: FourCC = FourCC::make(b"data");
 pub const FMT__SIG: FourCC = FourCC::make(b"fmt ");
 
 pub const BEXT_SIG: FourCC = FourCC::make(b"bext");
-//pub const FACT_SIG: FourCC = FourCC::make(b"fact");
+pub const CART_SIG: FourCC = FourCC::make(b"cart");
+pub const LEVL_SIG: FourCC = FourCC::make(b"levl");
+pub const FACT_SIG: FourCC = FourCC::make(b"fact");
 pub const IXML_SIG: FourCC = FourCC::make(b"iXML");
 pub const AXML_SIG: FourCC = FourCC::make(b"axml");
+pub const CHNA_SIG: FourCC = FourCC::make(b"chna");
+pub const DBMD_SIG: FourCC = FourCC::make(b"dbmd");
 
 pub const JUNK_SIG: FourCC = FourCC::make(b"JUNK");
 pub const FLLR_SIG: FourCC = FourCC::make(b"FLLR");
 pub const ELM1_SIG: FourCC = FourCC::make(b"elm1");
 pub const LIST_SIG: FourCC = FourCC::make(b"LIST");
+pub const INFO_SIG: FourCC = FourCC::make(b"INFO");
+pub const CSET_SIG: FourCC = FourCC::make(b"CSET");
 
 pub const CUE__SIG: FourCC = FourCC::make(b"cue ");
 pub const ADTL_SIG: FourCC = FourCC::make(b"adtl");
 pub const LABL_SIG: FourCC = FourCC::make(b"labl");
 pub const NOTE_SIG: FourCC = FourCC::make(b"note");
 pub const LTXT_SIG: FourCC = FourCC::make(b"ltxt");
+pub const R64M_SIG: FourCC = FourCC::make(b"r64m");
+
+pub const SMPL_SIG: FourCC = FourCC::make(b"smpl");
+pub const INST_SIG: FourCC = FourCC::make(b"inst");
 
 
 #[cfg(test)]
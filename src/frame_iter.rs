@@ -0,0 +1,144 @@
+use std::io::{Read, Seek};
+use std::marker::PhantomData;
+
+use super::errors::Error;
+use super::sample::Sample;
+use super::wavereader::AudioFrameReader;
+
+/// An iterator over the frames of an `AudioFrameReader`.
+///
+/// Created by `AudioFrameReader::frames()`. Each item is a single frame of
+/// interleaved samples. Iteration ends at the end of the audio data, or
+/// after the first error.
+pub struct Frames<'a, R: Read + Seek, S: Sample> {
+    reader: &'a mut AudioFrameReader<R>,
+    failed: bool,
+    sample: PhantomData<S>
+}
+
+impl<'a, R: Read + Seek, S: Sample> Frames<'a, R, S> {
+    pub(crate) fn new(reader: &'a mut AudioFrameReader<R>) -> Self {
+        Frames { reader, failed: false, sample: PhantomData }
+    }
+}
+
+impl<'a, R: Read + Seek, S: Sample> Iterator for Frames<'a, R, S> {
+    type Item = Result<Vec<S>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let mut buffer = self.reader.format().create_frame_buffer(1);
+        match self.reader.read_frames(&mut buffer) {
+            Ok(0) => None,
+            Ok(_) => Some(Ok(buffer)),
+            Err(e) => { self.failed = true; Some(Err(e)) }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.failed {
+            (0, Some(0))
+        } else {
+            let remaining = self.reader.frames_remaining() as usize;
+            (remaining, Some(remaining))
+        }
+    }
+}
+
+/// An iterator over blocks of frames of an `AudioFrameReader`.
+///
+/// Created by `AudioFrameReader::blocks()`. Each item is a block of
+/// interleaved samples. Iteration ends at the end of the audio data, or
+/// after the first error.
+pub struct Blocks<'a, R: Read + Seek, S: Sample> {
+    reader: &'a mut AudioFrameReader<R>,
+    frame_count: usize,
+    failed: bool,
+    sample: PhantomData<S>
+}
+
+impl<'a, R: Read + Seek, S: Sample> Blocks<'a, R, S> {
+    pub(crate) fn new(reader: &'a mut AudioFrameReader<R>, frame_count: usize) -> Self {
+        assert!(frame_count > 0, "Blocks must contain at least one frame");
+        Blocks { reader, frame_count, failed: false, sample: PhantomData }
+    }
+}
+
+impl<'a, R: Read + Seek, S: Sample> Iterator for Blocks<'a, R, S> {
+    type Item = Result<Vec<S>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let format = self.reader.format();
+        let mut buffer = format.create_frame_buffer(self.frame_count);
+        match self.reader.read_frames(&mut buffer) {
+            Ok(0) => None,
+            Ok(n) => {
+                buffer.truncate(n as usize * format.channel_count as usize);
+                Some(Ok(buffer))
+            },
+            Err(e) => { self.failed = true; Some(Err(e)) }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.failed {
+            (0, Some(0))
+        } else {
+            let remaining = self.reader.frames_remaining() as usize;
+            let blocks = remaining.div_ceil(self.frame_count);
+            (blocks, Some(blocks))
+        }
+    }
+}
+
+/// An iterator over the samples of one channel of an `AudioFrameReader`.
+///
+/// Created by `AudioFrameReader::channel_samples()`. Iteration ends at the
+/// end of the audio data, or after the first error.
+pub struct ChannelSamples<'a, R: Read + Seek, S: Sample> {
+    reader: &'a mut AudioFrameReader<R>,
+    channel: u16,
+    buffer: [S; 1],
+    failed: bool
+}
+
+impl<'a, R: Read + Seek, S: Sample> ChannelSamples<'a, R, S> {
+    pub(crate) fn new(reader: &'a mut AudioFrameReader<R>, channel: u16) -> Self {
+        let format = reader.format();
+        assert!(channel < format.channel_count,
+            "Channel {} is out of range, channel count is {}", channel, format.channel_count);
+        ChannelSamples { reader, channel, buffer: [S::default()], failed: false }
+    }
+}
+
+impl<'a, R: Read + Seek, S: Sample> Iterator for ChannelSamples<'a, R, S> {
+    type Item = Result<S, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        match self.reader.read_channels(&[self.channel], &mut self.buffer) {
+            Ok(0) => None,
+            Ok(_) => Some(Ok(self.buffer[0])),
+            Err(e) => { self.failed = true; Some(Err(e)) }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.failed {
+            (0, Some(0))
+        } else {
+            let remaining = self.reader.frames_remaining() as usize;
+            (remaining, Some(remaining))
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use super::errors::Error;
+use super::fourcc::FourCC;
+use super::list_form::{ListFormItem, code_page_encoding};
+
+use encoding::{DecoderTrap, EncoderTrap};
+use encoding::all::WINDOWS_1252;
+
+/// INFO metadata, the content of a `LIST` chunk with form type `INFO`.
+///
+/// The common tags are available as fields, and any other tags are kept in
+/// `other`, in the order they were read.
+///
+/// INFO strings don't carry their own encoding. When reading, strings are
+/// decoded with the code page given by a `CSET` chunk if the file has one,
+/// otherwise as UTF-8 if they're valid UTF-8 and as Windows-1252 if they
+/// aren't. When writing, strings are encoded with `code_page`, and
+/// `WaveWriter::write_info()` returns an error if the code page isn't
+/// supported or a string can't be represented in it.
+///
+/// ```
+/// use bwavfile::{Info, FourCC};
+///
+/// let mut info = Info::default();
+/// info.title = Some(String::from("Café"));
+/// info.software = Some(String::from("bwavfile"));
+/// info.other.push((FourCC::make(b"IMED"), String::from("Tape")));
+///
+/// assert_eq!(info.get(FourCC::make(b"INAM")), Some("Café"));
+/// assert_eq!(info.get(FourCC::make(b"IMED")), Some("Tape"));
+/// ```
+///
+/// ## Resources
+/// - [Multimedia Programming Interface and Data Specifications 1.0](http://www-mmsp.ece.mcgill.ca/Documents/AudioFormats/WAVE/Docs/riffmci.pdf) (August 1991), "INFO List Chunk"
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Info {
+    /// Windows code page used to encode strings when writing. 0 and 65001
+    /// are UTF-8, 1252 is Windows Latin-1.
+    pub code_page: u16,
+
+    /// `INAM` Name, the title of the subject of the file
+    pub title: Option<String>,
+
+    /// `IART` Artist
+    pub artist: Option<String>,
+
+    /// `ICMT` Comments
+    pub comment: Option<String>,
+
+    /// `ICRD` Creation date, preferably in the form YYYY-MM-DD
+    pub creation_date: Option<String>,
+
+    /// `ISFT` Software, the application that created the file
+    pub software: Option<String>,
+
+    /// `IPRD` Product, the name of the album or product
+    pub product: Option<String>,
+
+    /// `IGNR` Genre
+    pub genre: Option<String>,
+
+    /// `ICOP` Copyright
+    pub copyright: Option<String>,
+
+    /// `ITRK` Track number
+    pub track_number: Option<String>,
+
+    /// `IENG` Engineer
+    pub engineer: Option<String>,
+
+    /// `ITCH` Technician, the person who digitized the subject
+    pub technician: Option<String>,
+
+    /// `IKEY` Keywords, separated by semicolons
+    pub keywords: Option<String>,
+
+    /// `ISBJ` Subject
+    pub subject: Option<String>,
+
+    /// `ISRC` Source, the person or organization who supplied the subject
+    pub source: Option<String>,
+
+    /// Any other tags
+    pub other: Vec<(FourCC, String)>
+}
+
+const INAM_SIG: FourCC = FourCC::make(b"INAM");
+const IART_SIG: FourCC = FourCC::make(b"IART");
+const ICMT_SIG: FourCC = FourCC::make(b"ICMT");
+const ICRD_SIG: FourCC = FourCC::make(b"ICRD");
+const ISFT_SIG: FourCC = FourCC::make(b"ISFT");
+const IPRD_SIG: FourCC = FourCC::make(b"IPRD");
+const IGNR_SIG: FourCC = FourCC::make(b"IGNR");
+const ICOP_SIG: FourCC = FourCC::make(b"ICOP");
+const ITRK_SIG: FourCC = FourCC::make(b"ITRK");
+const IENG_SIG: FourCC = FourCC::make(b"IENG");
+const ITCH_SIG: FourCC = FourCC::make(b"ITCH");
+const IKEY_SIG: FourCC = FourCC::make(b"IKEY");
+const ISBJ_SIG: FourCC = FourCC::make(b"ISBJ");
+const ISRC_SIG: FourCC = FourCC::make(b"ISRC");
+
+fn decode_info_string(buffer: &[u8], code_page: Option<u16>) -> String {
+    let end = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+    let trimmed = &buffer[..end];
+    let encoding = match code_page {
+        Some(cp) => code_page_encoding(cp),
+        None if std::str::from_utf8(trimmed).is_ok() => None,
+        None => Some(WINDOWS_1252 as _)
+    };
+    match encoding {
+        Some(encoding) => encoding.decode(trimmed, DecoderTrap::Replace)
+            .unwrap_or_else(|_| String::from_utf8_lossy(trimmed).into_owned()),
+        None => String::from_utf8_lossy(trimmed).into_owned()
+    }
+}
+
+fn encode_info_string(tag: FourCC, val: &str, code_page: u16) -> Result<Vec<u8>, Error> {
+    let mut buf = match code_page {
+        0 | 65001 => val.as_bytes().to_vec(),
+        cp => code_page_encoding(cp)
+            .ok_or(Error::CodePageNotSupported { code_page })?
+            .encode(val, EncoderTrap::Strict)
+            .map_err(|_| Error::TextNotEncodable { tag, code_page })?
+    };
+    buf.push(0);
+    Ok( buf )
+}
+
+impl Info {
+
+    fn fields(&self) -> [(FourCC, &Option<String>); 14] {
+        [
+            (INAM_SIG, &self.title), (IART_SIG, &self.artist), (ICMT_SIG, &self.comment),
+            (ICRD_SIG, &self.creation_date), (ISFT_SIG, &self.software), (IPRD_SIG, &self.product),
+            (IGNR_SIG, &self.genre), (ICOP_SIG, &self.copyright), (ITRK_SIG, &self.track_number),
+            (IENG_SIG, &self.engineer), (ITCH_SIG, &self.technician), (IKEY_SIG, &self.keywords),
+            (ISBJ_SIG, &self.subject), (ISRC_SIG, &self.source)
+        ]
+    }
+
+    fn fields_mut(&mut self) -> [(FourCC, &mut Option<String>); 14] {
+        [
+            (INAM_SIG, &mut self.title), (IART_SIG, &mut self.artist), (ICMT_SIG, &mut self.comment),
+            (ICRD_SIG, &mut self.creation_date), (ISFT_SIG, &mut self.software), (IPRD_SIG, &mut self.product),
+            (IGNR_SIG, &mut self.genre), (ICOP_SIG, &mut self.copyright), (ITRK_SIG, &mut self.track_number),
+            (IENG_SIG, &mut self.engineer), (ITCH_SIG, &mut self.technician), (IKEY_SIG, &mut self.keywords),
+            (ISBJ_SIG, &mut self.subject), (ISRC_SIG, &mut self.source)
+        ]
+    }
+
+    /// The value of a tag, by its identifier
+    pub fn get(&self, tag: FourCC) -> Option<&str> {
+        self.fields().iter()
+            .find(|(sig, _)| *sig == tag)
+            .map(|(_, value)| value.as_deref())
+            .unwrap_or_else(|| self.other.iter().find(|(sig, _)| *sig == tag).map(|(_, v)| v.as_str()))
+    }
+
+    /// Set the value of a tag, by its identifier
+    pub fn set(&mut self, tag: FourCC, value: &str) {
+        if let Some((_, field)) = self.fields_mut().iter_mut().find(|(sig, _)| *sig == tag) {
+            **field = Some(value.to_string());
+        } else if let Some((_, v)) = self.other.iter_mut().find(|(sig, _)| *sig == tag) {
+            *v = value.to_string();
+        } else {
+            self.other.push((tag, value.to_string()));
+        }
+    }
+
+    /// Read INFO from the items of an INFO `LIST`.
+    ///
+    /// If `code_page` is `None`, each string is decoded as UTF-8 if it's
+    /// valid UTF-8, or Windows-1252 otherwise.
+    pub(crate) fn from_list_items(items: &[ListFormItem], code_page: Option<u16>) -> Self {
+        let mut info = Info { code_page: code_page.unwrap_or(0), ..Default::default() };
+        for item in items {
+            let value = decode_info_string(&item.contents, code_page);
+            if let Some((_, field)) = info.fields_mut().iter_mut().find(|(sig, _)| *sig == item.signature) {
+                **field = Some(value);
+            } else {
+                info.other.push((item.signature, value));
+            }
+        }
+        info
+    }
+
+    /// The items of an INFO `LIST` for this INFO, encoded with `code_page`.
+    pub(crate) fn to_list_items(&self) -> Result<Vec<ListFormItem>, Error> {
+        self.fields().iter()
+            .filter_map(|(sig, value)| value.as_ref().map(|v| (*sig, v)))
+            .chain(self.other.iter().map(|(sig, v)| (*sig, v)))
+            .map(|(signature, value)| Ok( ListFormItem {
+                signature, contents: encode_info_string(signature, value, self.code_page)?
+            }))
+            .collect()
+    }
+}
+
+#[test]
+fn test_info_string_encodings() {
+    assert_eq!(decode_info_string(b"Caf\xc3\xa9\0\0", None), "Café");
+    assert_eq!(decode_info_string(b"Caf\xe9\0", None), "Café");
+    assert_eq!(decode_info_string(b"\x8f\xe9\0", Some(1251)), "Џй");
+    assert_eq!(encode_info_string(INAM_SIG, "Café", 1252).unwrap(), b"Caf\xe9\0");
+    assert_eq!(encode_info_string(INAM_SIG, "Café", 0).unwrap(), b"Caf\xc3\xa9\0");
+    assert!(matches!(encode_info_string(INAM_SIG, "Café", 1),
+        Err(Error::CodePageNotSupported { code_page: 1 })));
+    assert!(matches!(encode_info_string(INAM_SIG, "Café 日本", 1252),
+        Err(Error::TextNotEncodable { tag: INAM_SIG, code_page: 1252 })));
+}
+
+#[test]
+fn test_info_round_trip() {
+    let mut info = Info::default();
+    info.code_page = 1252;
+    info.title = Some(String::from("Café"));
+    info.track_number = Some(String::from("3"));
+    info.set(FourCC::make(b"IMED"), "DAT");
+
+    let items = info.to_list_items().unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0].contents, b"Caf\xe9\0");
+
+    assert_eq!(Info::from_list_items(&items, Some(1252)), info);
+    assert_eq!(Info::from_list_items(&items, None).title.as_deref(), Some("Café"));
+}
@@ -0,0 +1,771 @@
+use xmltree::{Element, XMLNode, EmitterConfig};
+
+use super::errors::Error;
+use super::bext::Bext;
+use super::timecode::{Timecode, FrameRate};
+
+/// iXML production metadata.
+///
+/// Only available with the `ixml` feature.
+///
+/// Read from a file with `WaveReader::ixml()`, and written to a file by
+/// passing `to_bytes()` to `WaveWriter::write_ixml()`.
+///
+/// Any elements not described by this struct are preserved, so metadata
+/// read from a file can be modified and written back without loss.
+///
+/// ```
+/// use bwavfile::{IXml, IXmlTrack};
+///
+/// let mut ixml = IXml::default();
+/// ixml.project = Some(String::from("Test Project"));
+/// ixml.scene = Some(String::from("21A"));
+/// ixml.take = Some(String::from("3"));
+/// ixml.tracks.push(IXmlTrack::new(1, 1, "Boom"));
+///
+/// let read = IXml::parse(&ixml.to_bytes()).unwrap();
+/// assert_eq!(read, ixml);
+/// ```
+///
+/// ## Resources
+/// - [iXML Specification](http://www.ixml.info/)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IXml {
+    /// `IXML_VERSION`
+    pub version: Option<String>,
+
+    /// `PROJECT`, the name of the production
+    pub project: Option<String>,
+
+    /// `SCENE`
+    pub scene: Option<String>,
+
+    /// `TAKE`
+    pub take: Option<String>,
+
+    /// `TAPE`, the name of the roll or sound roll
+    pub tape: Option<String>,
+
+    /// `NOTE`, a comment on this take
+    pub note: Option<String>,
+
+    /// `SPEED`, the sample rate and timecode of the recording
+    pub speed: Option<IXmlSpeed>,
+
+    /// `TRACK_LIST`, a description of each track
+    pub tracks: Vec<IXmlTrack>,
+
+    /// `SYNC_POINT_LIST`
+    pub sync_points: Vec<IXmlSyncPoint>,
+
+    /// `BEXT`, a mirror of the Broadcast-WAV metadata
+    pub bext: Option<IXmlBext>,
+
+    /// `HISTORY`
+    pub history: Option<IXmlHistory>,
+
+    other_tracks: OtherNodes,
+    other_sync_points: OtherNodes,
+    other: OtherNodes
+}
+
+/// The `SPEED` element of iXML metadata.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IXmlSpeed {
+    /// `NOTE`
+    pub note: Option<String>,
+
+    /// `MASTER_SPEED`, the rate of the recording, e.g. "24000/1001"
+    pub master_speed: Option<String>,
+
+    /// `CURRENT_SPEED`, the rate of the file
+    pub current_speed: Option<String>,
+
+    /// `TIMECODE_RATE`, the timecode frame rate, e.g. "30000/1001"
+    pub timecode_rate: Option<String>,
+
+    /// `TIMECODE_FLAG`, "DF" or "NDF"
+    pub timecode_flag: Option<String>,
+
+    /// `FILE_SAMPLE_RATE`
+    pub file_sample_rate: Option<u32>,
+
+    /// `AUDIO_BIT_DEPTH`
+    pub audio_bit_depth: Option<u16>,
+
+    /// `DIGITIZER_SAMPLE_RATE`
+    pub digitizer_sample_rate: Option<u32>,
+
+    /// `TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_HI` and `_LO`, the start time of
+    /// the file in samples
+    pub timestamp_samples_since_midnight: Option<u64>,
+
+    /// `TIMESTAMP_SAMPLE_RATE`, the sample rate of the timestamp
+    pub timestamp_sample_rate: Option<u32>,
+
+    other: OtherNodes
+}
+
+/// A `TRACK` of the `TRACK_LIST` element of iXML metadata.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IXmlTrack {
+    /// `CHANNEL_INDEX`, the recorder's channel number, starting at 1
+    pub channel_index: u16,
+
+    /// `INTERLEAVE_INDEX`, the channel in the file, starting at 1
+    pub interleave_index: u16,
+
+    /// `NAME`
+    pub name: Option<String>,
+
+    /// `FUNCTION`, the recommended use of the track
+    pub function: Option<String>,
+
+    other: OtherNodes
+}
+
+/// A `SYNC_POINT` of the `SYNC_POINT_LIST` element of iXML metadata.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IXmlSyncPoint {
+    /// `SYNC_POINT_TYPE`, "RELATIVE" or "ABSOLUTE"
+    pub sync_point_type: Option<String>,
+
+    /// `SYNC_POINT_FUNCTION`
+    pub function: Option<String>,
+
+    /// `SYNC_POINT_COMMENT`
+    pub comment: Option<String>,
+
+    /// `SYNC_POINT_LOW` and `SYNC_POINT_HIGH`, the position in samples
+    pub sample: u64,
+
+    /// `SYNC_POINT_EVENT_DURATION`
+    pub event_duration: Option<u64>,
+
+    other: OtherNodes
+}
+
+/// The `BEXT` element of iXML metadata.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IXmlBext {
+    /// `BWF_DESCRIPTION`
+    pub description: Option<String>,
+
+    /// `BWF_ORIGINATOR`
+    pub originator: Option<String>,
+
+    /// `BWF_ORIGINATOR_REFERENCE`
+    pub originator_reference: Option<String>,
+
+    /// `BWF_ORIGINATION_DATE`
+    pub origination_date: Option<String>,
+
+    /// `BWF_ORIGINATION_TIME`
+    pub origination_time: Option<String>,
+
+    /// `BWF_TIME_REFERENCE_LOW` and `BWF_TIME_REFERENCE_HIGH`
+    pub time_reference: Option<u64>,
+
+    /// `BWF_VERSION`
+    pub version: Option<u16>,
+
+    /// `BWF_UMID`, as a hexadecimal string
+    pub umid: Option<String>,
+
+    /// `BWF_CODING_HISTORY`
+    pub coding_history: Option<String>,
+
+    other: OtherNodes
+}
+
+/// The `HISTORY` element of iXML metadata.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IXmlHistory {
+    /// `ORIGINAL_FILENAME`
+    pub original_filename: Option<String>,
+
+    /// `PARENT_FILENAME`
+    pub parent_filename: Option<String>,
+
+    /// `PARENT_UID`
+    pub parent_uid: Option<String>,
+
+    other: OtherNodes
+}
+
+fn element_text(element: &Element) -> String {
+    element.get_text().map(|t| t.trim().to_string()).unwrap_or_default()
+}
+
+/// Children of an element that aren't described by its struct, each with
+/// the number of described children that came before it.
+type OtherNodes = Vec<(usize, XMLNode)>;
+
+/// Calls `f` with every child element of `element`, and collects children
+/// `f` doesn't recognize, and comments, into the returned vector.
+fn collect_children<F>(element: Element, mut f: F) -> OtherNodes
+    where F: FnMut(&Element) -> bool {
+    let mut other = vec![];
+    let mut known = 0;
+    for node in element.children {
+        match node {
+            XMLNode::Element(child) => {
+                if f(&child) {
+                    known += 1;
+                } else {
+                    other.push((known, XMLNode::Element(child)));
+                }
+            },
+            XMLNode::Text(_) => (),
+            node => other.push((known, node))
+        }
+    }
+    other
+}
+
+/// Put each of `other` back among `known` children, where it was when it
+/// was read.
+fn merge_children(known: Vec<XMLNode>, other: &[(usize, XMLNode)]) -> Vec<XMLNode> {
+    let mut children = Vec::with_capacity(known.len() + other.len());
+    let mut other = other.iter().peekable();
+    for (index, node) in known.into_iter().enumerate() {
+        while let Some((_, o)) = other.next_if(|(position, _)| *position <= index) {
+            children.push(o.clone());
+        }
+        children.push(node);
+    }
+    children.extend(other.map(|(_, o)| o.clone()));
+    children
+}
+
+/// Parse the text of `element` into `field`, returns false if it can't be
+/// parsed.
+fn parse_into<T: std::str::FromStr>(element: &Element, field: &mut Option<T>) -> bool {
+    match element_text(element).parse() {
+        Ok(value) => { *field = Some(value); true },
+        Err(_) => false
+    }
+}
+
+fn set_high(value: &mut Option<u64>, high: u32) {
+    *value = Some((value.unwrap_or(0) & 0xFFFF_FFFF) | ((high as u64) << 32));
+}
+
+fn set_low(value: &mut Option<u64>, low: u32) {
+    *value = Some((value.unwrap_or(0) & !0xFFFF_FFFF) | low as u64);
+}
+
+fn text_element(name: &str, text: &str) -> XMLNode {
+    let mut element = Element::new(name);
+    element.children.push(XMLNode::Text(text.to_string()));
+    XMLNode::Element(element)
+}
+
+fn push_text(children: &mut Vec<XMLNode>, name: &str, text: &Option<String>) {
+    if let Some(t) = text {
+        children.push(text_element(name, t));
+    }
+}
+
+fn push_value<T: ToString>(children: &mut Vec<XMLNode>, name: &str, value: &Option<T>) {
+    if let Some(v) = value {
+        children.push(text_element(name, &v.to_string()));
+    }
+}
+
+fn push_split_u64(children: &mut Vec<XMLNode>, high_name: &str, low_name: &str, value: &Option<u64>) {
+    if let Some(v) = value {
+        children.push(text_element(high_name, &(v >> 32).to_string()));
+        children.push(text_element(low_name, &(v & 0xFFFF_FFFF).to_string()));
+    }
+}
+
+fn make_element(name: &str, children: Vec<XMLNode>) -> Element {
+    let mut element = Element::new(name);
+    element.children = children;
+    element
+}
+
+impl IXml {
+
+    /// Parse iXML from the content of an `iXML` chunk.
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        // iXML chunks are often padded with NULs or spaces
+        let end = data.iter().rposition(|b| *b != 0 && !b.is_ascii_whitespace())
+            .map(|p| p + 1).unwrap_or(0);
+        let root = Element::parse(&data[..end])?;
+        if root.name != "BWFXML" {
+            return Err( Error::IXmlRootNotRecognized { name: root.name } );
+        }
+        Ok( Self::from_element(root) )
+    }
+
+    /// Serialize this iXML as the content for an `iXML` chunk.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        let config = EmitterConfig::new().perform_indent(true);
+        self.to_element().write_with_config(&mut buf, config)
+            .expect("Error writing iXML");
+        buf
+    }
+
+    fn from_element(root: Element) -> Self {
+        let mut ixml = IXml::default();
+        let other = collect_children(root, |child| {
+            match child.name.as_str() {
+                "IXML_VERSION" => ixml.version = Some(element_text(child)),
+                "PROJECT" => ixml.project = Some(element_text(child)),
+                "SCENE" => ixml.scene = Some(element_text(child)),
+                "TAKE" => ixml.take = Some(element_text(child)),
+                "TAPE" => ixml.tape = Some(element_text(child)),
+                "NOTE" => ixml.note = Some(element_text(child)),
+                "SPEED" => ixml.speed = Some(IXmlSpeed::from_element(child.clone())),
+                "TRACK_LIST" => {
+                    let mut tracks = vec![];
+                    ixml.other_tracks = collect_children(child.clone(), |item| {
+                        match item.name.as_str() {
+                            "TRACK" => tracks.push(IXmlTrack::from_element(item.clone())),
+                            "TRACK_COUNT" => (),
+                            _ => return false
+                        }
+                        true
+                    });
+                    ixml.tracks = tracks;
+                },
+                "SYNC_POINT_LIST" => {
+                    let mut points = vec![];
+                    ixml.other_sync_points = collect_children(child.clone(), |item| {
+                        match item.name.as_str() {
+                            "SYNC_POINT" => points.push(IXmlSyncPoint::from_element(item.clone())),
+                            "SYNC_POINT_COUNT" => (),
+                            _ => return false
+                        }
+                        true
+                    });
+                    ixml.sync_points = points;
+                },
+                "BEXT" => ixml.bext = Some(IXmlBext::from_element(child.clone())),
+                "HISTORY" => ixml.history = Some(IXmlHistory::from_element(child.clone())),
+                _ => return false
+            }
+            true
+        });
+        ixml.other = other;
+        ixml
+    }
+
+    fn to_element(&self) -> Element {
+        let mut children = vec![];
+        push_text(&mut children, "IXML_VERSION", &self.version);
+        push_text(&mut children, "PROJECT", &self.project);
+        push_text(&mut children, "SCENE", &self.scene);
+        push_text(&mut children, "TAKE", &self.take);
+        push_text(&mut children, "TAPE", &self.tape);
+        push_text(&mut children, "NOTE", &self.note);
+
+        if let Some(speed) = &self.speed {
+            children.push(XMLNode::Element(speed.to_element()));
+        }
+
+        // The counts are written from the lists, so they stay correct if
+        // tracks or sync points are added or removed
+        if !self.tracks.is_empty() || !self.other_tracks.is_empty() {
+            let mut list = vec![text_element("TRACK_COUNT", &self.tracks.len().to_string())];
+            list.extend(self.tracks.iter().map(|t| XMLNode::Element(t.to_element())));
+            children.push(XMLNode::Element(make_element("TRACK_LIST",
+                merge_children(list, &self.other_tracks))));
+        }
+
+        if !self.sync_points.is_empty() || !self.other_sync_points.is_empty() {
+            let mut list = vec![text_element("SYNC_POINT_COUNT", &self.sync_points.len().to_string())];
+            list.extend(self.sync_points.iter().map(|p| XMLNode::Element(p.to_element())));
+            children.push(XMLNode::Element(make_element("SYNC_POINT_LIST",
+                merge_children(list, &self.other_sync_points))));
+        }
+
+        if let Some(bext) = &self.bext {
+            children.push(XMLNode::Element(bext.to_element()));
+        }
+
+        if let Some(history) = &self.history {
+            children.push(XMLNode::Element(history.to_element()));
+        }
+
+        make_element("BWFXML", merge_children(children, &self.other))
+    }
+}
+
+impl IXml {
+
+    /// Record the start timecode of the file in `SPEED`, and in `BEXT` if
+    /// present.
+    ///
+    /// `time_reference` is the start of the file in samples since midnight
+    /// at `sample_rate`, as in `Timecode::to_samples()`.
+    pub fn set_start_timecode(&mut self, timecode: &Timecode, time_reference: u64, sample_rate: u32) {
+        let speed = self.speed.get_or_insert_with(IXmlSpeed::default);
+        speed.timecode_rate = Some(timecode.rate().ixml_timecode_rate());
+        speed.timecode_flag = Some(timecode.rate().ixml_timecode_flag().to_string());
+        speed.timestamp_samples_since_midnight = Some(time_reference);
+        speed.timestamp_sample_rate = Some(sample_rate);
+        if let Some(bext) = self.bext.as_mut() {
+            bext.time_reference = Some(time_reference);
+        }
+    }
+}
+
+impl IXmlSpeed {
+
+    /// The timecode frame rate given by `TIMECODE_RATE` and `TIMECODE_FLAG`
+    pub fn frame_rate(&self) -> Option<FrameRate> {
+        FrameRate::from_ixml(self.timecode_rate.as_deref()?,
+            self.timecode_flag.as_deref().unwrap_or("NDF"))
+    }
+
+    fn from_element(element: Element) -> Self {
+        let mut speed = IXmlSpeed::default();
+        let other = collect_children(element, |child| {
+            match child.name.as_str() {
+                "NOTE" => speed.note = Some(element_text(child)),
+                "MASTER_SPEED" => speed.master_speed = Some(element_text(child)),
+                "CURRENT_SPEED" => speed.current_speed = Some(element_text(child)),
+                "TIMECODE_RATE" => speed.timecode_rate = Some(element_text(child)),
+                "TIMECODE_FLAG" => speed.timecode_flag = Some(element_text(child)),
+                "FILE_SAMPLE_RATE" => return parse_into(child, &mut speed.file_sample_rate),
+                "AUDIO_BIT_DEPTH" => return parse_into(child, &mut speed.audio_bit_depth),
+                "DIGITIZER_SAMPLE_RATE" => return parse_into(child, &mut speed.digitizer_sample_rate),
+                "TIMESTAMP_SAMPLE_RATE" => return parse_into(child, &mut speed.timestamp_sample_rate),
+                "TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_HI" => match element_text(child).parse() {
+                    Ok(high) => set_high(&mut speed.timestamp_samples_since_midnight, high),
+                    Err(_) => return false
+                },
+                "TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_LO" => match element_text(child).parse() {
+                    Ok(low) => set_low(&mut speed.timestamp_samples_since_midnight, low),
+                    Err(_) => return false
+                },
+                _ => return false
+            }
+            true
+        });
+        speed.other = other;
+        speed
+    }
+
+    fn to_element(&self) -> Element {
+        let mut children = vec![];
+        push_text(&mut children, "NOTE", &self.note);
+        push_text(&mut children, "MASTER_SPEED", &self.master_speed);
+        push_text(&mut children, "CURRENT_SPEED", &self.current_speed);
+        push_text(&mut children, "TIMECODE_RATE", &self.timecode_rate);
+        push_text(&mut children, "TIMECODE_FLAG", &self.timecode_flag);
+        push_value(&mut children, "FILE_SAMPLE_RATE", &self.file_sample_rate);
+        push_value(&mut children, "AUDIO_BIT_DEPTH", &self.audio_bit_depth);
+        push_value(&mut children, "DIGITIZER_SAMPLE_RATE", &self.digitizer_sample_rate);
+        push_split_u64(&mut children, "TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_HI",
+            "TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_LO", &self.timestamp_samples_since_midnight);
+        push_value(&mut children, "TIMESTAMP_SAMPLE_RATE", &self.timestamp_sample_rate);
+        make_element("SPEED", merge_children(children, &self.other))
+    }
+}
+
+impl IXmlTrack {
+
+    /// A new track description
+    pub fn new(channel_index: u16, interleave_index: u16, name: &str) -> Self {
+        IXmlTrack {
+            channel_index,
+            interleave_index,
+            name: Some(String::from(name)),
+            ..Default::default()
+        }
+    }
+
+    fn from_element(element: Element) -> Self {
+        let mut track = IXmlTrack::default();
+        let (mut channel_index, mut interleave_index) = (None, None);
+        let other = collect_children(element, |child| {
+            match child.name.as_str() {
+                "CHANNEL_INDEX" => return parse_into(child, &mut channel_index),
+                "INTERLEAVE_INDEX" => return parse_into(child, &mut interleave_index),
+                "NAME" => track.name = Some(element_text(child)),
+                "FUNCTION" => track.function = Some(element_text(child)),
+                _ => return false
+            }
+            true
+        });
+        track.other = other;
+        track.channel_index = channel_index.unwrap_or(0);
+        track.interleave_index = interleave_index.unwrap_or(0);
+        track
+    }
+
+    fn to_element(&self) -> Element {
+        let mut children = vec![
+            text_element("CHANNEL_INDEX", &self.channel_index.to_string()),
+            text_element("INTERLEAVE_INDEX", &self.interleave_index.to_string())
+        ];
+        push_text(&mut children, "NAME", &self.name);
+        push_text(&mut children, "FUNCTION", &self.function);
+        make_element("TRACK", merge_children(children, &self.other))
+    }
+}
+
+impl IXmlSyncPoint {
+    fn from_element(element: Element) -> Self {
+        let mut point = IXmlSyncPoint::default();
+        let mut sample = None;
+        let other = collect_children(element, |child| {
+            match child.name.as_str() {
+                "SYNC_POINT_TYPE" => point.sync_point_type = Some(element_text(child)),
+                "SYNC_POINT_FUNCTION" => point.function = Some(element_text(child)),
+                "SYNC_POINT_COMMENT" => point.comment = Some(element_text(child)),
+                "SYNC_POINT_EVENT_DURATION" => return parse_into(child, &mut point.event_duration),
+                "SYNC_POINT_HIGH" => match element_text(child).parse() {
+                    Ok(high) => set_high(&mut sample, high),
+                    Err(_) => return false
+                },
+                "SYNC_POINT_LOW" => match element_text(child).parse() {
+                    Ok(low) => set_low(&mut sample, low),
+                    Err(_) => return false
+                },
+                _ => return false
+            }
+            true
+        });
+        point.other = other;
+        point.sample = sample.unwrap_or(0);
+        point
+    }
+
+    fn to_element(&self) -> Element {
+        let mut children = vec![];
+        push_text(&mut children, "SYNC_POINT_TYPE", &self.sync_point_type);
+        push_text(&mut children, "SYNC_POINT_FUNCTION", &self.function);
+        push_text(&mut children, "SYNC_POINT_COMMENT", &self.comment);
+        children.push(text_element("SYNC_POINT_LOW", &(self.sample & 0xFFFF_FFFF).to_string()));
+        children.push(text_element("SYNC_POINT_HIGH", &(self.sample >> 32).to_string()));
+        push_value(&mut children, "SYNC_POINT_EVENT_DURATION", &self.event_duration);
+        make_element("SYNC_POINT", merge_children(children, &self.other))
+    }
+}
+
+impl IXmlBext {
+    fn from_element(element: Element) -> Self {
+        let mut bext = IXmlBext::default();
+        let other = collect_children(element, |child| {
+            match child.name.as_str() {
+                "BWF_DESCRIPTION" => bext.description = Some(element_text(child)),
+                "BWF_ORIGINATOR" => bext.originator = Some(element_text(child)),
+                "BWF_ORIGINATOR_REFERENCE" => bext.originator_reference = Some(element_text(child)),
+                "BWF_ORIGINATION_DATE" => bext.origination_date = Some(element_text(child)),
+                "BWF_ORIGINATION_TIME" => bext.origination_time = Some(element_text(child)),
+                "BWF_VERSION" => return parse_into(child, &mut bext.version),
+                "BWF_UMID" => bext.umid = Some(element_text(child)),
+                "BWF_CODING_HISTORY" => bext.coding_history = Some(element_text(child)),
+                "BWF_TIME_REFERENCE_HIGH" => match element_text(child).parse() {
+                    Ok(high) => set_high(&mut bext.time_reference, high),
+                    Err(_) => return false
+                },
+                "BWF_TIME_REFERENCE_LOW" => match element_text(child).parse() {
+                    Ok(low) => set_low(&mut bext.time_reference, low),
+                    Err(_) => return false
+                },
+                _ => return false
+            }
+            true
+        });
+        bext.other = other;
+        bext
+    }
+
+    fn to_element(&self) -> Element {
+        let mut children = vec![];
+        push_text(&mut children, "BWF_DESCRIPTION", &self.description);
+        push_text(&mut children, "BWF_ORIGINATOR", &self.originator);
+        push_text(&mut children, "BWF_ORIGINATOR_REFERENCE", &self.originator_reference);
+        push_text(&mut children, "BWF_ORIGINATION_DATE", &self.origination_date);
+        push_text(&mut children, "BWF_ORIGINATION_TIME", &self.origination_time);
+        if let Some(time_reference) = self.time_reference {
+            children.push(text_element("BWF_TIME_REFERENCE_LOW", &(time_reference & 0xFFFF_FFFF).to_string()));
+            children.push(text_element("BWF_TIME_REFERENCE_HIGH", &(time_reference >> 32).to_string()));
+        }
+        push_value(&mut children, "BWF_VERSION", &self.version);
+        push_text(&mut children, "BWF_UMID", &self.umid);
+        push_text(&mut children, "BWF_CODING_HISTORY", &self.coding_history);
+        make_element("BEXT", merge_children(children, &self.other))
+    }
+}
+
+impl From<&Bext> for IXmlBext {
+    fn from(bext: &Bext) -> Self {
+        IXmlBext {
+            description: Some(bext.description.clone()),
+            originator: Some(bext.originator.clone()),
+            originator_reference: Some(bext.originator_reference.clone()),
+            origination_date: Some(bext.origination_date.clone()),
+            origination_time: Some(bext.origination_time.clone()),
+            time_reference: Some(bext.time_reference),
+            version: Some(bext.version),
+            umid: bext.umid.map(|umid| umid.iter().map(|b| format!("{:02X}", b)).collect()),
+            coding_history: Some(bext.coding_history.clone()),
+            other: vec![]
+        }
+    }
+}
+
+impl IXmlHistory {
+    fn from_element(element: Element) -> Self {
+        let mut history = IXmlHistory::default();
+        let other = collect_children(element, |child| {
+            match child.name.as_str() {
+                "ORIGINAL_FILENAME" => history.original_filename = Some(element_text(child)),
+                "PARENT_FILENAME" => history.parent_filename = Some(element_text(child)),
+                "PARENT_UID" => history.parent_uid = Some(element_text(child)),
+                _ => return false
+            }
+            true
+        });
+        history.other = other;
+        history
+    }
+
+    fn to_element(&self) -> Element {
+        let mut children = vec![];
+        push_text(&mut children, "ORIGINAL_FILENAME", &self.original_filename);
+        push_text(&mut children, "PARENT_FILENAME", &self.parent_filename);
+        push_text(&mut children, "PARENT_UID", &self.parent_uid);
+        make_element("HISTORY", merge_children(children, &self.other))
+    }
+}
+
+#[cfg(test)]
+const TEST_IXML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<BWFXML>
+    <IXML_VERSION>2.10</IXML_VERSION>
+    <PROJECT>Test Project</PROJECT>
+    <SCENE>21A</SCENE>
+    <TAKE>3</TAKE>
+    <TAPE>SR001</TAPE>
+    <CIRCLED>TRUE</CIRCLED>
+    <NOTE>Plane overhead</NOTE>
+    <SPEED>
+        <MASTER_SPEED>24000/1001</MASTER_SPEED>
+        <CURRENT_SPEED>24000/1001</CURRENT_SPEED>
+        <TIMECODE_RATE>24000/1001</TIMECODE_RATE>
+        <TIMECODE_FLAG>NDF</TIMECODE_FLAG>
+        <FILE_SAMPLE_RATE>48000</FILE_SAMPLE_RATE>
+        <AUDIO_BIT_DEPTH>24</AUDIO_BIT_DEPTH>
+        <DIGITIZER_SAMPLE_RATE>48000</DIGITIZER_SAMPLE_RATE>
+        <TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_HI>1</TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_HI>
+        <TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_LO>2</TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_LO>
+        <TIMESTAMP_SAMPLE_RATE>48000</TIMESTAMP_SAMPLE_RATE>
+        <VENDOR_SPEED_THING>x</VENDOR_SPEED_THING>
+    </SPEED>
+    <VENDOR_LOCATION>Stage 4</VENDOR_LOCATION>
+    <TRACK_LIST>
+        <TRACK_COUNT>2</TRACK_COUNT>
+        <TRACK>
+            <CHANNEL_INDEX>1</CHANNEL_INDEX>
+            <INTERLEAVE_INDEX>1</INTERLEAVE_INDEX>
+            <NAME>Boom</NAME>
+            <FUNCTION>M-MID_SIDE</FUNCTION>
+        </TRACK>
+        <TRACK>
+            <CHANNEL_INDEX>2</CHANNEL_INDEX>
+            <INTERLEAVE_INDEX>2</INTERLEAVE_INDEX>
+            <NAME>Lav 1</NAME>
+        </TRACK>
+        <VENDOR_TRACK_THING>y</VENDOR_TRACK_THING>
+    </TRACK_LIST>
+    <SYNC_POINT_LIST>
+        <SYNC_POINT_COUNT>1</SYNC_POINT_COUNT>
+        <SYNC_POINT>
+            <SYNC_POINT_TYPE>RELATIVE</SYNC_POINT_TYPE>
+            <SYNC_POINT_FUNCTION>SLATE_GENERIC</SYNC_POINT_FUNCTION>
+            <SYNC_POINT_COMMENT>Clap</SYNC_POINT_COMMENT>
+            <SYNC_POINT_LOW>48000</SYNC_POINT_LOW>
+            <SYNC_POINT_HIGH>0</SYNC_POINT_HIGH>
+            <SYNC_POINT_EVENT_DURATION>0</SYNC_POINT_EVENT_DURATION>
+        </SYNC_POINT>
+        <!-- vendor sync comment -->
+    </SYNC_POINT_LIST>
+    <BEXT>
+        <BWF_DESCRIPTION>sSPEED=023.976-ND</BWF_DESCRIPTION>
+        <BWF_ORIGINATOR>Recorder</BWF_ORIGINATOR>
+        <BWF_TIME_REFERENCE_LOW>2</BWF_TIME_REFERENCE_LOW>
+        <BWF_TIME_REFERENCE_HIGH>1</BWF_TIME_REFERENCE_HIGH>
+        <BWF_VERSION>1</BWF_VERSION>
+    </BEXT>
+    <HISTORY>
+        <ORIGINAL_FILENAME>21A-3.wav</ORIGINAL_FILENAME>
+    </HISTORY>
+    <USER>Vendor data</USER>
+</BWFXML>
+"#;
+
+#[test]
+fn test_parse_ixml() {
+    let mut data = TEST_IXML.as_bytes().to_vec();
+    data.extend_from_slice(&[0u8; 7]);
+    let ixml = IXml::parse(&data).unwrap();
+
+    assert_eq!(ixml.version.as_deref(), Some("2.10"));
+    assert_eq!(ixml.project.as_deref(), Some("Test Project"));
+    assert_eq!(ixml.scene.as_deref(), Some("21A"));
+    assert_eq!(ixml.take.as_deref(), Some("3"));
+    assert_eq!(ixml.tape.as_deref(), Some("SR001"));
+    assert_eq!(ixml.note.as_deref(), Some("Plane overhead"));
+
+    let speed = ixml.speed.as_ref().unwrap();
+    assert_eq!(speed.timecode_rate.as_deref(), Some("24000/1001"));
+    assert_eq!(speed.timecode_flag.as_deref(), Some("NDF"));
+    assert_eq!(speed.file_sample_rate, Some(48000));
+    assert_eq!(speed.audio_bit_depth, Some(24));
+    assert_eq!(speed.timestamp_samples_since_midnight, Some(0x1_0000_0002));
+
+    assert_eq!(ixml.tracks.len(), 2);
+    assert_eq!(ixml.tracks[1].channel_index, 2);
+    assert_eq!(ixml.tracks[1].name.as_deref(), Some("Lav 1"));
+    assert_eq!(ixml.tracks[0].function.as_deref(), Some("M-MID_SIDE"));
+
+    assert_eq!(ixml.sync_points.len(), 1);
+    assert_eq!(ixml.sync_points[0].sample, 48000);
+    assert_eq!(ixml.sync_points[0].comment.as_deref(), Some("Clap"));
+
+    let bext = ixml.bext.as_ref().unwrap();
+    assert_eq!(bext.time_reference, Some(0x1_0000_0002));
+    assert_eq!(bext.version, Some(1));
+
+    assert_eq!(ixml.history.as_ref().unwrap().original_filename.as_deref(), Some("21A-3.wav"));
+}
+
+#[test]
+fn test_ixml_round_trip_preserves_unknown() {
+    let ixml = IXml::parse(TEST_IXML.as_bytes()).unwrap();
+    let bytes = ixml.to_bytes();
+    let text = String::from_utf8(bytes.clone()).unwrap();
+
+    assert!(text.contains("<CIRCLED>TRUE</CIRCLED>"));
+    assert!(text.contains("<USER>Vendor data</USER>"));
+    assert!(text.contains("<VENDOR_SPEED_THING>x</VENDOR_SPEED_THING>"));
+    assert!(text.contains("<TRACK_COUNT>2</TRACK_COUNT>"));
+    assert!(text.contains("<VENDOR_TRACK_THING>y</VENDOR_TRACK_THING>"));
+    assert!(text.contains("<!-- vendor sync comment -->"));
+
+    let position = |s: &str| text.find(s).unwrap();
+    assert!(position("<TAPE>") < position("<CIRCLED>"));
+    assert!(position("<CIRCLED>") < position("<NOTE>"));
+    assert!(position("</SPEED>") < position("<VENDOR_LOCATION>"));
+    assert!(position("<VENDOR_LOCATION>") < position("<TRACK_LIST>"));
+    assert!(position("</TRACK>") < position("<VENDOR_TRACK_THING>"));
+    assert!(position("<TIMESTAMP_SAMPLE_RATE>") < position("<VENDOR_SPEED_THING>"));
+
+    assert_eq!(IXml::parse(&bytes).unwrap(), ixml);
+    assert_eq!(IXml::parse(&bytes).unwrap().to_bytes(), bytes);
+}
+
+#[test]
+fn test_ixml_root_must_be_bwfxml() {
+    assert!(matches!(IXml::parse(b"<ebuCoreMain><PROJECT>x</PROJECT></ebuCoreMain>"),
+        Err(Error::IXmlRootNotRecognized { .. })));
+}
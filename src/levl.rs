@@ -1,3 +1,315 @@
-/// Resources
-/// 
-/// [EBU 3285 Supplement 3](https://tech.ebu.ch/docs/tech/tech3285s3.pdf) (July 2001): Peak Metadata
\ No newline at end of file
+use super::errors::Error;
+use super::sample::Sample;
+use super::bext::{OriginationDate, OriginationTime};
+
+use byteorder::{WriteBytesExt, ReadBytesExt, LittleEndian};
+
+use std::io::{Cursor, Read, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The sample format of peak values in a `PeakEnvelope`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeakFormat {
+    /// 8-bit unsigned peak values, format 1
+    UnsignedChar,
+
+    /// 16-bit unsigned peak values, format 2
+    UnsignedShort
+}
+
+impl PeakFormat {
+    fn max_value(self) -> u16 {
+        match self {
+            PeakFormat::UnsignedChar => u8::MAX as u16,
+            PeakFormat::UnsignedShort => u16::MAX
+        }
+    }
+}
+
+/// Peak envelope metadata, the content of a `levl` chunk.
+///
+/// A peak envelope is a low-resolution overview of the audio data, for
+/// drawing waveforms without reading the whole file. Each peak frame
+/// contains, for each channel, the peak value of `block_size` audio frames.
+///
+/// `WaveWriter` can generate a peak envelope while audio is being written,
+/// see `WaveWriter::generate_peak_envelope()`, or write an existing one with
+/// `WaveWriter::write_peak_envelope()`.
+///
+/// ## Resources
+///
+/// [EBU 3285 Supplement 3](https://tech.ebu.ch/docs/tech/tech3285s3.pdf) (July 2001): Peak Metadata
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeakEnvelope {
+    /// Version of the peak envelope, 0
+    pub version: u32,
+
+    /// Format of the peak values
+    pub format: PeakFormat,
+
+    /// Number of peak values for each channel in a peak frame: 1 for the
+    /// absolute peak, or 2 for the positive peak followed by the magnitude
+    /// of the negative peak
+    pub points_per_value: u32,
+
+    /// Number of audio frames per peak frame, 256 by default
+    pub block_size: u32,
+
+    /// Number of channels
+    pub channel_count: u32,
+
+    /// Number of peak frames
+    pub frame_count: u32,
+
+    /// Audio frame at which the peak of peaks occurs, or `0xFFFFFFFF` if
+    /// unknown
+    pub position_of_peak: u32,
+
+    /// Offset of the peak data from the start of the chunk, 128
+    pub offset_to_peaks: u32,
+
+    /// Time the peak envelope was created, as "YYYY:MM:DD:hh:mm:ss:uuu"
+    pub timestamp: String,
+
+    /// Peak values, interleaved by point and channel. A peak value of
+    /// `u8::MAX` or `u16::MAX`, depending on the format, is full scale.
+    pub peaks: Vec<u16>
+}
+
+const HEADER_LENGTH: usize = 120;
+const TIMESTAMP_LENGTH: usize = 28;
+const RESERVED_LENGTH: usize = 60;
+
+/// `dwOffsetToPeaks`, the header plus the chunk's signature and length
+const OFFSET_TO_PEAKS: u32 = HEADER_LENGTH as u32 + 8;
+
+/// Format a time as a peak envelope timestamp, "YYYY:MM:DD:hh:mm:ss:uuu", in UTC.
+fn format_timestamp(time: SystemTime) -> String {
+    let date = OriginationDate::from_system_time(time);
+    let tod = OriginationTime::from_system_time(time);
+    let millis = time.duration_since(UNIX_EPOCH).unwrap_or_default().subsec_millis();
+    format!("{:04}:{:02}:{:02}:{:02}:{:02}:{:02}:{:03}", date.year(), date.month(), date.day(),
+        tod.hour(), tod.minute(), tod.second(), millis)
+}
+
+impl PeakEnvelope {
+
+    /// The peak values for `channel` in the peak frame at `frame`, one for
+    /// each of `points_per_value`.
+    pub fn peak(&self, frame: usize, channel: usize) -> Option<&[u16]> {
+        if channel >= self.channel_count as usize {
+            return None;
+        }
+        let points = self.points_per_value as usize;
+        let start = (frame * self.channel_count as usize + channel) * points;
+        self.peaks.get(start..start + points)
+    }
+
+    /// Parse the content of a `levl` chunk.
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, Error> {
+        let mut cursor = Cursor::new(data);
+        let version = cursor.read_u32::<LittleEndian>()?;
+        let format = match cursor.read_u32::<LittleEndian>()? {
+            1 => PeakFormat::UnsignedChar,
+            2 => PeakFormat::UnsignedShort,
+            format => return Err(Error::PeakFormatNotRecognized { format })
+        };
+        let points_per_value = cursor.read_u32::<LittleEndian>()?;
+        let block_size = cursor.read_u32::<LittleEndian>()?;
+        let channel_count = cursor.read_u32::<LittleEndian>()?;
+        let frame_count = cursor.read_u32::<LittleEndian>()?;
+        let position_of_peak = cursor.read_u32::<LittleEndian>()?;
+        let offset_to_peaks = cursor.read_u32::<LittleEndian>()?;
+
+        let mut timestamp = [0u8; TIMESTAMP_LENGTH];
+        cursor.read_exact(&mut timestamp)?;
+        let timestamp_length = timestamp.iter().position(|c| *c == 0).unwrap_or(TIMESTAMP_LENGTH);
+        let timestamp = String::from_utf8_lossy(&timestamp[..timestamp_length]).into_owned();
+
+        // the header counts can't be trusted to fit the chunk, so read no
+        // more peaks than there is data for
+        let peaks_start = (offset_to_peaks.saturating_sub(8) as usize).min(data.len());
+        let peak_size = match format {
+            PeakFormat::UnsignedChar => 1,
+            PeakFormat::UnsignedShort => 2
+        };
+        let count = (frame_count as usize).checked_mul(channel_count as usize)
+            .and_then(|n| n.checked_mul(points_per_value as usize))
+            .unwrap_or(usize::MAX)
+            .min((data.len() - peaks_start) / peak_size);
+
+        cursor.set_position(peaks_start as u64);
+        let mut peaks = Vec::with_capacity(count);
+        for _ in 0..count {
+            peaks.push(match format {
+                PeakFormat::UnsignedChar => cursor.read_u8()? as u16,
+                PeakFormat::UnsignedShort => cursor.read_u16::<LittleEndian>()?
+            });
+        }
+
+        Ok( PeakEnvelope { version, format, points_per_value, block_size, channel_count,
+            frame_count, position_of_peak, offset_to_peaks, timestamp, peaks } )
+    }
+
+    /// The content of a `levl` chunk for this peak envelope.
+    ///
+    /// Peak data is always written immediately after the header, and
+    /// `offset_to_peaks` is written accordingly.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut cursor = Cursor::new(vec![0u8; 0]);
+        let format = match self.format {
+            PeakFormat::UnsignedChar => 1,
+            PeakFormat::UnsignedShort => 2
+        };
+        cursor.write_u32::<LittleEndian>(self.version).unwrap();
+        cursor.write_u32::<LittleEndian>(format).unwrap();
+        cursor.write_u32::<LittleEndian>(self.points_per_value).unwrap();
+        cursor.write_u32::<LittleEndian>(self.block_size).unwrap();
+        cursor.write_u32::<LittleEndian>(self.channel_count).unwrap();
+        cursor.write_u32::<LittleEndian>(self.frame_count).unwrap();
+        cursor.write_u32::<LittleEndian>(self.position_of_peak).unwrap();
+        cursor.write_u32::<LittleEndian>(OFFSET_TO_PEAKS).unwrap();
+
+        let mut timestamp = self.timestamp.as_bytes().to_vec();
+        timestamp.truncate(TIMESTAMP_LENGTH - 1);
+        timestamp.resize(TIMESTAMP_LENGTH, 0);
+        cursor.write_all(&timestamp).unwrap();
+        cursor.write_all(&[0u8; RESERVED_LENGTH]).unwrap();
+
+        for peak in self.peaks.iter() {
+            match self.format {
+                PeakFormat::UnsignedChar => cursor.write_u8(*peak as u8).unwrap(),
+                PeakFormat::UnsignedShort => cursor.write_u16::<LittleEndian>(*peak).unwrap()
+            }
+        }
+        cursor.into_inner()
+    }
+}
+
+/// Computes a `PeakEnvelope` from audio frames.
+#[derive(Debug, Clone)]
+pub(crate) struct PeakEnvelopeGenerator {
+    envelope: PeakEnvelope,
+
+    /// Largest positive and negative magnitudes in the current block, per channel
+    block_peaks: Vec<(f64, f64)>,
+    block_frames: u32,
+
+    audio_frames: u64,
+    peak_of_peaks: f64
+}
+
+impl PeakEnvelopeGenerator {
+
+    pub(crate) fn new(channel_count: u16, format: PeakFormat, points_per_value: u32, block_size: u32) -> Self {
+        assert!(points_per_value == 1 || points_per_value == 2, "points_per_value must be 1 or 2");
+        assert!(block_size > 0, "block_size must be greater than zero");
+        PeakEnvelopeGenerator {
+            envelope: PeakEnvelope {
+                version: 0,
+                format,
+                points_per_value,
+                block_size,
+                channel_count: channel_count as u32,
+                frame_count: 0,
+                position_of_peak: 0xFFFF_FFFF,
+                offset_to_peaks: OFFSET_TO_PEAKS,
+                timestamp: String::new(),
+                peaks: vec![]
+            },
+            block_peaks: vec![(0.0, 0.0); channel_count as usize],
+            block_frames: 0,
+            audio_frames: 0,
+            peak_of_peaks: 0.0
+        }
+    }
+
+    /// Add interleaved audio frames to the envelope.
+    pub(crate) fn push_frames<S: Sample>(&mut self, buffer: &[S]) {
+        let channel_count = self.block_peaks.len();
+        for frame in buffer.chunks_exact(channel_count) {
+            for (sample, peak) in frame.iter().zip(self.block_peaks.iter_mut()) {
+                let value = sample.to_float().clamp(-1.0, 1.0);
+                if value > peak.0 { peak.0 = value; }
+                if -value > peak.1 { peak.1 = -value; }
+                if value.abs() > self.peak_of_peaks {
+                    self.peak_of_peaks = value.abs();
+                    self.envelope.position_of_peak = self.audio_frames.min(0xFFFF_FFFE) as u32;
+                }
+            }
+            self.audio_frames += 1;
+            self.block_frames += 1;
+            if self.block_frames == self.envelope.block_size {
+                self.end_block();
+            }
+        }
+    }
+
+    fn end_block(&mut self) {
+        let max = self.envelope.format.max_value() as f64;
+        for peak in self.block_peaks.iter_mut() {
+            if self.envelope.points_per_value == 1 {
+                self.envelope.peaks.push((peak.0.max(peak.1) * max).round() as u16);
+            } else {
+                self.envelope.peaks.push((peak.0 * max).round() as u16);
+                self.envelope.peaks.push((peak.1 * max).round() as u16);
+            }
+            *peak = (0.0, 0.0);
+        }
+        self.envelope.frame_count += 1;
+        self.block_frames = 0;
+    }
+
+    /// The envelope of all of the frames added, timestamped now.
+    pub(crate) fn finish(mut self) -> PeakEnvelope {
+        if self.block_frames > 0 {
+            self.end_block();
+        }
+        self.envelope.timestamp = format_timestamp(SystemTime::now());
+        self.envelope
+    }
+}
+
+#[test]
+fn test_timestamp() {
+    use std::time::Duration;
+    assert_eq!(format_timestamp(UNIX_EPOCH), "1970:01:01:00:00:00:000");
+    let time = UNIX_EPOCH + Duration::from_millis(951_827_696_789);
+    assert_eq!(format_timestamp(time), "2000:02:29:12:34:56:789");
+}
+
+#[test]
+fn test_generate_round_trip() {
+    let mut generator = PeakEnvelopeGenerator::new(2, PeakFormat::UnsignedChar, 2, 4);
+    generator.push_frames(&[0.5f32, -0.25, -1.0, 0.0, 0.0, 0.0]);
+    generator.push_frames(&[0.25f32, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 2.0]);
+
+    let envelope = generator.finish();
+    assert_eq!(envelope.frame_count, 2);
+    assert_eq!(envelope.peak(0, 0), Some(&[128u16, 255][..]));
+    assert_eq!(envelope.peak(0, 1), Some(&[0u16, 64][..]));
+    assert_eq!(envelope.peak(1, 1), Some(&[255u16, 0][..]));
+    assert_eq!(envelope.peak(0, 2), None);
+    assert_eq!(envelope.position_of_peak, 1);
+
+    let bytes = envelope.to_bytes();
+    assert_eq!(bytes.len(), HEADER_LENGTH + 2 * 2 * 2);
+    assert_eq!(PeakEnvelope::parse(&bytes).unwrap(), envelope);
+}
+
+#[test]
+fn test_parse_malformed_counts() {
+    let mut generator = PeakEnvelopeGenerator::new(1, PeakFormat::UnsignedShort, 1, 4);
+    generator.push_frames(&[0.5f32; 8]);
+    let mut bytes = generator.finish().to_bytes();
+
+    // points per value, channel count and frame count
+    bytes[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+    bytes[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+    bytes[20..24].copy_from_slice(&u32::MAX.to_le_bytes());
+    let envelope = PeakEnvelope::parse(&bytes).unwrap();
+    assert_eq!(envelope.peaks, [32768, 32768]);
+
+    bytes[28..32].copy_from_slice(&u32::MAX.to_le_bytes()); // offset to peaks
+    assert!(PeakEnvelope::parse(&bytes).unwrap().peaks.is_empty());
+}
@@ -38,26 +38,68 @@ Apps we test against:
 extern crate encoding;
 extern crate byteorder;
 extern crate uuid;
+#[cfg(any(feature = "ixml", feature = "adm"))]
+extern crate xmltree;
 
 mod fourcc;
 mod errors;
 mod common_format;
 
 mod parser;
+mod raw_chunk_reader;
 mod list_form;
 
 mod chunks;
 mod cue;
 mod bext;
+mod umid;
+mod coding_history;
+mod usid;
+mod timecode;
+mod dbmd;
+mod info;
+mod sampler;
+mod cart;
+mod levl;
+mod loudness;
+#[cfg(feature = "ixml")]
+mod ixml;
+#[cfg(feature = "adm")]
+mod adm;
 mod fmt;
+mod sample;
 
 mod wavereader;
+mod frame_iter;
 mod wavewriter;
+mod editor;
 
 pub use errors::Error;
+pub use fourcc::FourCC;
+pub use parser::ChunkIteratorItem;
 pub use wavereader::{WaveReader, AudioFrameReader};
+pub use raw_chunk_reader::RawChunkReader;
+pub use list_form::ListFormItem;
+pub use frame_iter::{Frames, Blocks, ChannelSamples};
 pub use wavewriter::{WaveWriter, AudioFrameWriter};
-pub use bext::Bext;
+pub use editor::WaveEditor;
+pub use bext::{Bext, BextBuilder, OriginationDate, OriginationTime};
+pub use umid::{Umid, UmidSourcePack, UmidTimeDate};
+pub use coding_history::CodingHistoryEntry;
+pub use usid::Usid;
+pub use timecode::{Timecode, FrameRate, Pull};
+#[cfg(feature = "ixml")]
+pub use ixml::{IXml, IXmlSpeed, IXmlTrack, IXmlSyncPoint, IXmlBext, IXmlHistory};
+#[cfg(feature = "adm")]
+pub use adm::{Adm, AudioProgramme, AudioContent, AudioObject, AudioPackFormat, AudioChannelFormat,
+    AudioBlockFormat, AudioStreamFormat, AudioTrackFormat, AudioTrackUid};
 pub use fmt::{WaveFmt, WaveFmtExtended, ChannelDescriptor, ChannelMask, ADMAudioID};
 pub use common_format::CommonFormat;
-pub use cue::Cue;
\ No newline at end of file
+pub use sample::{Sample, I24};
+pub use cue::{Cue, LabeledText};
+pub use info::Info;
+pub use loudness::LoudnessMeter;
+pub use levl::{PeakEnvelope, PeakFormat};
+pub use sampler::{Sampler, SampleLoop, LoopType, Instrument};
+pub use cart::{Cart, CartTimer};
+pub use dbmd::{DolbyMetadata, DolbyMetadataSegment, DolbyEMetadata, DolbyAtmosMetadata};
\ No newline at end of file
@@ -1,9 +1,16 @@
-use super::fourcc::{FourCC, ReadFourCC};
-use byteorder::{ReadBytesExt, LittleEndian};
-use std::io::{Cursor, Error, Read};
+use super::fourcc::{FourCC, ReadFourCC, WriteFourCC};
+use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
+use encoding::EncodingRef;
+use encoding::label::encoding_from_windows_code_page;
+use std::io::{Cursor, Error, Read, Write};
 
+/// A sub-chunk of a `LIST` chunk.
+#[derive(Debug, Clone, PartialEq)]
 pub struct ListFormItem {
+    /// The sub-chunk's signature
     pub signature : FourCC,
+
+    /// The sub-chunk's content, not including any pad byte
     pub contents : Vec<u8>
 }
 
@@ -37,4 +44,46 @@ pub fn collect_list_form(list_contents :& [u8]) -> Result<Vec<ListFormItem>, Err
     
 
     Ok( retval )
-}
\ No newline at end of file
+}
+
+/// The inverse of `collect_list_form`, gives the contents of a LIST chunk
+/// with the given form type containing each of `items`
+/// 
+pub fn compile_list_form(form: FourCC, items: &[ListFormItem]) -> Result<Vec<u8>, Error> {
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    cursor.write_fourcc(form)?;
+
+    for item in items {
+        cursor.write_fourcc(item.signature)?;
+        cursor.write_u32::<LittleEndian>(item.contents.len() as u32)?;
+        cursor.write_all(&item.contents)?;
+        if item.contents.len() % 2 == 1 {
+            cursor.write_u8(0)?;
+        }
+    }
+
+    Ok( cursor.into_inner() )
+}
+
+/// The encoding for a Windows code page, as used by the text in `adtl` and
+/// `INFO` lists, if it's known and not UTF-8
+pub(crate) fn code_page_encoding(code_page: u16) -> Option<EncodingRef> {
+    match code_page {
+        0 | 65001 => None,
+        cp => encoding_from_windows_code_page(cp as usize)
+    }
+}
+
+#[test]
+fn test_list_form_round_trip() {
+    let items = vec![
+        ListFormItem { signature: FourCC::make(b"labl"), contents: vec![1, 0, 0, 0, b'a', 0] },
+        ListFormItem { signature: FourCC::make(b"note"), contents: vec![2, 0, 0, 0, b'b'] },
+    ];
+
+    let buf = compile_list_form(FourCC::make(b"adtl"), &items).unwrap();
+    assert_eq!(buf.len(), 4 + 8 + 6 + 8 + 5 + 1);
+
+    let collected = collect_list_form(&buf).unwrap();
+    assert_eq!(collected, items);
+}
@@ -0,0 +1,430 @@
+use std::io::{Read, Seek};
+
+use super::errors::Error;
+use super::fmt::{WaveFmt, ChannelMask};
+use super::bext::{Bext, LU, LUFS, Decibels};
+use super::sample::Sample;
+use super::wavereader::AudioFrameReader;
+
+/// Measures loudness and true peak, as specified by EBU R128.
+///
+/// Audio is K-weighted and measured in 100 millisecond steps. The meter
+/// reports integrated loudness (gated as described by ITU-R BS.1770-4),
+/// loudness range (EBU Tech 3342), maximum momentary (400 ms) and
+/// short-term (3 s) loudness, and the maximum true peak level, measured by
+/// oversampling four times.
+///
+/// A meter can measure audio from an `AudioFrameReader` with `measure()`,
+/// or can be fed frames while they're written to a file, see
+/// `WaveWriter::measure_loudness()`. The results can be recorded in a
+/// version 2 `Bext` with `fill_bext()`.
+///
+/// ```
+/// use bwavfile::{LoudnessMeter, WaveFmt};
+///
+/// let format = WaveFmt::new_pcm_stereo(48000, 24);
+/// let mut meter = LoudnessMeter::new(&format);
+///
+/// // 1 kHz tone at -23 dBFS for 5 seconds
+/// let amplitude = 10f64.powf(-23.0 / 20.0);
+/// let frames : Vec<f64> = (0..48000 * 5)
+///     .map(|i| amplitude * (2.0 * std::f64::consts::PI * 1000.0 * i as f64 / 48000.0).sin())
+///     .flat_map(|s| vec![s, s])
+///     .collect();
+/// meter.push_frames(&frames);
+///
+/// let integrated = meter.integrated_loudness().unwrap();
+/// assert!((integrated - -23.0).abs() < 0.1);
+/// ```
+///
+/// ## Resources
+/// - [ITU-R BS.1770-4](https://www.itu.int/rec/R-REC-BS.1770/en) (October 2015), "Algorithms to measure audio programme loudness and true-peak audio level"
+/// - [EBU R 128](https://tech.ebu.ch/docs/r/r128.pdf) (August 2020), "Loudness normalisation and permitted maximum level of audio signals"
+/// - [EBU Tech 3341](https://tech.ebu.ch/docs/tech/tech3341.pdf) (October 2023), "Loudness Metering: 'EBU Mode' metering to supplement EBU R 128 loudness normalization"
+/// - [EBU Tech 3342](https://tech.ebu.ch/docs/tech/tech3342.pdf) (October 2023), "Loudness Range: A measure to supplement EBU R 128 loudness normalization"
+#[derive(Debug, Clone)]
+pub struct LoudnessMeter {
+    channels: Vec<ChannelState>,
+
+    /// Frames in each 100 ms step
+    step_length: usize,
+
+    /// Frames so far in the current step
+    step_position: usize,
+
+    /// Weighted energy of each complete step, the last 30 are kept
+    steps: Vec<f64>,
+
+    /// Energy of each 400 ms momentary block
+    momentary_blocks: Vec<f64>,
+
+    /// Energy of each 3 s short-term block
+    short_term_blocks: Vec<f64>,
+
+    max_momentary: Option<f64>,
+    max_short_term: Option<f64>
+}
+
+#[derive(Debug, Clone)]
+struct ChannelState {
+    weight: f64,
+    shelf: Biquad,
+    high_pass: Biquad,
+    sum_of_squares: f64,
+
+    /// Most recent samples, newest last, for true peak interpolation
+    history: [f64; TRUE_PEAK_TAPS],
+    true_peak: f64
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b0: f64, b1: f64, b2: f64,
+    a1: f64, a2: f64,
+    z1: f64, z2: f64
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        // transposed direct form II
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+}
+
+const STEPS_PER_MOMENTARY: usize = 4;
+const STEPS_PER_SHORT_TERM: usize = 30;
+
+const ABSOLUTE_GATE: f64 = -70.0;
+const INTEGRATED_RELATIVE_GATE: f64 = -10.0;
+const RANGE_RELATIVE_GATE: f64 = -20.0;
+
+const TRUE_PEAK_TAPS: usize = 12;
+
+/// Interpolation filter phases for 4x oversampling, from ITU-R BS.1770-4
+/// Annex 2
+const TRUE_PEAK_PHASES: [[f64; TRUE_PEAK_TAPS]; 4] = [
+    [ 0.0017089843750,  0.0109863281250, -0.0196533203125,  0.0332031250000,
+     -0.0594482421875,  0.1373291015625,  0.9721679687500, -0.1022949218750,
+      0.0476074218750, -0.0266113281250,  0.0148925781250, -0.0083007812500],
+    [-0.0291748046875,  0.0292968750000, -0.0517578125000,  0.0891113281250,
+     -0.1665039062500,  0.4650878906250,  0.7797851562500, -0.2003173828125,
+      0.1015625000000, -0.0582275390625,  0.0330810546875, -0.0189208984375],
+    [-0.0189208984375,  0.0330810546875, -0.0582275390625,  0.1015625000000,
+     -0.2003173828125,  0.7797851562500,  0.4650878906250, -0.1665039062500,
+      0.0891113281250, -0.0517578125000,  0.0292968750000, -0.0291748046875],
+    [-0.0083007812500,  0.0148925781250, -0.0266113281250,  0.0476074218750,
+     -0.1022949218750,  0.9721679687500,  0.1373291015625, -0.0594482421875,
+      0.0332031250000, -0.0196533203125,  0.0109863281250,  0.0017089843750],
+];
+
+/// The K-weighting pre-filter, a high shelf, for a sample rate
+fn shelf_filter(sample_rate: f64) -> Biquad {
+    let f0 = 1681.974450955533;
+    let gain = 3.999843853973347;
+    let q = 0.7071752369554196;
+
+    let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+    let vh = 10f64.powf(gain / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    Biquad {
+        b0: (vh + vb * k / q + k * k) / a0,
+        b1: 2.0 * (k * k - vh) / a0,
+        b2: (vh - vb * k / q + k * k) / a0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+        z1: 0.0, z2: 0.0
+    }
+}
+
+/// The K-weighting RLB filter, a high pass, for a sample rate
+fn high_pass_filter(sample_rate: f64) -> Biquad {
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+
+    let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    Biquad {
+        b0: 1.0, b1: -2.0, b2: 1.0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+        z1: 0.0, z2: 0.0
+    }
+}
+
+/// Channel weighting, surround channels are +1.5 dB and LFE is ignored
+fn channel_weight(speaker: ChannelMask) -> f64 {
+    match speaker {
+        ChannelMask::LowFrequency => 0.0,
+        ChannelMask::BackLeft | ChannelMask::BackRight |
+        ChannelMask::SideLeft | ChannelMask::SideRight => 1.41,
+        _ => 1.0
+    }
+}
+
+fn loudness(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+fn energy(loudness: f64) -> f64 {
+    10f64.powf((loudness + 0.691) / 10.0)
+}
+
+/// Blocks passing the absolute gate, and the relative gate `relative` LU
+/// below their mean.
+fn gated(blocks: &[f64], relative: f64) -> Vec<f64> {
+    let absolute : Vec<f64> = blocks.iter().cloned()
+        .filter(|e| loudness(*e) > ABSOLUTE_GATE)
+        .collect();
+    if absolute.is_empty() {
+        return absolute;
+    }
+    let mean = absolute.iter().sum::<f64>() / absolute.len() as f64;
+    let threshold = energy(loudness(mean) + relative);
+    absolute.into_iter().filter(|e| *e > threshold).collect()
+}
+
+impl LoudnessMeter {
+
+    /// A new meter for audio in `format`.
+    ///
+    /// Channels are weighted by their speaker assignment as given by
+    /// `WaveFmt::channels()`.
+    pub fn new(format: &WaveFmt) -> Self {
+        let sample_rate = format.sample_rate as f64;
+        let channels = format.channels().iter().map(|c| ChannelState {
+            weight: channel_weight(c.speaker),
+            shelf: shelf_filter(sample_rate),
+            high_pass: high_pass_filter(sample_rate),
+            sum_of_squares: 0.0,
+            history: [0.0; TRUE_PEAK_TAPS],
+            true_peak: 0.0
+        }).collect();
+
+        LoudnessMeter {
+            channels,
+            step_length: (format.sample_rate as usize / 10).max(1),
+            step_position: 0,
+            steps: vec![],
+            momentary_blocks: vec![],
+            short_term_blocks: vec![],
+            max_momentary: None,
+            max_short_term: None
+        }
+    }
+
+    /// Measure audio from a frame reader, from its current position to the
+    /// end of the audio data.
+    pub fn measure<R: Read + Seek>(reader: &mut AudioFrameReader<R>) -> Result<Self, Error> {
+        let format = reader.format();
+        let mut meter = LoudnessMeter::new(&format);
+        let mut buffer = format.create_frame_buffer::<f64>(4096);
+        loop {
+            let frames = reader.read_frames(&mut buffer)? as usize;
+            if frames == 0 { break; }
+            meter.push_frames(&buffer[..frames * format.channel_count as usize]);
+        }
+        Ok( meter )
+    }
+
+    /// Add interleaved audio frames to the measurement.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `buffer.len()` modulo the meter's
+    /// channel count is not zero.
+    pub fn push_frames<S: Sample>(&mut self, buffer: &[S]) {
+        let channel_count = self.channels.len();
+        assert_eq!(buffer.len() % channel_count, 0,
+            "frames buffer does not contain a number of samples % channel_count == 0");
+
+        for frame in buffer.chunks_exact(channel_count) {
+            for (sample, channel) in frame.iter().zip(self.channels.iter_mut()) {
+                let x = sample.to_float();
+                channel.push_true_peak(x);
+                let y = channel.high_pass.process(channel.shelf.process(x));
+                channel.sum_of_squares += y * y;
+            }
+            self.step_position += 1;
+            if self.step_position == self.step_length {
+                self.end_step();
+            }
+        }
+    }
+
+    fn end_step(&mut self) {
+        let step_length = self.step_length as f64;
+        let step = self.channels.iter_mut().map(|c| {
+            let e = c.weight * c.sum_of_squares / step_length;
+            c.sum_of_squares = 0.0;
+            e
+        }).sum::<f64>();
+        self.step_position = 0;
+
+        self.steps.push(step);
+        if self.steps.len() > STEPS_PER_SHORT_TERM {
+            self.steps.remove(0);
+        }
+
+        if self.steps.len() >= STEPS_PER_MOMENTARY {
+            let block = self.steps[self.steps.len() - STEPS_PER_MOMENTARY..].iter().sum::<f64>()
+                / STEPS_PER_MOMENTARY as f64;
+            self.momentary_blocks.push(block);
+            self.max_momentary = Some(self.max_momentary.map_or(block, |m| m.max(block)));
+        }
+
+        if self.steps.len() == STEPS_PER_SHORT_TERM {
+            let block = self.steps.iter().sum::<f64>() / STEPS_PER_SHORT_TERM as f64;
+            self.short_term_blocks.push(block);
+            self.max_short_term = Some(self.max_short_term.map_or(block, |m| m.max(block)));
+        }
+    }
+
+    /// Integrated loudness in LUFS.
+    ///
+    /// Returns `None` if less than 400 ms of audio has been measured, or if
+    /// all of the audio is below the absolute gate of -70 LUFS.
+    pub fn integrated_loudness(&self) -> Option<LUFS> {
+        let blocks = gated(&self.momentary_blocks, INTEGRATED_RELATIVE_GATE);
+        if blocks.is_empty() {
+            None
+        } else {
+            Some( loudness(blocks.iter().sum::<f64>() / blocks.len() as f64) as LUFS )
+        }
+    }
+
+    /// Loudness range in LU.
+    ///
+    /// Returns `None` if less than 3 seconds of audio has been measured, or
+    /// if all of the audio is below the absolute gate of -70 LUFS.
+    pub fn loudness_range(&self) -> Option<LU> {
+        let mut values : Vec<f64> = gated(&self.short_term_blocks, RANGE_RELATIVE_GATE)
+            .into_iter().map(loudness).collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let percentile = |p: f64| values[((values.len() - 1) as f64 * p).round() as usize];
+        Some( (percentile(0.95) - percentile(0.10)) as LU )
+    }
+
+    /// Maximum momentary loudness in LUFS, `None` if less than 400 ms of
+    /// audio has been measured.
+    pub fn max_momentary_loudness(&self) -> Option<LUFS> {
+        self.max_momentary.map(|e| loudness(e) as LUFS)
+    }
+
+    /// Maximum short-term loudness in LUFS, `None` if less than 3 seconds of
+    /// audio has been measured.
+    pub fn max_short_term_loudness(&self) -> Option<LUFS> {
+        self.max_short_term.map(|e| loudness(e) as LUFS)
+    }
+
+    /// Maximum true peak level in dBTP, of all channels.
+    ///
+    /// Returns `None` if no audio has been measured or the audio is silent.
+    pub fn max_true_peak_level(&self) -> Option<Decibels> {
+        let peak = self.channels.iter().map(|c| c.true_peak).fold(0.0, f64::max);
+        if peak > 0.0 {
+            Some( (20.0 * peak.log10()) as Decibels )
+        } else {
+            None
+        }
+    }
+
+    /// Record this measurement in `bext`.
+    ///
+    /// `bext` is upgraded to version 2 if it's an earlier version, and its
+    /// loudness fields are set. Values the meter can't measure are set to
+    /// `None`.
+    pub fn fill_bext(&self, bext: &mut Bext) {
+        if bext.version < 2 {
+            bext.version = 2;
+        }
+        if bext.umid.is_none() {
+            bext.umid = Some([0u8; 64]);
+        }
+        bext.loudness_value = self.integrated_loudness();
+        bext.loudness_range = self.loudness_range();
+        bext.max_true_peak_level = self.max_true_peak_level();
+        bext.max_momentary_loudness = self.max_momentary_loudness();
+        bext.max_short_term_loudness = self.max_short_term_loudness();
+    }
+}
+
+impl ChannelState {
+    fn push_true_peak(&mut self, x: f64) {
+        self.history.copy_within(1.., 0);
+        self.history[TRUE_PEAK_TAPS - 1] = x;
+
+        let mut peak = x.abs();
+        for phase in TRUE_PEAK_PHASES.iter() {
+            let y : f64 = phase.iter().zip(self.history.iter().rev())
+                .map(|(c, s)| c * s).sum();
+            peak = peak.max(y.abs());
+        }
+        self.true_peak = self.true_peak.max(peak);
+    }
+}
+
+#[cfg(test)]
+fn stereo_tone(frequency: f64, dbfs: f64, seconds: f64, sample_rate: u32) -> Vec<f64> {
+    let amplitude = 10f64.powf(dbfs / 20.0);
+    let count = (seconds * sample_rate as f64) as usize;
+    (0..count)
+        .map(|i| amplitude * (2.0 * std::f64::consts::PI * frequency * i as f64 / sample_rate as f64).sin())
+        .flat_map(|s| vec![s, s])
+        .collect()
+}
+
+#[test]
+fn test_tech_3341_case_1() {
+    // Stereo 1 kHz at -23 dBFS, M, S and I should be -23 LUFS +/- 0.1 LU
+    let mut meter = LoudnessMeter::new(&WaveFmt::new_pcm_stereo(48000, 24));
+    meter.push_frames(&stereo_tone(1000.0, -23.0, 20.0, 48000));
+
+    assert!((meter.integrated_loudness().unwrap() - -23.0).abs() < 0.1);
+    assert!((meter.max_momentary_loudness().unwrap() - -23.0).abs() < 0.1);
+    assert!((meter.max_short_term_loudness().unwrap() - -23.0).abs() < 0.1);
+    assert!(meter.loudness_range().unwrap().abs() < 0.1);
+    assert!((meter.max_true_peak_level().unwrap() - -23.0).abs() < 0.2);
+}
+
+#[test]
+fn test_tech_3342_case_1() {
+    // Stereo 1 kHz at -20 dBFS then -30 dBFS, LRA should be 10 LU +/- 1 LU
+    let mut meter = LoudnessMeter::new(&WaveFmt::new_pcm_stereo(44100, 16));
+    meter.push_frames(&stereo_tone(1000.0, -20.0, 20.0, 44100));
+    meter.push_frames(&stereo_tone(1000.0, -30.0, 20.0, 44100));
+
+    assert!((meter.loudness_range().unwrap() - 10.0).abs() < 1.0);
+}
+
+#[test]
+fn test_true_peak_inter_sample() {
+    // A tone at fs/4 with a 45 degree phase offset has sample peaks 3 dB
+    // below its true peak
+    let amplitude = 0.5f64;
+    let frames : Vec<f64> = (0..48000)
+        .map(|i| amplitude * (std::f64::consts::FRAC_PI_2 * i as f64 + std::f64::consts::FRAC_PI_4).sin())
+        .collect();
+    let mut meter = LoudnessMeter::new(&WaveFmt::new_pcm_mono(48000, 24));
+    meter.push_frames(&frames);
+
+    let sample_peak = 20.0 * (amplitude * std::f64::consts::FRAC_1_SQRT_2).log10();
+    let true_peak = meter.max_true_peak_level().unwrap() as f64;
+    assert!(true_peak > sample_peak + 2.5);
+    assert!((true_peak - 20.0 * amplitude.log10()).abs() < 0.6);
+}
+
+#[test]
+fn test_silence() {
+    let mut meter = LoudnessMeter::new(&WaveFmt::new_pcm_mono(48000, 24));
+    meter.push_frames(&vec![0.0f32; 48000 * 4]);
+    assert_eq!(meter.integrated_loudness(), None);
+    assert_eq!(meter.loudness_range(), None);
+    assert_eq!(meter.max_true_peak_level(), None);
+}
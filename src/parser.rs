@@ -9,7 +9,7 @@ use byteorder::ReadBytesExt;
 
 use super::errors::Error;
 use super::fourcc::{FourCC, ReadFourCC};
-use super::fourcc::{RIFF_SIG, RF64_SIG, BW64_SIG, WAVE_SIG, DS64_SIG, DATA_SIG};
+use super::fourcc::{RIFF_SIG, RF64_SIG, BW64_SIG, WAVE_SIG, DS64_SIG, DATA_SIG, LIST_SIG};
 
 // just for your reference...
 // RF64 documentation https://www.itu.int/dms_pubrec/itu-r/rec/bs/R-REC-BS.2088-1-201910-I!!PDF-E.pdf
@@ -24,7 +24,7 @@ pub enum Event {
     ReadHeader { signature: FourCC, length_field: u32 },
     ReadRF64Header { signature: FourCC },
     ReadDS64 {file_size: u64, long_sizes: HashMap<FourCC,u64> },
-    BeginChunk { signature: FourCC, content_start: u64, content_length: u64 },
+    BeginChunk { signature: FourCC, content_start: u64, content_length: u64, form: Option<FourCC> },
     Failed { error: Error },
     FinishParse
 }
@@ -45,11 +45,21 @@ pub struct Parser<R: Read + Seek> {
     ds64state: HashMap<FourCC,u64>
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// A chunk in a Wave file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ChunkIteratorItem {
+    /// The chunk's four-character signature
     pub signature: FourCC,
+
+    /// File offset of the start of the chunk's content
     pub start: u64,
-    pub length: u64
+
+    /// Length of the chunk's content, in bytes
+    pub length: u64,
+
+    /// The form type of a `LIST` chunk, the first four bytes of its
+    /// content. This is `None` for any other chunk.
+    pub form: Option<FourCC>
 }
 
 impl<R: Read + Seek> Parser<R> {
@@ -72,8 +82,8 @@ impl<R: Read + Seek> Parser<R> {
 
     pub fn into_chunk_iterator(self) -> impl Iterator<Item = Result<ChunkIteratorItem, Error>>{
         self.filter_map({|event|
-            if let Event::BeginChunk {signature , content_start, content_length } = event {
-                Some(Ok(ChunkIteratorItem {signature, start: content_start, length: content_length }))
+            if let Event::BeginChunk {signature , content_start, content_length, form } = event {
+                Some(Ok(ChunkIteratorItem {signature, start: content_start, length: content_length, form }))
             } else if let Event::Failed { error }  = event {
                 Some(Err(error))
             } else {
@@ -222,12 +232,21 @@ impl<R: Read + Seek> Parser<R> {
             }
 
             let this_displacement :u64 = if this_size % 2 == 1 { this_size + 1 } else { this_size }; 
-            self.stream.seek(Current(this_displacement as i64))?;
+
+            let form = if this_fourcc == LIST_SIG && this_size >= 4 {
+                let form = self.stream.read_fourcc()?;
+                self.stream.seek(Current(this_displacement as i64 - 4))?;
+                Some(form)
+            } else {
+                self.stream.seek(Current(this_displacement as i64))?;
+                None
+            };
 
             event = Event::BeginChunk {
                 signature: this_fourcc,
                 content_start: at + 8,
-                content_length: this_size
+                content_length: this_size,
+                form
             };
             
             state = State::ReadyForChunk {
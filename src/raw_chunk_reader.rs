@@ -0,0 +1,75 @@
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
+
+/// A bounded view of the content of a single chunk.
+///
+/// Created by `WaveReader::chunk_reader()`. Reads begin at the start of the
+/// chunk's content and end at the end of the chunk, and seeks are relative
+/// to the start of the chunk.
+#[derive(Debug)]
+pub struct RawChunkReader<'a, R: Read + Seek> {
+    reader: &'a mut R,
+    start: u64,
+    length: u64,
+    position: u64
+}
+
+impl<'a, R: Read + Seek> RawChunkReader<'a, R> {
+    pub(crate) fn new(reader: &'a mut R, start: u64, length: u64) -> Self {
+        RawChunkReader { reader, start, length, position: 0 }
+    }
+
+    /// Length of the chunk's content, in bytes
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+}
+
+impl<'a, R: Read + Seek> Read for RawChunkReader<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.length {
+            return Ok(0);
+        }
+
+        let remaining = self.length - self.position;
+        let to_read = (buf.len() as u64).min(remaining) as usize;
+
+        self.reader.seek(SeekFrom::Start(self.start + self.position))?;
+        let read = self.reader.read(&mut buf[..to_read])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<'a, R: Read + Seek> Seek for RawChunkReader<'a, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_position = match pos {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::Current(d) => self.position.checked_add_signed(d),
+            SeekFrom::End(d) => self.length.checked_add_signed(d)
+        };
+
+        match new_position {
+            Some(p) => { self.position = p; Ok(p) },
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position"))
+        }
+    }
+}
+
+#[test]
+fn test_bounded_read() {
+    let mut cursor = io::Cursor::new((0u8..16).collect::<Vec<u8>>());
+    let mut reader = RawChunkReader::new(&mut cursor, 4, 8);
+
+    let mut buf = vec![];
+    reader.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, [4, 5, 6, 7, 8, 9, 10, 11]);
+
+    reader.seek(SeekFrom::End(-2)).unwrap();
+    let mut buf = [0u8; 4];
+    assert_eq!(reader.read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf[..2], [10, 11]);
+
+    assert!(reader.seek(SeekFrom::Current(-20)).is_err());
+}
@@ -0,0 +1,251 @@
+use std::fmt::Debug;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use super::common_format::CommonFormat;
+use super::fmt::WaveFmt;
+
+/// A 24-bit signed integer sample.
+///
+/// The sample value is held in the low 24 bits of an `i32`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct I24(i32);
+
+impl I24 {
+    /// The largest value of a 24-bit sample.
+    pub const MAX: I24 = I24(0x7F_FFFF);
+
+    /// The smallest value of a 24-bit sample.
+    pub const MIN: I24 = I24(-0x80_0000);
+
+    /// Create a new 24-bit sample, clipping `value` to the 24-bit range.
+    pub fn new(value: i32) -> Self {
+        I24(value.clamp(Self::MIN.0, Self::MAX.0))
+    }
+
+    /// The value of the sample.
+    pub fn value(self) -> i32 {
+        self.0
+    }
+}
+
+impl From<I24> for i32 {
+    fn from(sample: I24) -> Self {
+        sample.0
+    }
+}
+
+/// An audio sample type that can be read from or written to a Wave file.
+///
+/// `Sample` is implemented for `i16`, `I24`, `i32`, `f32` and `f64`, and
+/// `AudioFrameReader` and `AudioFrameWriter` will convert between any of
+/// these types and the format of the file's audio data:
+///
+/// - Integer samples are scaled to the integer size of the file, so an
+///   `i16` sample written to a 24-bit file is shifted left by 8 bits and a
+///   24-bit sample read into an `i16` is shifted right by 8 bits.
+/// - Float samples are full-scale in the range `-1.0..1.0`. Float samples
+///   converted to integers are rounded and clipped to the integer range.
+///
+/// `i32` samples are full-scale too, so a 24-bit sample read as an `i32` is
+/// shifted left by 8 bits. This differs from the "right-aligned" `i32`
+/// samples of `AudioFrameReader::read_integer_frames()`,
+/// `AudioFrameWriter::write_integer_frames()` and `WaveFmt::unpack_frames()`,
+/// which keep the sample size of the file.
+///
+/// ```
+/// use bwavfile::{Sample, I24};
+///
+/// assert_eq!(i16::from_sample(I24::new(0x12_3456)), 0x1234);
+/// assert_eq!(I24::from_sample(0x1234i16), I24::new(0x12_3400));
+/// assert_eq!(f32::from_sample(i16::MIN), -1.0);
+/// assert_eq!(i16::from_sample(2.0f32), i16::MAX);
+/// ```
+pub trait Sample: Copy + Default + Debug + PartialEq {
+
+    /// Convert an integer sample `bits` wide into this type.
+    fn from_int(value: i32, bits: u16) -> Self;
+
+    /// Convert this sample into an integer sample `bits` wide.
+    fn to_int(self, bits: u16) -> i32;
+
+    /// Convert a float sample into this type.
+    fn from_float(value: f64) -> Self;
+
+    /// Convert this sample into a float sample.
+    fn to_float(self) -> f64;
+
+    /// Convert a sample of another type into this type.
+    fn from_sample<S: Sample>(sample: S) -> Self;
+}
+
+/// Shift an integer sample `from_bits` wide to one `to_bits` wide
+fn rescale_int(value: i32, from_bits: u16, to_bits: u16) -> i32 {
+    if from_bits >= to_bits {
+        value >> (from_bits - to_bits)
+    } else {
+        value << (to_bits - from_bits)
+    }
+}
+
+fn int_to_float(value: i32, bits: u16) -> f64 {
+    value as f64 / (1i64 << (bits - 1)) as f64
+}
+
+fn float_to_int(value: f64, bits: u16) -> i32 {
+    let full_scale = (1i64 << (bits - 1)) as f64;
+    (value * full_scale).round().clamp(-full_scale, full_scale - 1.0) as i32
+}
+
+impl Sample for i16 {
+    fn from_int(value: i32, bits: u16) -> Self { rescale_int(value, bits, 16) as i16 }
+    fn to_int(self, bits: u16) -> i32 { rescale_int(self as i32, 16, bits) }
+    fn from_float(value: f64) -> Self { float_to_int(value, 16) as i16 }
+    fn to_float(self) -> f64 { int_to_float(self as i32, 16) }
+    fn from_sample<S: Sample>(sample: S) -> Self { sample.to_int(16) as i16 }
+}
+
+impl Sample for I24 {
+    fn from_int(value: i32, bits: u16) -> Self { I24(rescale_int(value, bits, 24)) }
+    fn to_int(self, bits: u16) -> i32 { rescale_int(self.0, 24, bits) }
+    fn from_float(value: f64) -> Self { I24(float_to_int(value, 24)) }
+    fn to_float(self) -> f64 { int_to_float(self.0, 24) }
+    fn from_sample<S: Sample>(sample: S) -> Self { I24(sample.to_int(24)) }
+}
+
+impl Sample for i32 {
+    fn from_int(value: i32, bits: u16) -> Self { rescale_int(value, bits, 32) }
+    fn to_int(self, bits: u16) -> i32 { rescale_int(self, 32, bits) }
+    fn from_float(value: f64) -> Self { float_to_int(value, 32) }
+    fn to_float(self) -> f64 { int_to_float(self, 32) }
+    fn from_sample<S: Sample>(sample: S) -> Self { sample.to_int(32) }
+}
+
+impl Sample for f32 {
+    fn from_int(value: i32, bits: u16) -> Self { int_to_float(value, bits) as f32 }
+    fn to_int(self, bits: u16) -> i32 { float_to_int(self as f64, bits) }
+    fn from_float(value: f64) -> Self { value as f32 }
+    fn to_float(self) -> f64 { self as f64 }
+    fn from_sample<S: Sample>(sample: S) -> Self { sample.to_float() as f32 }
+}
+
+impl Sample for f64 {
+    fn from_int(value: i32, bits: u16) -> Self { int_to_float(value, bits) }
+    fn to_int(self, bits: u16) -> i32 { float_to_int(self, bits) }
+    fn from_float(value: f64) -> Self { value }
+    fn to_float(self) -> f64 { self }
+    fn from_sample<S: Sample>(sample: S) -> Self { sample.to_float() }
+}
+
+/// The binary encoding of a single sample in a Wave file's audio data.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SampleEncoding {
+    /// Unsigned 8-bit integer, offset by 0x80
+    UInt8,
+    Int16,
+    Int24,
+    Int32,
+    Float32,
+    Float64
+}
+
+impl SampleEncoding {
+
+    /// The encoding of samples in audio data with the given format.
+    ///
+    /// ### Panics
+    ///
+    /// This method will panic if the audio data is not integer or IEEE
+    /// float PCM, or if the sample size is not supported.
+    pub fn for_format(format: &WaveFmt) -> Self {
+        let is_float = matches!(format.common_format(),
+            CommonFormat::IeeeFloatPCM | CommonFormat::AmbisonicBFormatIeeeFloatPCM);
+
+        match (is_float, format.valid_bits_per_sample(), format.bits_per_sample) {
+            (true, _, 32) => Self::Float32,
+            (true, _, 64) => Self::Float64,
+            (false, 0..=8,8) => Self::UInt8,
+            (false, 9..=16,16) => Self::Int16,
+            (false, 10..=24,24) => Self::Int24,
+            (false, 25..=32,32) => Self::Int32,
+            (_, b,_)=> panic!("Unrecognized sample format, bits per sample {}, channels {}, block_alignment {}",
+                b, format.channel_count, format.block_alignment)
+        }
+    }
+
+    /// Count of bytes in each encoded sample
+    pub fn byte_count(self) -> usize {
+        match self {
+            Self::UInt8 => 1,
+            Self::Int16 => 2,
+            Self::Int24 => 3,
+            Self::Int32 | Self::Float32 => 4,
+            Self::Float64 => 8
+        }
+    }
+
+    /// True if the encoding is IEEE float
+    pub fn is_float(self) -> bool {
+        matches!(self, Self::Float32 | Self::Float64)
+    }
+
+    /// Count of bits in each sample, the width of the value passed to
+    /// `Sample::from_int()` when decoding an integer sample.
+    pub fn bit_count(self) -> u16 {
+        self.byte_count() as u16 * 8
+    }
+
+    /// Decode a single sample from the beginning of `bytes`
+    pub fn decode<S: Sample>(self, bytes: &[u8]) -> S {
+        match self {
+            Self::UInt8 => S::from_int(bytes[0] as i32 - 0x80_i32, 8), // EBU 3285 §A2.2
+            Self::Int16 => S::from_int(LittleEndian::read_i16(bytes) as i32, 16),
+            Self::Int24 => S::from_int(LittleEndian::read_i24(bytes), 24),
+            Self::Int32 => S::from_int(LittleEndian::read_i32(bytes), 32),
+            Self::Float32 => S::from_float(LittleEndian::read_f32(bytes) as f64),
+            Self::Float64 => S::from_float(LittleEndian::read_f64(bytes)),
+        }
+    }
+
+    /// Encode a single sample into the beginning of `bytes`
+    pub fn encode<S: Sample>(self, sample: S, bytes: &mut [u8]) {
+        match self {
+            Self::UInt8 => bytes[0] = (sample.to_int(8) + 0x80) as u8, // EBU 3285 §A2.2
+            Self::Int16 => LittleEndian::write_i16(bytes, sample.to_int(16) as i16),
+            Self::Int24 => LittleEndian::write_i24(bytes, sample.to_int(24)),
+            Self::Int32 => LittleEndian::write_i32(bytes, sample.to_int(32)),
+            Self::Float32 => LittleEndian::write_f32(bytes, sample.to_float() as f32),
+            Self::Float64 => LittleEndian::write_f64(bytes, sample.to_float()),
+        }
+    }
+}
+
+#[test]
+fn test_integer_scaling() {
+    assert_eq!(i32::from_sample(0x1234i16), 0x1234_0000);
+    assert_eq!(i16::from_sample(0x1234_5678i32), 0x1234);
+    assert_eq!(I24::from_sample(-1i16), I24::new(-0x100));
+    assert_eq!(i16::from_int(-0x80, 8), -0x8000);
+    assert_eq!(0x7FFFi16.to_int(24), 0x7F_FF00);
+}
+
+#[test]
+fn test_float_scaling() {
+    assert_eq!(f32::from_sample(0x4000i16), 0.5);
+    assert_eq!(f64::from_sample(I24::MIN), -1.0);
+    assert_eq!(i16::from_sample(0.5f32), 0x4000);
+    assert_eq!(i32::from_sample(1.0f64), i32::MAX);
+    assert_eq!(I24::from_sample(-1.5f64), I24::MIN);
+}
+
+#[test]
+fn test_encoding_round_trip() {
+    let encodings = [SampleEncoding::UInt8, SampleEncoding::Int16, SampleEncoding::Int24,
+        SampleEncoding::Int32, SampleEncoding::Float32, SampleEncoding::Float64];
+
+    for encoding in encodings.iter() {
+        let mut buf = [0u8; 8];
+        encoding.encode(-0.5f32, &mut buf);
+        assert_eq!(encoding.decode::<f32>(&buf), -0.5f32, "failed round trip for {:?}", encoding);
+    }
+}
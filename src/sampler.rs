@@ -1,2 +1,311 @@
+use super::errors::Error;
+use super::cue::Cue;
+
+use byteorder::{WriteBytesExt, ReadBytesExt, LittleEndian};
+
+use std::io::{Cursor, Read, Write};
+
+/// Sampler metadata, the content of a `smpl` chunk.
+///
+/// Describes how a sampler should play the audio data: its MIDI note and
+/// tuning, and the regions that should loop.
+///
+/// ```
+/// use bwavfile::{Sampler, SampleLoop, LoopType, Cue};
+///
+/// let cue = Cue::region(1, 1000, 500, "Sustain");
+///
+/// let mut sampler = Sampler::default();
+/// sampler.sample_period = 1_000_000_000 / 48000;
+/// sampler.midi_unity_note = 60;
+/// sampler.loops.push(SampleLoop::from_cue(&cue, LoopType::Forward).unwrap());
+///
+/// assert_eq!(sampler.loops[0].start, 1000);
+/// assert_eq!(sampler.loops[0].end, 1499);
+///
+/// let cues = vec![cue];
+/// assert_eq!(sampler.loops[0].cue(&cues).unwrap().label(), Some("Sustain"));
+/// ```
+///
 /// ## Resources
 /// - [Sampler Metadata](http://www.piclist.com/techref/io/serial/midi/wave.html)
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Sampler {
+    /// MIDI Manufacturers Association manufacturer code of the intended
+    /// sampler, 0 if none
+    pub manufacturer: u32,
+
+    /// Product code of the intended sampler, 0 if none
+    pub product: u32,
+
+    /// Duration of one sample in nanoseconds
+    pub sample_period: u32,
+
+    /// MIDI note number at which the sample plays at its original pitch
+    pub midi_unity_note: u32,
+
+    /// Fraction of a semitone up from `midi_unity_note`, where 0x80000000
+    /// is one half semitone
+    pub midi_pitch_fraction: u32,
+
+    /// SMPTE format of `smpte_offset`: 0 for none, or 24, 25, 29 (30 drop
+    /// frame) or 30 frames per second
+    pub smpte_format: u32,
+
+    /// SMPTE time at which the sample should start, packed as 0xhhmmssff
+    pub smpte_offset: u32,
+
+    /// Sample loops
+    pub loops: Vec<SampleLoop>,
+
+    /// Sampler-specific data
+    pub sampler_data: Vec<u8>
+}
+
+/// The way a `SampleLoop` is played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopType {
+    /// Loop forward, 0
+    Forward,
+
+    /// Alternate forward and backward, 1
+    Alternating,
+
+    /// Loop backward, 2
+    Backward,
+
+    /// Any other value
+    Other(u32)
+}
+
+impl From<u32> for LoopType {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => LoopType::Forward,
+            1 => LoopType::Alternating,
+            2 => LoopType::Backward,
+            x => LoopType::Other(x)
+        }
+    }
+}
+
+impl From<LoopType> for u32 {
+    fn from(value: LoopType) -> Self {
+        match value {
+            LoopType::Forward => 0,
+            LoopType::Alternating => 1,
+            LoopType::Backward => 2,
+            LoopType::Other(x) => x
+        }
+    }
+}
+
+/// A loop in a `Sampler`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleLoop {
+    /// The ident of the `Cue` this loop corresponds to, if any
+    pub cue_point_id: u32,
+
+    /// How the loop is played
+    pub loop_type: LoopType,
+
+    /// The first frame of the loop
+    pub start: u32,
+
+    /// The last frame of the loop, the loop includes this frame
+    pub end: u32,
+
+    /// Fraction of a frame to extend the loop by, where 0x80000000 is
+    /// one half frame
+    pub fraction: u32,
+
+    /// Number of times to play the loop, 0 for infinite
+    pub play_count: u32
+}
+
+impl SampleLoop {
+
+    /// A loop over the region of a `Cue`.
+    ///
+    /// Returns `None` if `cue` is not a region, or doesn't fit in a
+    /// standard WAV file.
+    pub fn from_cue(cue: &Cue, loop_type: LoopType) -> Option<Self> {
+        let length = cue.length().filter(|l| *l > 0)?;
+        let start = cue.frame;
+        let end = start.checked_add(length - 1)?;
+        if end > u32::MAX as u64 {
+            return None;
+        }
+        Some( SampleLoop {
+            cue_point_id: cue.ident, loop_type, start: start as u32, end: end as u32,
+            fraction: 0, play_count: 0
+        })
+    }
+
+    /// The `Cue` this loop corresponds to, in `cues`.
+    pub fn cue<'a>(&self, cues: &'a [Cue]) -> Option<&'a Cue> {
+        cues.iter().find(|c| c.ident == self.cue_point_id)
+    }
+}
+
+impl Sampler {
+
+    /// Parse the content of a `smpl` chunk.
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, Error> {
+        let mut cursor = Cursor::new(data);
+        let mut sampler = Sampler {
+            manufacturer: cursor.read_u32::<LittleEndian>()?,
+            product: cursor.read_u32::<LittleEndian>()?,
+            sample_period: cursor.read_u32::<LittleEndian>()?,
+            midi_unity_note: cursor.read_u32::<LittleEndian>()?,
+            midi_pitch_fraction: cursor.read_u32::<LittleEndian>()?,
+            smpte_format: cursor.read_u32::<LittleEndian>()?,
+            smpte_offset: cursor.read_u32::<LittleEndian>()?,
+            loops: vec![],
+            sampler_data: vec![]
+        };
+        let loop_count = cursor.read_u32::<LittleEndian>()?;
+        let sampler_data_length = cursor.read_u32::<LittleEndian>()?;
+
+        for _ in 0..loop_count {
+            sampler.loops.push( SampleLoop {
+                cue_point_id: cursor.read_u32::<LittleEndian>()?,
+                loop_type: cursor.read_u32::<LittleEndian>()?.into(),
+                start: cursor.read_u32::<LittleEndian>()?,
+                end: cursor.read_u32::<LittleEndian>()?,
+                fraction: cursor.read_u32::<LittleEndian>()?,
+                play_count: cursor.read_u32::<LittleEndian>()?
+            });
+        }
+
+        // cbSamplerData often overstates the data actually present, so take
+        // whatever there is, up to the length given
+        cursor.take(sampler_data_length as u64).read_to_end(&mut sampler.sampler_data)?;
+
+        Ok( sampler )
+    }
+
+    /// The content of a `smpl` chunk for this metadata.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut cursor = Cursor::new(vec![0u8; 0]);
+        cursor.write_u32::<LittleEndian>(self.manufacturer).unwrap();
+        cursor.write_u32::<LittleEndian>(self.product).unwrap();
+        cursor.write_u32::<LittleEndian>(self.sample_period).unwrap();
+        cursor.write_u32::<LittleEndian>(self.midi_unity_note).unwrap();
+        cursor.write_u32::<LittleEndian>(self.midi_pitch_fraction).unwrap();
+        cursor.write_u32::<LittleEndian>(self.smpte_format).unwrap();
+        cursor.write_u32::<LittleEndian>(self.smpte_offset).unwrap();
+        cursor.write_u32::<LittleEndian>(self.loops.len() as u32).unwrap();
+        cursor.write_u32::<LittleEndian>(self.sampler_data.len() as u32).unwrap();
+        for l in self.loops.iter() {
+            cursor.write_u32::<LittleEndian>(l.cue_point_id).unwrap();
+            cursor.write_u32::<LittleEndian>(l.loop_type.into()).unwrap();
+            cursor.write_u32::<LittleEndian>(l.start).unwrap();
+            cursor.write_u32::<LittleEndian>(l.end).unwrap();
+            cursor.write_u32::<LittleEndian>(l.fraction).unwrap();
+            cursor.write_u32::<LittleEndian>(l.play_count).unwrap();
+        }
+        cursor.write_all(&self.sampler_data).unwrap();
+        cursor.into_inner()
+    }
+}
+
+/// Instrument metadata, the content of an `inst` chunk.
+///
+/// Describes how a sample should be mapped onto a keyboard.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Instrument {
+    /// MIDI note number at which the sample plays at its original pitch
+    pub unshifted_note: u8,
+
+    /// Pitch adjustment in cents, -50 to +50
+    pub fine_tune: i8,
+
+    /// Gain in decibels, -64 to +64
+    pub gain: i8,
+
+    /// Lowest MIDI note the sample should be played for
+    pub low_note: u8,
+
+    /// Highest MIDI note the sample should be played for
+    pub high_note: u8,
+
+    /// Lowest MIDI velocity the sample should be played for
+    pub low_velocity: u8,
+
+    /// Highest MIDI velocity the sample should be played for
+    pub high_velocity: u8
+}
+
+impl Default for Instrument {
+    fn default() -> Self {
+        Instrument { unshifted_note: 60, fine_tune: 0, gain: 0, low_note: 0, high_note: 127,
+            low_velocity: 1, high_velocity: 127 }
+    }
+}
+
+impl Instrument {
+
+    /// Parse the content of an `inst` chunk.
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, Error> {
+        let mut cursor = Cursor::new(data);
+        Ok( Instrument {
+            unshifted_note: cursor.read_u8()?,
+            fine_tune: cursor.read_i8()?,
+            gain: cursor.read_i8()?,
+            low_note: cursor.read_u8()?,
+            high_note: cursor.read_u8()?,
+            low_velocity: cursor.read_u8()?,
+            high_velocity: cursor.read_u8()?
+        })
+    }
+
+    /// The content of an `inst` chunk for this metadata.
+    pub(crate) fn to_bytes(self) -> Vec<u8> {
+        vec![self.unshifted_note, self.fine_tune as u8, self.gain as u8, self.low_note,
+            self.high_note, self.low_velocity, self.high_velocity]
+    }
+}
+
+#[test]
+fn test_sampler_round_trip() {
+    let sampler = Sampler {
+        manufacturer: 0x01000041,
+        product: 2,
+        sample_period: 20833,
+        midi_unity_note: 57,
+        midi_pitch_fraction: 0x80000000,
+        smpte_format: 25,
+        smpte_offset: 0x01020304,
+        loops: vec![
+            SampleLoop { cue_point_id: 1, loop_type: LoopType::Forward, start: 100, end: 199,
+                fraction: 0, play_count: 0 },
+            SampleLoop { cue_point_id: 2, loop_type: LoopType::Other(32), start: 300, end: 399,
+                fraction: 0x40000000, play_count: 4 },
+        ],
+        sampler_data: vec![1, 2, 3]
+    };
+
+    let bytes = sampler.to_bytes();
+    assert_eq!(bytes.len(), 36 + 24 * 2 + 3);
+    assert_eq!(Sampler::parse(&bytes).unwrap(), sampler);
+}
+
+#[test]
+fn test_instrument_round_trip() {
+    let inst = Instrument { unshifted_note: 48, fine_tune: -12, gain: -6, low_note: 40,
+        high_note: 52, low_velocity: 1, high_velocity: 100 };
+    let bytes = inst.to_bytes();
+    assert_eq!(bytes, [48, 0xF4, 0xFA, 40, 52, 1, 100]);
+    assert_eq!(Instrument::parse(&bytes).unwrap(), inst);
+}
+
+#[test]
+fn test_sampler_short_sampler_data() {
+    let mut data = Sampler::default().to_bytes();
+    data[32..36].copy_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+    data.extend_from_slice(&[1, 2, 3, 4]);
+
+    let sampler = Sampler::parse(&data).unwrap();
+    assert_eq!(sampler.sampler_data, [1, 2, 3, 4]);
+}
@@ -0,0 +1,365 @@
+use std::fmt;
+
+/// A SMPTE timecode frame rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameRate {
+    /// 23.976 frames per second, counted as 24
+    Fps23_976,
+
+    /// 24 frames per second
+    Fps24,
+
+    /// 25 frames per second
+    Fps25,
+
+    /// 29.97 frames per second drop-frame, counted as 30 with frame numbers
+    /// 0 and 1 skipped at the start of every minute but every tenth
+    Fps29_97DropFrame,
+
+    /// 29.97 frames per second non-drop-frame, counted as 30
+    Fps29_97,
+
+    /// 30 frames per second
+    Fps30,
+
+    /// 48 frames per second
+    Fps48,
+
+    /// 50 frames per second
+    Fps50,
+
+    /// 59.94 frames per second drop-frame, counted as 60 with frame
+    /// numbers 0 to 3 skipped at the start of every minute but every tenth
+    Fps59_94DropFrame,
+
+    /// 59.94 frames per second non-drop-frame, counted as 60
+    Fps59_94,
+
+    /// 60 frames per second
+    Fps60
+}
+
+impl FrameRate {
+
+    /// The number of frames counted in each second of timecode
+    pub fn nominal_fps(&self) -> u32 {
+        match self {
+            FrameRate::Fps23_976 | FrameRate::Fps24 => 24,
+            FrameRate::Fps25 => 25,
+            FrameRate::Fps29_97DropFrame | FrameRate::Fps29_97 | FrameRate::Fps30 => 30,
+            FrameRate::Fps48 => 48,
+            FrameRate::Fps50 => 50,
+            FrameRate::Fps59_94DropFrame | FrameRate::Fps59_94 | FrameRate::Fps60 => 60
+        }
+    }
+
+    /// The actual frame rate, as a numerator and denominator
+    pub fn rate(&self) -> (u32, u32) {
+        match self {
+            FrameRate::Fps23_976 | FrameRate::Fps29_97DropFrame | FrameRate::Fps29_97 |
+            FrameRate::Fps59_94DropFrame | FrameRate::Fps59_94 => (self.nominal_fps() * 1000, 1001),
+            _ => (self.nominal_fps(), 1)
+        }
+    }
+
+    /// True if this is a drop-frame rate
+    pub fn is_drop_frame(&self) -> bool {
+        matches!(self, FrameRate::Fps29_97DropFrame | FrameRate::Fps59_94DropFrame)
+    }
+
+    /// The number of frame numbers skipped each minute, if drop-frame
+    fn dropped_frames(&self) -> u64 {
+        match self {
+            FrameRate::Fps29_97DropFrame => 2,
+            FrameRate::Fps59_94DropFrame => 4,
+            _ => 0
+        }
+    }
+
+    /// The number of frames in a day
+    fn frames_per_day(&self) -> u64 {
+        self.nominal_fps() as u64 * 86400 - self.dropped_frames() * 9 * 144
+    }
+
+    /// The frame rate described by an iXML `TIMECODE_RATE`, such as
+    /// "30000/1001", and `TIMECODE_FLAG`, "DF" or "NDF".
+    pub fn from_ixml(timecode_rate: &str, timecode_flag: &str) -> Option<Self> {
+        let mut parts = timecode_rate.trim().splitn(2, '/');
+        let numerator: u32 = parts.next()?.trim().parse().ok()?;
+        let denominator: u32 = match parts.next() {
+            Some(d) => d.trim().parse().ok()?,
+            None => 1
+        };
+        let drop_frame = timecode_flag.trim().eq_ignore_ascii_case("DF");
+
+        let rate = match (numerator, denominator, drop_frame) {
+            (24000, 1001, false) => FrameRate::Fps23_976,
+            (24, 1, false) => FrameRate::Fps24,
+            (25, 1, false) => FrameRate::Fps25,
+            (30000, 1001, true) => FrameRate::Fps29_97DropFrame,
+            (30000, 1001, false) => FrameRate::Fps29_97,
+            (30, 1, false) => FrameRate::Fps30,
+            (48, 1, false) => FrameRate::Fps48,
+            (50, 1, false) => FrameRate::Fps50,
+            (60000, 1001, true) => FrameRate::Fps59_94DropFrame,
+            (60000, 1001, false) => FrameRate::Fps59_94,
+            (60, 1, false) => FrameRate::Fps60,
+            _ => return None
+        };
+        Some(rate)
+    }
+
+    /// This rate as an iXML `TIMECODE_RATE`, such as "30000/1001"
+    pub fn ixml_timecode_rate(&self) -> String {
+        let (numerator, denominator) = self.rate();
+        format!("{}/{}", numerator, denominator)
+    }
+
+    /// This rate as an iXML `TIMECODE_FLAG`, "DF" or "NDF"
+    pub fn ixml_timecode_flag(&self) -> &'static str {
+        if self.is_drop_frame() { "DF" } else { "NDF" }
+    }
+}
+
+/// A change in speed between when audio was recorded and how its
+/// samples are counted, as in film and video transfers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pull {
+    /// Samples are counted at the sample rate
+    Nominal,
+
+    /// Samples are counted 0.1% faster than the sample rate, as when
+    /// audio recorded at 48048 Hz is stamped as 48 kHz
+    Up,
+
+    /// Samples are counted 0.1% slower than the sample rate, as when
+    /// audio recorded at 47952 Hz is stamped as 48 kHz
+    Down
+}
+
+impl Pull {
+    /// The sample rate multiplier, as a numerator and denominator
+    fn factor(&self) -> (u64, u64) {
+        match self {
+            Pull::Nominal => (1, 1),
+            Pull::Up => (1001, 1000),
+            Pull::Down => (1000, 1001)
+        }
+    }
+}
+
+/// A SMPTE timecode.
+///
+/// Timecodes convert to and from a count of frames since midnight, and a
+/// count of samples since midnight such as a `Bext`'s `time_reference`.
+///
+/// ```
+/// use bwavfile::{Timecode, FrameRate};
+///
+/// let tc = Timecode::new(1, 0, 0, 0, FrameRate::Fps29_97DropFrame).unwrap();
+/// assert_eq!(tc.to_string(), "01:00:00;00");
+/// assert_eq!(tc.frame_count(), 107892);
+///
+/// let samples = tc.to_samples(48000);
+/// assert_eq!(samples, 172_799_828);
+/// assert_eq!(Timecode::from_samples(samples, 48000, FrameRate::Fps29_97DropFrame), tc);
+///
+/// // 00:01:00;00 doesn't exist in drop-frame
+/// assert_eq!(Timecode::new(0, 1, 0, 0, FrameRate::Fps29_97DropFrame), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timecode {
+    hours: u8,
+    minutes: u8,
+    seconds: u8,
+    frames: u8,
+    rate: FrameRate
+}
+
+impl Timecode {
+
+    /// A timecode, or `None` if it isn't valid at `rate`.
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn new(hours: u8, minutes: u8, seconds: u8, frames: u8, rate: FrameRate) -> Option<Self> {
+        if hours > 23 || minutes > 59 || seconds > 59 || frames as u32 >= rate.nominal_fps() {
+            return None;
+        }
+        if seconds == 0 && minutes % 10 != 0 && (frames as u64) < rate.dropped_frames() {
+            return None;
+        }
+        Some( Timecode { hours, minutes, seconds, frames, rate } )
+    }
+
+    /// Parse a timecode in the form "HH:MM:SS:FF". Drop-frame timecodes
+    /// may be separated by `;` or `.` before the frames.
+    pub fn parse(s: &str, rate: FrameRate) -> Option<Self> {
+        let fields: Vec<&str> = s.trim().split([':', ';', '.']).collect();
+        if fields.len() != 4 || fields.iter().any(|f| f.is_empty() || !f.bytes().all(|c| c.is_ascii_digit())) {
+            return None;
+        }
+        Self::new(fields[0].parse().ok()?, fields[1].parse().ok()?, fields[2].parse().ok()?,
+            fields[3].parse().ok()?, rate)
+    }
+
+    /// The timecode `frame_count` frames after midnight, wrapping at 24 hours.
+    pub fn from_frame_count(frame_count: u64, rate: FrameRate) -> Self {
+        let mut count = frame_count % rate.frames_per_day();
+        let fps = rate.nominal_fps() as u64;
+        let drop = rate.dropped_frames();
+
+        if drop > 0 {
+            let frames_per_ten_minutes = fps * 600 - drop * 9;
+            let frames_per_minute = fps * 60 - drop;
+            let tens = count / frames_per_ten_minutes;
+            let rest = count % frames_per_ten_minutes;
+            count += drop * 9 * tens;
+            if rest > drop {
+                count += drop * ((rest - drop) / frames_per_minute);
+            }
+        }
+
+        Timecode {
+            hours: (count / (fps * 3600)) as u8,
+            minutes: (count / (fps * 60) % 60) as u8,
+            seconds: (count / fps % 60) as u8,
+            frames: (count % fps) as u8,
+            rate
+        }
+    }
+
+    /// The number of frames since midnight
+    pub fn frame_count(&self) -> u64 {
+        let fps = self.rate.nominal_fps() as u64;
+        let total_minutes = self.hours as u64 * 60 + self.minutes as u64;
+        let nominal = (total_minutes * 60 + self.seconds as u64) * fps + self.frames as u64;
+        nominal - self.rate.dropped_frames() * (total_minutes - total_minutes / 10)
+    }
+
+    /// The timecode of the frame playing `samples` samples after midnight.
+    pub fn from_samples(samples: u64, sample_rate: u32, rate: FrameRate) -> Self {
+        Self::from_samples_pulled(samples, sample_rate, rate, Pull::Nominal)
+    }
+
+    /// The timecode of the frame playing `samples` samples after midnight,
+    /// with samples counted at a pulled rate.
+    pub fn from_samples_pulled(samples: u64, sample_rate: u32, rate: FrameRate, pull: Pull) -> Self {
+        let (numerator, denominator) = rate.rate();
+        let (pull_numerator, pull_denominator) = pull.factor();
+        let frames = samples as u128 * numerator as u128 * pull_denominator as u128
+            / (sample_rate as u128 * denominator as u128 * pull_numerator as u128);
+        Self::from_frame_count(frames as u64, rate)
+    }
+
+    /// The number of samples from midnight to the start of this frame.
+    pub fn to_samples(&self, sample_rate: u32) -> u64 {
+        self.to_samples_pulled(sample_rate, Pull::Nominal)
+    }
+
+    /// The number of samples from midnight to the start of this frame,
+    /// with samples counted at a pulled rate.
+    pub fn to_samples_pulled(&self, sample_rate: u32, pull: Pull) -> u64 {
+        let (numerator, denominator) = self.rate.rate();
+        let (pull_numerator, pull_denominator) = pull.factor();
+        let dividend = self.frame_count() as u128 * sample_rate as u128 * denominator as u128
+            * pull_numerator as u128;
+        let divisor = numerator as u128 * pull_denominator as u128;
+        dividend.div_ceil(divisor) as u64
+    }
+
+    /// The hours
+    pub fn hours(&self) -> u8 {
+        self.hours
+    }
+
+    /// The minutes
+    pub fn minutes(&self) -> u8 {
+        self.minutes
+    }
+
+    /// The seconds
+    pub fn seconds(&self) -> u8 {
+        self.seconds
+    }
+
+    /// The frames
+    pub fn frames(&self) -> u8 {
+        self.frames
+    }
+
+    /// The frame rate
+    pub fn rate(&self) -> FrameRate {
+        self.rate
+    }
+}
+
+impl fmt::Display for Timecode {
+
+    /// The timecode as "HH:MM:SS:FF", or "HH:MM:SS;FF" if drop-frame.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let separator = if self.rate.is_drop_frame() { ';' } else { ':' };
+        write!(f, "{:02}:{:02}:{:02}{}{:02}", self.hours, self.minutes, self.seconds, separator, self.frames)
+    }
+}
+
+#[test]
+fn test_drop_frame_counting() {
+    let rate = FrameRate::Fps29_97DropFrame;
+    let cases = [
+        (0, "00:00:00;00"), (1799, "00:00:59;29"), (1800, "00:01:00;02"),
+        (17981, "00:09:59;29"), (17982, "00:10:00;00"), (17983, "00:10:00;01"),
+        (107892, "01:00:00;00"), (2589407, "23:59:59;29"), (2589408, "00:00:00;00")
+    ];
+    for (count, tc) in cases.iter() {
+        let timecode = Timecode::from_frame_count(*count, rate);
+        assert_eq!(timecode.to_string(), *tc);
+        assert_eq!(Timecode::parse(tc, rate), Some(timecode));
+        assert_eq!(timecode.frame_count(), *count % 2589408);
+    }
+
+    let rate = FrameRate::Fps59_94DropFrame;
+    assert_eq!(Timecode::from_frame_count(3599, rate).to_string(), "00:00:59;59");
+    assert_eq!(Timecode::from_frame_count(3600, rate).to_string(), "00:01:00;04");
+    assert_eq!(Timecode::new(0, 1, 0, 3, rate), None);
+    assert_eq!(Timecode::new(0, 10, 0, 3, rate).unwrap().frame_count(), 35964 + 3);
+}
+
+#[test]
+fn test_samples_round_trip() {
+    let rates = [FrameRate::Fps23_976, FrameRate::Fps24, FrameRate::Fps25, FrameRate::Fps29_97DropFrame,
+        FrameRate::Fps29_97, FrameRate::Fps30, FrameRate::Fps48, FrameRate::Fps50,
+        FrameRate::Fps59_94DropFrame, FrameRate::Fps59_94, FrameRate::Fps60];
+    for rate in rates.iter() {
+        for pull in [Pull::Nominal, Pull::Up, Pull::Down].iter() {
+            for tc in ["00:00:00:00", "10:00:00:00", "12:34:56:12", "23:59:59:23"].iter() {
+                let timecode = Timecode::parse(tc, *rate).unwrap();
+                let samples = timecode.to_samples_pulled(48000, *pull);
+                assert_eq!(Timecode::from_samples_pulled(samples, 48000, *rate, *pull), timecode);
+                if samples > 0 {
+                    assert_ne!(Timecode::from_samples_pulled(samples - 1, 48000, *rate, *pull), timecode);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_sample_rates() {
+    // 10:00:00:00 at 23.976 is 864000 frames of 2002 samples at 48 kHz
+    let tc = Timecode::new(10, 0, 0, 0, FrameRate::Fps23_976).unwrap();
+    assert_eq!(tc.to_samples(48000), 864000 * 2002);
+    assert_eq!(Timecode::new(10, 0, 0, 0, FrameRate::Fps24).unwrap().to_samples(48000), 1_728_000_000);
+    assert_eq!(Timecode::new(10, 0, 0, 0, FrameRate::Fps25).unwrap().to_samples(96000), 3_456_000_000);
+    assert_eq!(tc.to_samples_pulled(48000, Pull::Up), 864000 * 2002 * 1001 / 1000);
+    assert_eq!(Timecode::from_samples(1_728_000_000, 48000, FrameRate::Fps24).to_string(), "10:00:00:00");
+}
+
+#[test]
+fn test_ixml_rates() {
+    assert_eq!(FrameRate::from_ixml("30000/1001", "DF"), Some(FrameRate::Fps29_97DropFrame));
+    assert_eq!(FrameRate::from_ixml("30000/1001", "NDF"), Some(FrameRate::Fps29_97));
+    assert_eq!(FrameRate::from_ixml("25/1", "NDF"), Some(FrameRate::Fps25));
+    assert_eq!(FrameRate::from_ixml("24", ""), Some(FrameRate::Fps24));
+    assert_eq!(FrameRate::from_ixml("25/1", "DF"), None);
+    assert_eq!(FrameRate::Fps23_976.ixml_timecode_rate(), "24000/1001");
+    assert_eq!(FrameRate::Fps59_94DropFrame.ixml_timecode_flag(), "DF");
+}
@@ -0,0 +1,441 @@
+use super::bext::OriginationDate;
+
+use std::fmt;
+
+/// The first ten bytes of a SMPTE 330M UMID's universal label.
+const UMID_LABEL: [u8; 10] = [0x06, 0x0A, 0x2B, 0x34, 0x01, 0x01, 0x01, 0x05, 0x01, 0x01];
+
+/// Length field of a basic UMID
+const BASIC_LENGTH: u8 = 0x13;
+
+/// Length field of an extended UMID
+const EXTENDED_LENGTH: u8 = 0x33;
+
+/// A SMPTE 330M Unique Material Identifier.
+///
+/// A basic UMID is 32 bytes long and identifies a piece of material and an
+/// instance of it. An extended UMID adds a 32-byte source pack describing
+/// when, where and by whom the material was created.
+///
+/// In a `Bext` the UMID is stored in a 64-byte field, which is padded with
+/// zeroes if the UMID is basic. Read and write it with `Bext::parsed_umid()`
+/// and `Bext::set_umid()`.
+///
+/// ```
+/// use bwavfile::{Umid, UmidSourcePack};
+///
+/// let mut umid = Umid::new(0x08, 0x20, 1, [0x5Au8; 16]);
+/// assert_eq!(umid.to_bytes().len(), 32);
+///
+/// umid.source_pack = Some(UmidSourcePack {
+///     country: *b"GBR\0", organization: *b"EBU\0", user: *b"BWF\0",
+///     ..UmidSourcePack::default()
+/// });
+/// let bytes = umid.to_bytes();
+/// assert_eq!(bytes.len(), 64);
+/// assert_eq!(Umid::from_bytes(&bytes), Some(umid));
+/// ```
+///
+/// ## Resources
+/// - SMPTE ST 330:2011 "Unique Material Identifier (UMID)"
+/// - [EBU Tech 3285](https://tech.ebu.ch/docs/tech/tech3285.pdf), "UMID"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Umid {
+    /// The first ten bytes of the universal label
+    label: [u8; 10],
+
+    /// The kind of material identified, for example 0x08 for a single
+    /// audio component or 0x09 for two or more audio components
+    pub material_type: u8,
+
+    /// How the material and instance numbers were generated; the material
+    /// number method is in the high nibble and the instance number method
+    /// in the low nibble
+    pub creation_method: u8,
+
+    /// Distinguishes instances of the same material, 24 bits. This is zero
+    /// for the original material.
+    pub instance_number: u32,
+
+    /// Identifies the material
+    pub material_number: [u8; 16],
+
+    /// The source pack of an extended UMID, or `None` if the UMID is basic
+    pub source_pack: Option<UmidSourcePack>
+}
+
+/// The source pack of an extended SMPTE 330M UMID.
+///
+/// Each field is kept in the form SMPTE 330M encodes it, and can be read and
+/// written as a typed value with `time_date()`, `altitude()`, `longitude()`
+/// and `latitude()` and the matching setters.
+///
+/// The spatial coordinates are each coded as eight BCD digits, most
+/// significant first. The high bit of the first digit is set for altitudes
+/// below sea level, longitudes west of Greenwich and latitudes south of the
+/// equator.
+///
+/// ```
+/// use bwavfile::{UmidSourcePack, UmidTimeDate, OriginationDate};
+///
+/// let mut pack = UmidSourcePack::default();
+/// pack.set_latitude(51.50722);
+/// pack.set_longitude(-0.1275);
+/// pack.set_altitude(-12);
+/// pack.set_time_date(&UmidTimeDate {
+///     hours: 10, minutes: 20, seconds: 30, frames: 12, drop_frame: false,
+///     date: OriginationDate::new(2021, 6, 1).unwrap(), time_zone: 0
+/// });
+///
+/// assert_eq!(pack.latitude, [0x05, 0x15, 0x07, 0x22]);
+/// assert_eq!(pack.longitude(), Some(-0.1275));
+/// assert_eq!(pack.altitude(), Some(-12));
+/// assert_eq!(pack.time_date().unwrap().date.to_string(), "2021-06-01");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UmidSourcePack {
+    /// Time and date the material was created, as a SMPTE 12M time code
+    /// followed by a Modified Julian Date and time zone
+    pub time_date: [u8; 8],
+
+    /// Altitude of the point of creation
+    pub altitude: [u8; 4],
+
+    /// Longitude of the point of creation
+    pub longitude: [u8; 4],
+
+    /// Latitude of the point of creation
+    pub latitude: [u8; 4],
+
+    /// Country code, an ISO 3166 alpha code
+    pub country: [u8; 4],
+
+    /// Organization code
+    pub organization: [u8; 4],
+
+    /// User code, assigned by the organization
+    pub user: [u8; 4]
+}
+
+impl UmidSourcePack {
+
+    /// The country code as text
+    pub fn country_code(&self) -> String {
+        code_text(&self.country)
+    }
+
+    /// The organization code as text
+    pub fn organization_code(&self) -> String {
+        code_text(&self.organization)
+    }
+
+    /// The user code as text
+    pub fn user_code(&self) -> String {
+        code_text(&self.user)
+    }
+}
+
+/// The time and date of an extended UMID's source pack.
+///
+/// The time is a SMPTE 12M time code and the date a Modified Julian Date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UmidTimeDate {
+    /// Hours of the time code
+    pub hours: u8,
+
+    /// Minutes of the time code
+    pub minutes: u8,
+
+    /// Seconds of the time code
+    pub seconds: u8,
+
+    /// Frames of the time code
+    pub frames: u8,
+
+    /// True if the time code is drop-frame
+    pub drop_frame: bool,
+
+    /// The date the material was created
+    pub date: OriginationDate,
+
+    /// The SMPTE 330M time zone code, 0 for UTC
+    pub time_zone: u8
+}
+
+/// Modified Julian Date of 1970-01-01
+const MJD_UNIX_EPOCH: i64 = 40587;
+
+const DROP_FRAME_FLAG: u8 = 0x40;
+const NEGATIVE_FLAG: u8 = 0x80;
+
+fn from_bcd(byte: u8) -> Option<u8> {
+    if byte >> 4 < 10 && byte & 0x0F < 10 {
+        Some((byte >> 4) * 10 + (byte & 0x0F))
+    } else {
+        None
+    }
+}
+
+fn to_bcd(value: u8) -> u8 {
+    ((value / 10 % 10) << 4) | (value % 10)
+}
+
+/// Decode a signed eight-digit BCD coordinate
+fn decode_coordinate(field: &[u8; 4]) -> Option<i64> {
+    let mut digits = *field;
+    digits[0] &= !NEGATIVE_FLAG;
+    let magnitude = digits.iter()
+        .try_fold(0i64, |acc, b| from_bcd(*b).map(|v| acc * 100 + v as i64))?;
+    Some(if field[0] & NEGATIVE_FLAG != 0 { -magnitude } else { magnitude })
+}
+
+/// Encode a signed coordinate as eight BCD digits, clipping its magnitude
+/// to leave the high bit of the first digit for the sign
+fn encode_coordinate(value: i64) -> [u8; 4] {
+    let mut magnitude = value.unsigned_abs().min(79_999_999);
+    let mut field = [0u8; 4];
+    for byte in field.iter_mut().rev() {
+        *byte = to_bcd((magnitude % 100) as u8);
+        magnitude /= 100;
+    }
+    if value < 0 {
+        field[0] |= NEGATIVE_FLAG;
+    }
+    field
+}
+
+impl UmidSourcePack {
+
+    /// The time and date the material was created, or `None` if the field
+    /// isn't valid BCD
+    pub fn time_date(&self) -> Option<UmidTimeDate> {
+        let t = &self.time_date;
+        let mjd = from_bcd(t[4])? as i64 * 10000 + from_bcd(t[5])? as i64 * 100 + from_bcd(t[6])? as i64;
+        Some( UmidTimeDate {
+            frames: from_bcd(t[0] & 0x3F)?,
+            drop_frame: t[0] & DROP_FRAME_FLAG != 0,
+            seconds: from_bcd(t[1] & 0x7F)?,
+            minutes: from_bcd(t[2] & 0x7F)?,
+            hours: from_bcd(t[3] & 0x3F)?,
+            date: OriginationDate::from_days_since_epoch(mjd - MJD_UNIX_EPOCH),
+            time_zone: t[7]
+        })
+    }
+
+    /// Set the time and date the material was created
+    pub fn set_time_date(&mut self, time_date: &UmidTimeDate) {
+        let mjd = (time_date.date.days_since_epoch() + MJD_UNIX_EPOCH).clamp(0, 999_999) as u32;
+        self.time_date = [
+            to_bcd(time_date.frames) | if time_date.drop_frame { DROP_FRAME_FLAG } else { 0 },
+            to_bcd(time_date.seconds),
+            to_bcd(time_date.minutes),
+            to_bcd(time_date.hours),
+            to_bcd((mjd / 10000) as u8),
+            to_bcd((mjd / 100 % 100) as u8),
+            to_bcd((mjd % 100) as u8),
+            time_date.time_zone
+        ];
+    }
+
+    /// Altitude in metres above sea level, or `None` if the field isn't
+    /// valid BCD
+    pub fn altitude(&self) -> Option<i64> {
+        decode_coordinate(&self.altitude)
+    }
+
+    /// Set the altitude in metres above sea level
+    pub fn set_altitude(&mut self, metres: i64) {
+        self.altitude = encode_coordinate(metres);
+    }
+
+    /// Longitude in degrees east, or `None` if the field isn't valid BCD or
+    /// is out of range
+    pub fn longitude(&self) -> Option<f64> {
+        decode_coordinate(&self.longitude)
+            .filter(|v| v.abs() <= 180_00000)
+            .map(|v| v as f64 / 100000.0)
+    }
+
+    /// Set the longitude in degrees east, to five decimal places
+    pub fn set_longitude(&mut self, degrees: f64) {
+        self.longitude = encode_coordinate((degrees.clamp(-180.0, 180.0) * 100000.0).round() as i64);
+    }
+
+    /// Latitude in degrees north, or `None` if the field isn't valid BCD or
+    /// is out of range
+    pub fn latitude(&self) -> Option<f64> {
+        decode_coordinate(&self.latitude)
+            .filter(|v| v.abs() <= 90_00000)
+            .map(|v| v as f64 / 100000.0)
+    }
+
+    /// Set the latitude in degrees north, to five decimal places
+    pub fn set_latitude(&mut self, degrees: f64) {
+        self.latitude = encode_coordinate((degrees.clamp(-90.0, 90.0) * 100000.0).round() as i64);
+    }
+}
+
+fn code_text(code: &[u8; 4]) -> String {
+    code.iter().take_while(|c| **c != 0).map(|c| *c as char).collect()
+}
+
+impl Umid {
+
+    /// A basic UMID with a SMPTE 330M universal label.
+    pub fn new(material_type: u8, creation_method: u8, instance_number: u32,
+        material_number: [u8; 16]) -> Self {
+        Umid { label: UMID_LABEL, material_type, creation_method,
+            instance_number: instance_number & 0xFF_FFFF, material_number, source_pack: None }
+    }
+
+    /// True if this UMID has a source pack
+    pub fn is_extended(&self) -> bool {
+        self.source_pack.is_some()
+    }
+
+    /// Read a UMID from its binary form.
+    ///
+    /// Trailing bytes after the UMID are ignored, so this accepts the 64-byte
+    /// `umid` field of a `Bext`. Returns `None` if `bytes` does not begin with
+    /// a SMPTE universal label and a basic or extended length.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 32 || bytes[0..4] != UMID_LABEL[0..4] {
+            return None;
+        }
+
+        let source_pack = match bytes[12] {
+            BASIC_LENGTH => None,
+            EXTENDED_LENGTH if bytes.len() >= 64 => {
+                let field = |at: usize| [bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]];
+                let mut time_date = [0u8; 8];
+                time_date.copy_from_slice(&bytes[32..40]);
+                Some(UmidSourcePack {
+                    time_date,
+                    altitude: field(40), longitude: field(44), latitude: field(48),
+                    country: field(52), organization: field(56), user: field(60)
+                })
+            },
+            _ => return None
+        };
+
+        let mut label = [0u8; 10];
+        label.copy_from_slice(&bytes[0..10]);
+        let mut material_number = [0u8; 16];
+        material_number.copy_from_slice(&bytes[16..32]);
+
+        Some( Umid {
+            label,
+            material_type: bytes[10],
+            creation_method: bytes[11],
+            instance_number: (bytes[13] as u32) << 16 | (bytes[14] as u32) << 8 | bytes[15] as u32,
+            material_number,
+            source_pack
+        })
+    }
+
+    /// The binary form of this UMID, 32 bytes long if it is basic or 64 if
+    /// it is extended.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(64);
+        bytes.extend_from_slice(&self.label);
+        bytes.push(self.material_type);
+        bytes.push(self.creation_method);
+        bytes.push(if self.is_extended() { EXTENDED_LENGTH } else { BASIC_LENGTH });
+        bytes.extend_from_slice(&self.instance_number.to_be_bytes()[1..4]);
+        bytes.extend_from_slice(&self.material_number);
+        if let Some(pack) = &self.source_pack {
+            bytes.extend_from_slice(&pack.time_date);
+            bytes.extend_from_slice(&pack.altitude);
+            bytes.extend_from_slice(&pack.longitude);
+            bytes.extend_from_slice(&pack.latitude);
+            bytes.extend_from_slice(&pack.country);
+            bytes.extend_from_slice(&pack.organization);
+            bytes.extend_from_slice(&pack.user);
+        }
+        bytes
+    }
+}
+
+impl fmt::Display for Umid {
+
+    /// The UMID as uppercase hexadecimal digits.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for b in self.to_bytes() {
+            write!(f, "{:02X}", b)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_umid_basic() {
+    let bytes: Vec<u8> = [0x06, 0x0A, 0x2B, 0x34, 0x01, 0x01, 0x01, 0x05, 0x01, 0x01, 0x0D, 0x20,
+        0x13, 0x00, 0x00, 0x02].iter().cloned().chain(1..=16u8).chain([0u8; 32].iter().cloned())
+        .collect();
+
+    let umid = Umid::from_bytes(&bytes).unwrap();
+    assert_eq!(umid.material_type, 0x0D);
+    assert_eq!(umid.creation_method, 0x20);
+    assert_eq!(umid.instance_number, 2);
+    assert_eq!(umid.material_number[15], 16);
+    assert!(!umid.is_extended());
+    assert_eq!(umid.to_bytes(), &bytes[0..32]);
+    assert_eq!(umid.to_string(), "060A2B340101010501010D20130000020102030405060708090A0B0C0D0E0F10");
+
+    assert_eq!(Umid::from_bytes(&[0u8; 64]), None);
+}
+
+#[test]
+fn test_umid_extended() {
+    let mut umid = Umid::new(0x08, 0x22, 0x123456, [7u8; 16]);
+    umid.source_pack = Some(UmidSourcePack {
+        time_date: [1, 2, 3, 4, 5, 6, 7, 8],
+        altitude: [0, 0, 1, 0], longitude: [0x12, 0x34, 0x56, 0x78], latitude: [0x87, 0x65, 0x43, 0x21],
+        country: *b"USA\0", organization: *b"ACME", user: *b"JH\0\0"
+    });
+
+    let bytes = umid.to_bytes();
+    assert_eq!(bytes.len(), 64);
+    assert_eq!(bytes[12], 0x33);
+    assert_eq!(&bytes[13..16], &[0x12, 0x34, 0x56]);
+
+    let read = Umid::from_bytes(&bytes).unwrap();
+    assert_eq!(read, umid);
+    let pack = read.source_pack.unwrap();
+    assert_eq!(pack.country_code(), "USA");
+    assert_eq!(pack.organization_code(), "ACME");
+    assert_eq!(pack.user_code(), "JH");
+}
+
+#[test]
+fn test_umid_source_pack_fields() {
+    let pack = UmidSourcePack {
+        time_date: [0x29 | 0x40, 0x30, 0x45, 0x23, 0x05, 0x95, 0x80, 0x01],
+        altitude: [0x00, 0x00, 0x12, 0x34],
+        longitude: [0x80 | 0x12, 0x25, 0x00, 0x00],
+        latitude: [0x03, 0x70, 0x12, 0x34],
+        ..UmidSourcePack::default()
+    };
+
+    let time_date = pack.time_date().unwrap();
+    assert_eq!((time_date.hours, time_date.minutes, time_date.seconds, time_date.frames),
+        (23, 45, 30, 29));
+    assert!(time_date.drop_frame);
+    assert_eq!(time_date.date, OriginationDate::new(2022, 1, 1).unwrap());
+    assert_eq!(time_date.time_zone, 1);
+    assert_eq!(pack.altitude(), Some(1234));
+    assert_eq!(pack.longitude(), Some(-122.5));
+    assert_eq!(pack.latitude(), Some(37.01234));
+
+    let mut written = UmidSourcePack::default();
+    written.set_time_date(&time_date);
+    written.set_altitude(1234);
+    written.set_longitude(-122.5);
+    written.set_latitude(37.01234);
+    assert_eq!(written, pack);
+
+    let invalid = UmidSourcePack { time_date: [0xAA; 8], latitude: [0x09, 0x99, 0x99, 0x99],
+        ..UmidSourcePack::default() };
+    assert_eq!(invalid.time_date(), None);
+    assert_eq!(invalid.latitude(), None);
+}
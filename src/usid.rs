@@ -0,0 +1,223 @@
+use super::errors::Error;
+use super::bext::OriginationTime;
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::time::SystemTime;
+
+/// An EBU R99 Unique Source Identifier, for a `Bext`'s `originator_reference`.
+///
+/// A USID is 32 characters long: a two-letter country code, a
+/// three-character organisation code, a twelve-character serial number of
+/// the recorder, the time the recording was made and a nine-digit random
+/// number.
+///
+/// ```
+/// use bwavfile::{Usid, OriginationTime};
+///
+/// let usid = Usid::new("GB", "EBU", "RECORDER0001",
+///     OriginationTime::new(14, 30, 15).unwrap(), 123456789).unwrap();
+/// assert_eq!(usid.to_string(), "GBEBURECORDER0001143015123456789");
+/// assert_eq!(Usid::parse("GBEBURECORDER0001143015123456789").unwrap(), usid);
+///
+/// assert!(Usid::new("GBR", "EBU", "RECORDER0001",
+///     OriginationTime::new(14, 30, 15).unwrap(), 0).is_err());
+///
+/// let generated = Usid::generate("US", "ACM", "SN0000000042").unwrap();
+/// assert_eq!(generated.to_string().len(), 32);
+/// ```
+///
+/// ## Resources
+/// - [EBU Tech R099](https://tech.ebu.ch/docs/r/r099.pdf) (October 2011) "‘Unique’ Source Identifier (USID) for use in the
+///   &lt;OriginatorReference&gt; field of the Broadcast Wave Format"
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Usid {
+    country: String,
+    organization: String,
+    serial_number: String,
+    origination_time: OriginationTime,
+    random_number: u32
+}
+
+const COUNTRY_LENGTH: usize = 2;
+const ORGANIZATION_LENGTH: usize = 3;
+const SERIAL_NUMBER_LENGTH: usize = 12;
+const RANDOM_NUMBER_LIMIT: u32 = 1_000_000_000;
+
+/// The length of a USID in characters.
+const USID_LENGTH: usize = 32;
+
+fn validate_code(value: &str, length: usize, field: &'static str) -> Result<String, Error> {
+    if value.len() == length && value.bytes().all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()) {
+        Ok(value.to_string())
+    } else {
+        Err(Error::UsidFieldInvalid { field })
+    }
+}
+
+fn validate_country(value: &str) -> Result<String, Error> {
+    if value.len() == COUNTRY_LENGTH && value.bytes().all(|c| c.is_ascii_uppercase()) {
+        Ok(value.to_string())
+    } else {
+        Err(Error::UsidFieldInvalid { field: "country" })
+    }
+}
+
+/// The codes identifying a recorder, from which its USIDs are generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UsidSource {
+    country: String,
+    organization: String,
+    serial_number: String
+}
+
+impl UsidSource {
+
+    /// Validate the codes of a recorder, as for `Usid::new()`.
+    pub(crate) fn new(country: &str, organization: &str, serial_number: &str) -> Result<Self, Error> {
+        Ok( UsidSource {
+            country: validate_country(country)?,
+            organization: validate_code(organization, ORGANIZATION_LENGTH, "organization")?,
+            serial_number: validate_code(serial_number, SERIAL_NUMBER_LENGTH, "serial_number")?
+        })
+    }
+
+    /// A new USID from this recorder for a recording made at
+    /// `origination_time`.
+    pub(crate) fn generate_at(&self, origination_time: OriginationTime) -> Result<Usid, Error> {
+        Usid::generate_at(&self.country, &self.organization, &self.serial_number, origination_time)
+    }
+}
+
+impl Usid {
+
+    /// A USID from its fields.
+    ///
+    /// `country` must be an ISO 3166 two-letter country code, in capitals.
+    /// `organization` must be three capital letters or digits, and
+    /// `serial_number` twelve. `random_number` must have at most nine digits.
+    pub fn new(country: &str, organization: &str, serial_number: &str,
+        origination_time: OriginationTime, random_number: u32) -> Result<Self, Error> {
+
+        if random_number >= RANDOM_NUMBER_LIMIT {
+            return Err(Error::UsidFieldInvalid { field: "random_number" });
+        }
+        Ok( Usid {
+            country: validate_country(country)?,
+            organization: validate_code(organization, ORGANIZATION_LENGTH, "organization")?,
+            serial_number: validate_code(serial_number, SERIAL_NUMBER_LENGTH, "serial_number")?,
+            origination_time,
+            random_number
+        })
+    }
+
+    /// A new USID for a recording made now, with a random random number.
+    ///
+    /// The origination time is in UTC.
+    pub fn generate(country: &str, organization: &str, serial_number: &str) -> Result<Self, Error> {
+        Self::generate_at(country, organization, serial_number,
+            OriginationTime::from_system_time(SystemTime::now()))
+    }
+
+    /// A new USID for a recording made at `origination_time`, with a random
+    /// random number.
+    pub fn generate_at(country: &str, organization: &str, serial_number: &str,
+        origination_time: OriginationTime) -> Result<Self, Error> {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default().as_nanos());
+        let random_number = (hasher.finish() % RANDOM_NUMBER_LIMIT as u64) as u32;
+        Self::new(country, organization, serial_number, origination_time, random_number)
+    }
+
+    /// Parse a USID, as found in an `originator_reference`.
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        if s.len() != USID_LENGTH || !s.is_ascii() {
+            return Err(Error::UsidFieldInvalid { field: "originator_reference" });
+        }
+        let (country, rest) = s.split_at(COUNTRY_LENGTH);
+        let (organization, rest) = rest.split_at(ORGANIZATION_LENGTH);
+        let (serial_number, rest) = rest.split_at(SERIAL_NUMBER_LENGTH);
+        let (time, random) = rest.split_at(6);
+
+        let digits = |s: &str| s.bytes().all(|c| c.is_ascii_digit());
+        let origination_time = Some(time)
+            .filter(|t| digits(t))
+            .and_then(|t| OriginationTime::new(t[0..2].parse().ok()?, t[2..4].parse().ok()?,
+                t[4..6].parse().ok()?))
+            .ok_or(Error::UsidFieldInvalid { field: "origination_time" })?;
+        let random_number = Some(random)
+            .filter(|r| digits(r))
+            .and_then(|r| r.parse().ok())
+            .ok_or(Error::UsidFieldInvalid { field: "random_number" })?;
+
+        Self::new(country, organization, serial_number, origination_time, random_number)
+    }
+
+    /// ISO 3166 country code
+    pub fn country(&self) -> &str {
+        &self.country
+    }
+
+    /// Organisation code
+    pub fn organization(&self) -> &str {
+        &self.organization
+    }
+
+    /// Serial number of the recorder
+    pub fn serial_number(&self) -> &str {
+        &self.serial_number
+    }
+
+    /// Time the recording was made
+    pub fn origination_time(&self) -> OriginationTime {
+        self.origination_time
+    }
+
+    /// Random number
+    pub fn random_number(&self) -> u32 {
+        self.random_number
+    }
+}
+
+impl fmt::Display for Usid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let t = self.origination_time;
+        write!(f, "{}{}{}{:02}{:02}{:02}{:09}", self.country, self.organization, self.serial_number,
+            t.hour(), t.minute(), t.second(), self.random_number)
+    }
+}
+
+#[test]
+fn test_usid_validation() {
+    let time = OriginationTime::new(9, 5, 0).unwrap();
+    let usid = Usid::new("CH", "SRG", "000012345678", time, 42).unwrap();
+    assert_eq!(usid.to_string(), "CHSRG000012345678090500000000042");
+    assert_eq!(Usid::parse(&usid.to_string()).unwrap(), usid);
+
+    let field = |r: Result<Usid, Error>| match r {
+        Err(Error::UsidFieldInvalid { field }) => field,
+        _ => "ok"
+    };
+    assert_eq!(field(Usid::new("ch", "SRG", "000012345678", time, 42)), "country");
+    assert_eq!(field(Usid::new("C1", "SRG", "000012345678", time, 42)), "country");
+    assert_eq!(field(Usid::new("CH", "SR", "000012345678", time, 42)), "organization");
+    assert_eq!(field(Usid::new("CH", "SRG", "0000-2345678", time, 42)), "serial_number");
+    assert_eq!(field(Usid::new("CH", "SRG", "000012345678", time, 1_000_000_000)), "random_number");
+
+    assert_eq!(field(Usid::parse("CHSRG000012345678")), "originator_reference");
+    assert_eq!(field(Usid::parse("CHSRG000012345678250500000000042")), "origination_time");
+    assert_eq!(field(Usid::parse("CHSRG00001234567809050000000004x")), "random_number");
+    assert_eq!(field(Usid::parse("CHSRG000012345678090500000000042")), "ok");
+}
+
+#[test]
+fn test_usid_generate() {
+    let time = OriginationTime::new(23, 59, 59).unwrap();
+    let a = Usid::generate_at("DE", "IRT", "ABCDEF123456", time).unwrap();
+    let b = Usid::generate_at("DE", "IRT", "ABCDEF123456", time).unwrap();
+    assert!(a.to_string().starts_with("DEIRTABCDEF123456235959"));
+    assert!(a.random_number() < 1_000_000_000);
+    assert_ne!(a.random_number(), b.random_number());
+}
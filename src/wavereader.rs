@@ -3,22 +3,35 @@ use std::fs::File;
 
 use std::io::SeekFrom;
 use std::io::Cursor;
-use std::io::{Read, Seek, BufReader};
-use std::io::SeekFrom::{Start,Current,};
-
-use super::parser::Parser;
-use super::fourcc::{FourCC, ReadFourCC, FMT__SIG, DATA_SIG, BEXT_SIG, LIST_SIG,
-    JUNK_SIG, FLLR_SIG, CUE__SIG, ADTL_SIG, AXML_SIG, IXML_SIG};
+use std::io::{Read, Write, Seek, BufReader};
+use std::io::SeekFrom::Start;
+
+use super::parser::{Parser, ChunkIteratorItem};
+use super::raw_chunk_reader::RawChunkReader;
+use super::list_form::{ListFormItem, collect_list_form};
+use super::fourcc::{FourCC, FMT__SIG, DATA_SIG, BEXT_SIG, LIST_SIG,
+    JUNK_SIG, FLLR_SIG, CUE__SIG, ADTL_SIG, R64M_SIG, CHNA_SIG, AXML_SIG, IXML_SIG,
+    DBMD_SIG, INFO_SIG, CSET_SIG, SMPL_SIG, INST_SIG, LEVL_SIG, CART_SIG};
 use super::errors::Error as ParserError;
 use super::fmt::{WaveFmt, ChannelDescriptor, ChannelMask};
 use super::bext::Bext;
+use super::timecode::{Timecode, FrameRate, Pull};
 use super::chunks::ReadBWaveChunks;
 use super::cue::Cue;
+use super::dbmd::DolbyMetadata;
+use super::info::Info;
+use super::sampler::{Sampler, Instrument};
+use super::cart::Cart;
+use super::levl::PeakEnvelope;
+#[cfg(feature = "ixml")]
+use super::ixml::IXml;
+#[cfg(feature = "adm")]
+use super::adm::Adm;
 use super::errors::Error;
 use super::CommonFormat;
-
-use byteorder::LittleEndian;
-use byteorder::ReadBytesExt;
+use super::sample::{Sample, SampleEncoding};
+use super::frame_iter::{Frames, Blocks, ChannelSamples};
+use super::wavewriter::AudioFrameWriter;
 
 
 
@@ -32,7 +45,13 @@ pub struct AudioFrameReader<R: Read + Seek> {
     inner : R,
     format: WaveFmt,
     start: u64,
-    length: u64
+    length: u64,
+
+    /// Read position relative to `start`, in bytes
+    position: u64,
+
+    /// Scratch buffer for raw audio data
+    raw_buffer: Vec<u8>
 }
 
 impl<R: Read + Seek> AudioFrameReader<R> {
@@ -44,17 +63,20 @@ impl<R: Read + Seek> AudioFrameReader<R> {
     /// This method does a few sanity checks on the provided format
     /// parameter to confirm the `block_alignment` law is fulfilled
     /// and the format tag is readable by this implementation (only
-    /// format 0x01 is supported at this time.) 
+    /// integer and IEEE float PCM, including the ambisonic B-format 
+    /// variants, are supported at this time.) 
     pub fn new(mut inner: R, format: WaveFmt, start: u64, length: u64) -> Result<Self, Error> {
         assert!(format.block_alignment * 8 == format.bits_per_sample * format.channel_count, 
             "Unable to read audio frames from packed formats: block alignment is {}, should be {}",
             format.block_alignment, (format.bits_per_sample / 8 ) * format.channel_count);
         
-        assert!(format.common_format() == CommonFormat::IntegerPCM , 
+        assert!(matches!(format.common_format(), 
+                CommonFormat::IntegerPCM | CommonFormat::AmbisonicBFormatIntegerPCM |
+                CommonFormat::IeeeFloatPCM | CommonFormat::AmbisonicBFormatIeeeFloatPCM), 
                 "Unsupported format tag {:?}", format.tag);
         
         inner.seek(Start(start))?;
-        Ok( AudioFrameReader { inner , format , start, length} )
+        Ok( AudioFrameReader { inner , format , start, length, position: 0, raw_buffer: vec![] } )
     }
 
     /// Unwrap the inner reader.
@@ -62,6 +84,59 @@ impl<R: Read + Seek> AudioFrameReader<R> {
         self.inner
     }
 
+    /// Format of the audio data.
+    pub fn format(&self) -> WaveFmt {
+        self.format
+    }
+
+    /// The count of frames between the read position and the end of the 
+    /// audio data.
+    pub fn frames_remaining(&self) -> u64 {
+        self.length.saturating_sub(self.position) / self.format.block_alignment as u64
+    }
+
+    /// An iterator over each frame from the read position to the end of 
+    /// the audio data.
+    /// 
+    /// Each frame is a `Vec` of interleaved samples, converted to `S` as 
+    /// described by `Sample`.
+    /// 
+    /// ```
+    /// use bwavfile::WaveReader;
+    /// 
+    /// let mut r = WaveReader::open("tests/media/ff_pink.wav").unwrap();
+    /// let mut frame_reader = r.audio_frame_reader().unwrap();
+    /// 
+    /// for frame in frame_reader.frames::<f32>() {
+    ///     let frame = frame.unwrap();
+    ///     assert_eq!(frame.len(), 2);
+    /// }
+    /// ```
+    pub fn frames<S: Sample>(&mut self) -> Frames<'_, R, S> {
+        Frames::new(self)
+    }
+
+    /// An iterator over blocks of up to `frame_count` frames from the read 
+    /// position to the end of the audio data.
+    /// 
+    /// Each block is a `Vec` of interleaved samples, the final block will
+    /// contain fewer than `frame_count` frames if the audio data is not an
+    /// even multiple of `frame_count` frames long.
+    pub fn blocks<S: Sample>(&mut self, frame_count: usize) -> Blocks<'_, R, S> {
+        Blocks::new(self, frame_count)
+    }
+
+    /// An iterator over the samples of a single channel from the read 
+    /// position to the end of the audio data.
+    /// 
+    /// ### Panics
+    /// 
+    /// This method will panic if `channel` is not less than the channel 
+    /// count.
+    pub fn channel_samples<S: Sample>(&mut self, channel: u16) -> ChannelSamples<'_, R, S> {
+        ChannelSamples::new(self, channel)
+    }
+
     /// Locate the read position to a different frame
     /// 
     /// Seeks within the audio stream.
@@ -73,10 +148,10 @@ impl<R: Read + Seek> AudioFrameReader<R> {
     pub fn locate(&mut self, to :u64) -> Result<u64,Error> {
         let position = to * self.format.block_alignment as u64;
         let seek_result = self.inner.seek(Start(self.start + position))?;
-        Ok( (seek_result - self.start) / self.format.block_alignment as u64 )
+        self.position = seek_result - self.start;
+        Ok( self.position / self.format.block_alignment as u64 )
     }
 
-
     /// Read a frame
     /// 
     /// A single frame is read from the audio stream and the read location
@@ -94,35 +169,246 @@ impl<R: Read + Seek> AudioFrameReader<R> {
     /// ### Panics
     /// 
     /// The `buffer` must have a number of elements equal to the number of 
-    /// channels and this method will panic if this is not the case.
+    /// channels and this method will panic if this is not the case. This 
+    /// method will also panic if the audio data is not integer PCM; use
+    /// `read_float_frame()` to read IEEE float audio data.
     pub fn read_integer_frame(&mut self, buffer:&mut [i32]) -> Result<u64,Error> {
         assert!(buffer.len() as u16 == self.format.channel_count, 
             "read_integer_frame was called with a mis-sized buffer, expected {}, was {}", 
             self.format.channel_count, buffer.len());
 
-        let framed_bits_per_sample = self.format.block_alignment * 8 / self.format.channel_count;
+        self.read_integer_frames(buffer)
+    }
+
+    /// Read frames into a buffer
+    /// 
+    /// Fills `buffer` with as many interleaved frames as it can hold, or as 
+    /// many as remain in the audio data, and advances the read location by
+    /// that number of frames. 
+    /// 
+    /// Samples are written to the buffer in the same way as 
+    /// `read_integer_frame()`.
+    /// 
+    /// Returns the number of frames read, which will be zero if the read
+    /// location is at the end of the audio data.
+    /// 
+    /// ### Panics
+    /// 
+    /// The length of `buffer` must be a multiple of the number of channels 
+    /// and this method will panic if this is not the case. This method will
+    /// also panic if the audio data is not integer PCM; use 
+    /// `read_float_frames()` to read IEEE float audio data.
+    pub fn read_integer_frames(&mut self, buffer:&mut [i32]) -> Result<u64,Error> {
+        let encoding = SampleEncoding::for_format(&self.format);
+        assert!(!encoding.is_float(), 
+            "read_integer_frames was called on IEEE float audio data, use read_float_frames");
+
+        let frames_read = self.read_raw_frames(buffer.len())?;
+        let sample_count = frames_read * self.format.channel_count as usize;
+        self.format.unpack_frames(&self.raw_buffer, &mut buffer[0..sample_count]);
+        Ok( frames_read as u64 )
+    }
 
-        let tell = self.inner.seek(Current(0))?;
+    /// Read frames of any `Sample` type into a buffer
+    /// 
+    /// Fills `buffer` with as many interleaved frames as it can hold, or as 
+    /// many as remain in the audio data, and advances the read location by
+    /// that number of frames. The raw audio data is read from the inner 
+    /// reader in a single request.
+    /// 
+    /// Samples are converted from the file's sample format to `S` as 
+    /// described by `Sample`; for example a 24-bit file can be read directly
+    /// into `f32` or `i16` samples. Note that `i32` samples read this way are
+    /// full-scale, use `read_integer_frames()` to read right-aligned samples.
+    /// 
+    /// Returns the number of frames read, which will be zero if the read
+    /// location is at the end of the audio data.
+    /// 
+    /// ```
+    /// use bwavfile::WaveReader;
+    /// 
+    /// let mut r = WaveReader::open("tests/media/pt_24bit_stereo.wav").unwrap();
+    /// let mut frame_reader = r.audio_frame_reader().unwrap();
+    /// let mut buffer = vec![0f32; 2 * 1024];
+    /// 
+    /// let read = frame_reader.read_frames(&mut buffer).unwrap();
+    /// assert_eq!(read, 1024);
+    /// ```
+    /// 
+    /// ### Panics
+    /// 
+    /// The length of `buffer` must be a multiple of the number of channels 
+    /// and this method will panic if this is not the case.
+    pub fn read_frames<S: Sample>(&mut self, buffer:&mut [S]) -> Result<u64,Error> {
+        let frames_read = self.read_raw_frames(buffer.len())?;
+        let sample_count = frames_read * self.format.channel_count as usize;
+        self.format.unpack_samples(&self.raw_buffer, &mut buffer[0..sample_count]);
+        Ok( frames_read as u64 )
+    }
 
-        if (tell - self.start) < self.length {
-            for n in 0..(self.format.channel_count as usize) {
-                buffer[n] = match (self.format.bits_per_sample, framed_bits_per_sample) {
-                    (0..=8,8) => self.inner.read_u8()? as i32 - 0x80_i32, // EBU 3285 §A2.2
-                    (9..=16,16) => self.inner.read_i16::<LittleEndian>()? as i32,
-                    (10..=24,24) => self.inner.read_i24::<LittleEndian>()?,
-                    (25..=32,32) => self.inner.read_i32::<LittleEndian>()?,
-                    (b,_)=> panic!("Unrecognized integer format, bits per sample {}, channels {}, block_alignment {}", 
-                        b, self.format.channel_count, self.format.block_alignment)
-                }
+    /// Read a frame as floating-point samples
+    /// 
+    /// A single frame is read from the audio stream and the read location
+    /// is advanced one frame.
+    /// 
+    /// IEEE float audio data, in 32- or 64-bit samples, is written back to 
+    /// the buffer as-is (64-bit samples are converted to `f32`). Integer PCM
+    /// audio data is normalized so that full-scale is in the range 
+    /// `-1.0..1.0`, allowing any audio format readable by this 
+    /// `AudioFrameReader` to be read as floats.
+    /// 
+    /// ### Panics
+    /// 
+    /// The `buffer` must have a number of elements equal to the number of 
+    /// channels and this method will panic if this is not the case.
+    pub fn read_float_frame(&mut self, buffer:&mut [f32]) -> Result<u64, Error> {
+        assert!(buffer.len() as u16 == self.format.channel_count, 
+            "read_float_frame was called with a mis-sized buffer, expected {}, was {}", 
+            self.format.channel_count, buffer.len());
+
+        self.read_float_frames(buffer)
+    }
+
+    /// Read frames as floating-point samples into a buffer
+    /// 
+    /// Fills `buffer` with as many interleaved frames as it can hold, or as 
+    /// many as remain in the audio data, in the same way as 
+    /// `read_integer_frames()`. Samples are written to the buffer in the 
+    /// same way as `read_float_frame()`.
+    /// 
+    /// Returns the number of frames read, which will be zero if the read
+    /// location is at the end of the audio data.
+    /// 
+    /// ### Panics
+    /// 
+    /// The length of `buffer` must be a multiple of the number of channels 
+    /// and this method will panic if this is not the case.
+    pub fn read_float_frames(&mut self, buffer:&mut [f32]) -> Result<u64, Error> {
+        self.read_frames(buffer)
+    }
+
+    /// Read a subset of channels into a buffer
+    /// 
+    /// Fills `buffer` with as many frames as it can hold, or as many as 
+    /// remain in the audio data, where each frame contains only the 
+    /// samples of the channels given in `channels`, in the order given. 
+    /// Samples of other channels are not decoded.
+    /// 
+    /// Returns the number of frames read, which will be zero if the read
+    /// location is at the end of the audio data.
+    /// 
+    /// ```
+    /// use bwavfile::WaveReader;
+    /// 
+    /// let mut r = WaveReader::open("tests/media/pt_24bit_51.wav").unwrap();
+    /// let mut frame_reader = r.audio_frame_reader().unwrap();
+    /// 
+    /// // read the right and left channels, in that order
+    /// let mut buffer = vec![0i32; 2 * 512];
+    /// let read = frame_reader.read_channels(&[1, 0], &mut buffer).unwrap();
+    /// assert_eq!(read, 512);
+    /// ```
+    /// 
+    /// ### Panics
+    /// 
+    /// The length of `buffer` must be a multiple of the length of `channels`
+    /// and every channel index must be less than the channel count; this 
+    /// method will panic if this is not the case.
+    pub fn read_channels<S: Sample>(&mut self, channels: &[u16], buffer: &mut [S]) -> Result<u64, Error> {
+        assert!(!channels.is_empty(), "read_channels was called with no channels");
+        assert!(channels.iter().all(|c| *c < self.format.channel_count), 
+            "read_channels was called with a channel index out of range, channel count is {}", 
+            self.format.channel_count);
+        assert_eq!(buffer.len() % channels.len(), 0, 
+            "buffer does not contain a number of samples % channels.len() == 0");
+
+        let encoding = SampleEncoding::for_format(&self.format);
+        let sample_bytes = encoding.byte_count();
+        let frames_requested = buffer.len() / channels.len();
+        let frames_read = self.read_raw_frames(frames_requested * self.format.channel_count as usize)?;
+
+        let raw_frames = self.raw_buffer.chunks_exact(self.format.block_alignment as usize);
+        for (raw_frame, frame) in raw_frames.zip(buffer.chunks_exact_mut(channels.len())) {
+            for (sample, channel) in frame.iter_mut().zip(channels) {
+                let offset = *channel as usize * sample_bytes;
+                *sample = encoding.decode(&raw_frame[offset..offset + sample_bytes]);
             }
-            Ok( 1 )
+        }
+
+        Ok( frames_read as u64 )
+    }
+
+    /// De-interleave the audio data into several writers
+    /// 
+    /// Reads every frame from the read position to the end of the audio 
+    /// data, in a single pass, and writes channel `n` to `writers[n]`. 
+    /// Channels without a corresponding writer are skipped. Samples are
+    /// transferred as `i32` for integer formats and `f64` for float formats,
+    /// so no precision is lost when the writers have the same sample format
+    /// as the reader.
+    /// 
+    /// Returns the number of frames transferred.
+    /// 
+    /// ### Panics
+    /// 
+    /// Every writer must be monoaural and there may not be more writers 
+    /// than channels; this method will panic if this is not the case.
+    pub fn deinterleave_into<W: Write + Seek>(&mut self, writers: &mut [AudioFrameWriter<W>]) -> Result<u64, Error> {
+        if SampleEncoding::for_format(&self.format).is_float() {
+            self.deinterleave_samples::<f64, W>(writers)
         } else {
-            Ok( 0 )
+            self.deinterleave_samples::<i32, W>(writers)
         }
     }
 
-    pub fn read_float_frame(&mut self, buffer:&mut [f32]) -> Result<u64, Error> {
-        todo!()
+    fn deinterleave_samples<S: Sample, W: Write + Seek>(&mut self, writers: &mut [AudioFrameWriter<W>]) -> Result<u64, Error> {
+        assert!(writers.len() <= self.format.channel_count as usize, 
+            "deinterleave_into was called with {} writers, channel count is {}", 
+            writers.len(), self.format.channel_count);
+        assert!(writers.iter().all(|w| w.format().channel_count == 1), 
+            "deinterleave_into requires monoaural writers");
+
+        let block_length = 4096;
+        let channel_count = self.format.channel_count as usize;
+        let mut buffer : Vec<S> = self.format.create_frame_buffer(block_length);
+        let mut channel_buffer : Vec<S> = vec![S::default(); block_length];
+        let mut total = 0u64;
+
+        loop {
+            let frames_read = self.read_frames(&mut buffer)? as usize;
+            if frames_read == 0 { break; }
+
+            for (n, writer) in writers.iter_mut().enumerate() {
+                let frames = buffer[0..frames_read * channel_count].chunks_exact(channel_count);
+                for (sample, frame) in channel_buffer.iter_mut().zip(frames) {
+                    *sample = frame[n];
+                }
+                writer.write_frames(&channel_buffer[0..frames_read])?;
+            }
+
+            total += frames_read as u64;
+        }
+
+        Ok( total )
+    }
+
+    /// Read the raw audio data for up to `sample_count` samples into 
+    /// `raw_buffer` and return the number of frames read.
+    fn read_raw_frames(&mut self, sample_count: usize) -> Result<usize, Error> {
+        let channel_count = self.format.channel_count as usize;
+        let block_alignment = self.format.block_alignment as u64;
+
+        assert_eq!(sample_count % channel_count, 0, 
+            "frames buffer does not contain a number of samples % channel_count == 0");
+
+        let frame_count = self.frames_remaining().min((sample_count / channel_count) as u64);
+        let byte_count = frame_count * block_alignment;
+
+        self.raw_buffer.resize(byte_count as usize, 0);
+        self.inner.read_exact(&mut self.raw_buffer)?;
+        self.position += byte_count;
+
+        Ok( frame_count as usize )
     }
 }
 
@@ -175,6 +461,7 @@ impl<R: Read + Seek> AudioFrameReader<R> {
 #[derive(Debug)]
 pub struct WaveReader<R: Read + Seek> {
     pub inner: R,
+    chunks: Vec<ChunkIteratorItem>,
 }
 
 impl WaveReader<BufReader<File>> {
@@ -211,7 +498,11 @@ impl<R: Read + Seek> WaveReader<R> {
     /// will return an `Err(errors::Error)` immediately if there is a structural 
     /// inconsistency that makes the stream unreadable or if it's missing 
     /// essential components that make interpreting the audio data impossible.
-     
+    ///
+    /// The chunk structure of the file is parsed once, here, and the 
+    /// resulting chunk list is used by all of the other methods, so
+    /// metadata accessors don't re-walk the file.
+    ///
     /// ```rust
     /// use std::fs::File;
     /// use std::io::{Error,ErrorKind};
@@ -230,8 +521,9 @@ impl<R: Read + Seek> WaveReader<R> {
     /// }
     /// 
     /// ```
-    pub fn new(inner: R) -> Result<Self,ParserError> {
-        let mut retval = Self { inner };
+    pub fn new(mut inner: R) -> Result<Self,ParserError> {
+        let chunks = Parser::make(&mut inner)?.into_chunk_list()?;
+        let mut retval = Self { inner, chunks };
         retval.validate_readable()?;
         Ok(retval)
     }
@@ -242,6 +534,28 @@ impl<R: Read + Seek> WaveReader<R> {
         return self.inner;
     }
 
+    /// The chunks in the file, in the order they appear.
+    ///
+    /// This list is read once when the `WaveReader` is created. Each item
+    /// gives the signature of the chunk and the file offset and length of
+    /// its content, not including the eight-byte chunk header. `LIST` chunks
+    /// also give their form type.
+    ///
+    /// ```
+    /// use bwavfile::WaveReader;
+    ///
+    /// let w = WaveReader::open("tests/media/ff_minimal.wav").unwrap();
+    /// let chunks = w.chunks();
+    ///
+    /// assert_eq!(chunks.len(), 2);
+    /// assert_eq!(chunks[0].start, 20);
+    /// assert_eq!(chunks[0].length, 16);
+    /// assert_eq!(chunks[1].start, 44);
+    /// ```
+    pub fn chunks(&self) -> &[ChunkIteratorItem] {
+        &self.chunks
+    }
+
     ///
     /// Create an `AudioFrameReader` for reading each audio frame and consume the `WaveReader`.
     ///
@@ -282,9 +596,46 @@ impl<R: Read + Seek> WaveReader<R> {
 
     }
 
+    /// The timecode of the start of the file at `rate`, from the
+    /// `time_reference` of its Broadcast-WAV metadata.
+    ///
+    /// Returns `Ok(None)` if the file has no Broadcast-WAV metadata. With
+    /// the `ixml` feature, the frame rate the file was recorded at can be
+    /// read with `timecode_rate()`.
+    ///
+    /// ```
+    /// use bwavfile::{WaveReader, FrameRate};
+    ///
+    /// let mut r = WaveReader::open("tests/media/pt_24bit.wav").unwrap();
+    /// let tc = r.start_timecode(FrameRate::Fps24).unwrap().unwrap();
+    /// assert_eq!(tc.to_string(), "05:59:52:00");
+    /// ```
+    pub fn start_timecode(&mut self, rate: FrameRate) -> Result<Option<Timecode>, ParserError> {
+        self.start_timecode_pulled(rate, Pull::Nominal)
+    }
+
+    /// The timecode of the start of the file at `rate`, with the samples of
+    /// the `time_reference` counted at a pulled rate.
+    pub fn start_timecode_pulled(&mut self, rate: FrameRate, pull: Pull) -> Result<Option<Timecode>, ParserError> {
+        let sample_rate = self.format()?.sample_rate;
+        Ok( self.broadcast_extension()?
+            .map(|bext| Timecode::from_samples_pulled(bext.time_reference, sample_rate, rate, pull)) )
+    }
+
+    /// The timecode frame rate recorded in the `SPEED` of the file's iXML
+    /// metadata.
+    ///
+    /// Returns `Ok(None)` if there is no iXML metadata or it doesn't give a
+    /// recognized frame rate. Only available with the `ixml` feature.
+    #[cfg(feature = "ixml")]
+    pub fn timecode_rate(&mut self) -> Result<Option<FrameRate>, ParserError> {
+        Ok( self.ixml()?.and_then(|ixml| ixml.speed).and_then(|speed| speed.frame_rate()) )
+    }
+
     /// Describe the channels in this file
     /// 
-    /// Returns a vector of channel descriptors, one for each channel
+    /// Returns a vector of channel descriptors, one for each channel. If the
+    /// file has a `chna` chunk, each channel's ADM audio IDs are read from it.
     /// 
     /// ```rust
     /// use bwavfile::WaveReader;
@@ -309,13 +660,32 @@ impl<R: Read + Seek> WaveReader<R> {
             (n,_) => vec![ChannelMask::DirectOut; n as usize]
         };
 
+        let mut chna_buffer : Vec<u8> = vec![];
+        let audio_ids = if self.read_chunk(CHNA_SIG, 0, &mut chna_buffer)? > 0 {
+            Cursor::new(chna_buffer).read_chna()?
+        } else {
+            vec![]
+        };
+
         Ok( (0..format.channel_count).zip(channel_masks)
-            .map(|(i,m)| ChannelDescriptor { index: i, speaker:m, adm_track_audio_ids: vec![] } )
+            .map(|(i,m)| ChannelDescriptor { 
+                index: i, 
+                speaker:m, 
+                adm_track_audio_ids: audio_ids.iter()
+                    .filter(|(track_index, _)| *track_index == i + 1)
+                    .map(|(_, id)| *id)
+                    .collect() 
+            })
             .collect() )
     }
 
     /// Read cue points.
     /// 
+    /// Cue points are read from the `cue ` chunk and the `adtl` `LIST`. If the
+    /// file has an RF64 `r64m` marker chunk, its 64-bit positions are merged
+    /// into the cue points they are linked to, and markers that don't link to
+    /// a cue point are added to the list.
+    /// 
     /// ```rust
     /// use bwavfile::WaveReader;
     /// use bwavfile::Cue;
@@ -325,33 +695,42 @@ impl<R: Read + Seek> WaveReader<R> {
     /// 
     /// assert_eq!(cue_points.len(), 3);
     /// assert_eq!(cue_points[0].frame, 12532);
-    /// assert_eq!(cue_points[0].length, None);
-    /// assert_eq!(cue_points[0].label, Some(String::from("Marker 1")));
-    /// assert_eq!(cue_points[0].note, Some(String::from("Marker 1 Comment")));
+    /// assert_eq!(cue_points[0].length(), None);
+    /// assert_eq!(cue_points[0].label(), Some("Marker 1"));
+    /// assert_eq!(cue_points[0].note(), Some("Marker 1 Comment"));
     /// 
     /// assert_eq!(cue_points[1].frame, 20997);
-    /// assert_eq!(cue_points[1].length, None);
-    /// assert_eq!(cue_points[1].label, Some(String::from("Marker 2")));
-    /// assert_eq!(cue_points[1].note, Some(String::from("Marker 2 Comment"))); 
+    /// assert_eq!(cue_points[1].length(), None);
+    /// assert_eq!(cue_points[1].label(), Some("Marker 2"));
+    /// assert_eq!(cue_points[1].note(), Some("Marker 2 Comment")); 
     /// 
     /// assert_eq!(cue_points[2].frame, 26711);
-    /// assert_eq!(cue_points[2].length, Some(6465));
-    /// assert_eq!(cue_points[2].label, Some(String::from("Timed Region")));
-    /// assert_eq!(cue_points[2].note, Some(String::from("Region Comment"))); 
+    /// assert_eq!(cue_points[2].length(), Some(6465));
+    /// assert_eq!(cue_points[2].label(), Some("Timed Region"));
+    /// assert_eq!(cue_points[2].note(), Some("Region Comment")); 
     /// 
     /// ```
     pub fn cue_points(&mut self) -> Result<Vec<Cue>,ParserError> {
         let mut cue_buffer : Vec<u8> = vec![];
         let mut adtl_buffer : Vec<u8> = vec![];
 
+        let mut r64m_buffer : Vec<u8> = vec![];
+
         let cue_read = self.read_chunk(CUE__SIG, 0, &mut cue_buffer)?;
         let adtl_read = self.read_list(ADTL_SIG, &mut adtl_buffer)?;
+        let r64m_read = self.read_chunk(R64M_SIG, 0, &mut r64m_buffer)?;
 
-        match (cue_read, adtl_read) {
-            (0,_) => Ok( vec![] ),
-            (_,0) => Ok( Cue::collect_from(&cue_buffer, None)? ),
-            (_,_) => Ok( Cue::collect_from(&cue_buffer, Some(&adtl_buffer) )? )
+        let mut cues = match (cue_read, adtl_read) {
+            (0,_) => vec![],
+            (_,0) => Cue::collect_from(&cue_buffer, None)?,
+            (_,_) => Cue::collect_from(&cue_buffer, Some(&adtl_buffer) )?
+        };
+
+        if r64m_read > 0 {
+            Cue::merge_rf64_markers(&mut cues, &r64m_buffer)?;
         }
+
+        Ok( cues )
     }
 
     /// Read iXML data.
@@ -363,6 +742,20 @@ impl<R: Read + Seek> WaveReader<R> {
         self.read_chunk(IXML_SIG, 0, buffer) 
     }
 
+    /// Read and parse iXML metadata.
+    /// 
+    /// Returns `Ok(None)` if there is no iXML metadata present in the file.
+    /// Only available with the `ixml` feature.
+    #[cfg(feature = "ixml")]
+    pub fn ixml(&mut self) -> Result<Option<IXml>, ParserError> {
+        let mut buffer = vec![];
+        if self.read_ixml(&mut buffer)? > 0 {
+            Ok( Some( IXml::parse(&buffer)? ) )
+        } else {
+            Ok( None )
+        }
+    }
+
     /// Read AXML data.
     /// 
     /// The axml data will be appended to `buffer`. By convention this will 
@@ -374,6 +767,194 @@ impl<R: Read + Seek> WaveReader<R> {
         self.read_chunk(AXML_SIG, 0, buffer)
     }
 
+    /// Read and parse ADM metadata from the axml chunk.
+    ///
+    /// Returns `Ok(None)` if there is no axml metadata present in the file.
+    /// The tracks of the file are related to the ADM by the `chna` chunk,
+    /// see `channels()`. Only available with the `adm` feature.
+    #[cfg(feature = "adm")]
+    pub fn adm(&mut self) -> Result<Option<Adm>, ParserError> {
+        let mut buffer = vec![];
+        if self.read_axml(&mut buffer)? > 0 {
+            Ok( Some( Adm::parse(&buffer)? ) )
+        } else {
+            Ok( None )
+        }
+    }
+
+    /// Read and parse Dolby metadata from the `dbmd` chunk.
+    ///
+    /// Returns `Ok(None)` if there is no Dolby metadata present in the file.
+    pub fn dolby_metadata(&mut self) -> Result<Option<DolbyMetadata>, ParserError> {
+        let mut buffer = vec![];
+        if self.read_chunk(DBMD_SIG, 0, &mut buffer)? > 0 {
+            Ok( Some( DolbyMetadata::parse(&buffer)? ) )
+        } else {
+            Ok( None )
+        }
+    }
+
+    /// Read INFO metadata from the `LIST` chunk with form type `INFO`.
+    ///
+    /// Strings are decoded with the code page in the file's `CSET` chunk,
+    /// if it has one. Otherwise each string is decoded as UTF-8 if it's 
+    /// valid UTF-8 and as Windows-1252 if not.
+    ///
+    /// Returns `Ok(None)` if there is no INFO metadata in the file.
+    pub fn info(&mut self) -> Result<Option<Info>, ParserError> {
+        let mut cset = vec![];
+        let code_page = if self.read_chunk(CSET_SIG, 0, &mut cset)? >= 2 {
+            Some(u16::from_le_bytes([cset[0], cset[1]]))
+        } else {
+            None
+        };
+        self.info_with_code_page(code_page)
+    }
+
+    /// Read INFO metadata, decoding strings with a given code page.
+    ///
+    /// If `code_page` is `None`, each string is decoded as UTF-8 if it's 
+    /// valid UTF-8 and as Windows-1252 if not.
+    pub fn info_with_code_page(&mut self, code_page: Option<u16>) -> Result<Option<Info>, ParserError> {
+        if self.get_list_form(INFO_SIG)?.is_some() {
+            let items = self.list_form_items(INFO_SIG)?;
+            Ok( Some( Info::from_list_items(&items, code_page) ) )
+        } else {
+            Ok( None )
+        }
+    }
+
+    /// Read sampler metadata from the `smpl` chunk.
+    ///
+    /// Sample loops refer to the cues in `cue_points()` by their ident.
+    /// Returns `Ok(None)` if there is no sampler metadata in the file.
+    pub fn sampler(&mut self) -> Result<Option<Sampler>, ParserError> {
+        let mut buffer = vec![];
+        if self.read_chunk(SMPL_SIG, 0, &mut buffer)? > 0 {
+            Ok( Some( Sampler::parse(&buffer)? ) )
+        } else {
+            Ok( None )
+        }
+    }
+
+    /// Read instrument metadata from the `inst` chunk.
+    ///
+    /// Returns `Ok(None)` if there is no instrument metadata in the file.
+    pub fn instrument(&mut self) -> Result<Option<Instrument>, ParserError> {
+        let mut buffer = vec![];
+        if self.read_chunk(INST_SIG, 0, &mut buffer)? > 0 {
+            Ok( Some( Instrument::parse(&buffer)? ) )
+        } else {
+            Ok( None )
+        }
+    }
+
+    /// Read radio traffic metadata from the `cart` chunk.
+    ///
+    /// Returns `Ok(None)` if there is no cart metadata in the file.
+    pub fn cart(&mut self) -> Result<Option<Cart>, ParserError> {
+        let mut buffer = vec![];
+        if self.read_chunk(CART_SIG, 0, &mut buffer)? > 0 {
+            Ok( Some( Cart::parse(&buffer)? ) )
+        } else {
+            Ok( None )
+        }
+    }
+
+    /// Read the peak envelope from the `levl` chunk.
+    ///
+    /// Returns `Ok(None)` if there is no peak envelope in the file.
+    pub fn peak_envelope(&mut self) -> Result<Option<PeakEnvelope>, ParserError> {
+        let mut buffer = vec![];
+        if self.read_chunk(LEVL_SIG, 0, &mut buffer)? > 0 {
+            Ok( Some( PeakEnvelope::parse(&buffer)? ) )
+        } else {
+            Ok( None )
+        }
+    }
+
+    /// Read the content of a chunk.
+    ///
+    /// Reads the content of the `index`th chunk with signature `ident` into
+    /// `buffer`, replacing its contents. If there is no such chunk in the
+    /// file, `Ok(0)` will be returned.
+    ///
+    /// This can be used to read chunks this crate does not otherwise
+    /// interpret.
+    ///
+    /// ```
+    /// use bwavfile::{WaveReader, FourCC};
+    ///
+    /// let mut f = WaveReader::open("tests/media/pt_24bit.wav").unwrap();
+    /// let mut buf = vec![];
+    ///
+    /// let read = f.read_chunk(FourCC::make(b"minf"), 0, &mut buf).unwrap();
+    /// assert_eq!(read, buf.len());
+    ///
+    /// let read = f.read_chunk(FourCC::make(b"zzzz"), 0, &mut buf).unwrap();
+    /// assert_eq!(read, 0);
+    /// ```
+    pub fn read_chunk(&mut self, ident: FourCC, index: u32, buffer: &mut Vec<u8>) -> Result<usize, ParserError> {
+        match self.get_chunk_extent_at_index(ident, index) {
+            Ok((start, length)) => {
+                buffer.resize(length as usize, 0x0);
+                self.inner.seek(SeekFrom::Start(start))?;
+                self.inner.read_exact(buffer)?;
+                Ok( buffer.len() )
+            },
+            Err(ParserError::ChunkMissing { signature : _} ) => Ok(0),
+            Err( any ) => Err(any)
+        }
+    }
+
+    /// Read the content of a `LIST` chunk.
+    ///
+    /// Reads the content of the first `LIST` chunk with form type `form`,
+    /// including the form type itself, into `buffer`. If there is no such
+    /// `LIST` in the file, `Ok(0)` will be returned.
+    pub fn read_list(&mut self, form: FourCC, buffer: &mut Vec<u8>) -> Result<usize, ParserError> {
+        if let Some(index) = self.get_list_form(form)? {
+            self.read_chunk(LIST_SIG, index, buffer)
+        } else {
+            Ok( 0 )
+        }
+    }
+
+    /// The sub-chunks of a `LIST` chunk.
+    ///
+    /// Reads the first `LIST` chunk with form type `form` and returns each
+    /// of its sub-chunks, in order. If there is no such `LIST` in the file
+    /// the returned vector will be empty.
+    pub fn list_form_items(&mut self, form: FourCC) -> Result<Vec<ListFormItem>, ParserError> {
+        let mut buffer = vec![];
+        if self.read_list(form, &mut buffer)? > 0 {
+            Ok( collect_list_form(&buffer)? )
+        } else {
+            Ok( vec![] )
+        }
+    }
+
+    /// A bounded reader over the content of a chunk.
+    ///
+    /// The returned reader reads the content of the `index`th chunk with
+    /// signature `ident`, and can be used to stream large chunks without
+    /// reading them into memory all at once.
+    ///
+    /// ```
+    /// use std::io::Read;
+    /// use bwavfile::{WaveReader, FourCC};
+    ///
+    /// let mut f = WaveReader::open("tests/media/ff_minimal.wav").unwrap();
+    /// let mut reader = f.chunk_reader(FourCC::make(b"fmt "), 0).unwrap();
+    ///
+    /// let mut buf = vec![];
+    /// reader.read_to_end(&mut buf).unwrap();
+    /// assert_eq!(buf.len(), 16);
+    /// ```
+    pub fn chunk_reader(&mut self, ident: FourCC, index: u32) -> Result<RawChunkReader<'_, R>, ParserError> {
+        let (start, length) = self.get_chunk_extent_at_index(ident, index)?;
+        Ok( RawChunkReader::new(&mut self.inner, start, length) )
+    }
 
     /**
     * Validate file is readable.
@@ -424,8 +1005,8 @@ impl<R: Read + Seek> WaveReader<R> {
     pub fn validate_minimal(&mut self) -> Result<(), ParserError>  {
         self.validate_readable()?;
 
-        let chunk_fourccs : Vec<FourCC> = Parser::make(&mut self.inner)?
-            .into_chunk_list()?.iter().map(|c| c.signature ).collect();
+        let chunk_fourccs : Vec<FourCC> = self.chunks.iter()
+            .map(|c| c.signature ).collect();
 
         if chunk_fourccs == vec![FMT__SIG, DATA_SIG] {
             Ok(()) /* FIXME: finish implementation */
@@ -485,7 +1066,7 @@ impl<R: Read + Seek> WaveReader<R> {
     pub fn validate_prepared_for_append(&mut self) -> Result<(), ParserError> {
         self.validate_readable()?;
 
-        let chunks = Parser::make(&mut self.inner)?.into_chunk_list()?;
+        let chunks = &self.chunks;
         let ds64_space_required = 92;
 
         let eligible_filler_chunks = chunks.iter()
@@ -511,56 +1092,19 @@ impl<R: Read + Seek> WaveReader<R> {
 impl<R:Read+Seek> WaveReader<R> {
 
     // Private implementation
-    //
-    // As time passes thi get smore obnoxious because I haven't implemented recursive chunk 
-    // parsing in the raw parser and I'm working around it
-
-    // fn chunk_reader(&mut self, signature: FourCC, at_index: u32) -> Result<RawChunkReader<R>, ParserError> {
-    //     let (start, length) = self.get_chunk_extent_at_index(signature, at_index)?;
-    //     Ok( RawChunkReader::new(&mut self.inner, start, length) )
-    // } 
-
-    fn read_list(&mut self, ident: FourCC, buffer: &mut Vec<u8>) -> Result<usize, ParserError> {
-        if let Some(index) = self.get_list_form(ident)? {
-            self.read_chunk(LIST_SIG, index, buffer)
-        } else {
-            Ok( 0 )
-        }
-    }
-
-
-    fn read_chunk(&mut self, ident: FourCC, at: u32, mut buffer: &mut Vec<u8>) -> Result<usize, ParserError> {
-
-        match self.get_chunk_extent_at_index(ident, at) {
-            Ok((start, length)) => {
-                buffer.resize(length as usize, 0x0);
-                self.inner.seek(SeekFrom::Start(start))?;
-                self.inner.read(&mut buffer).map_err(|e| ParserError::IOError(e))
-            },
-            Err(ParserError::ChunkMissing { signature : _} ) => Ok(0),
-            Err( any ) => Err(any.into())
-        }
-    }
 
     /// Extent of every chunk with the given fourcc
     fn get_chunks_extents(&mut self, fourcc: FourCC) -> Result<Vec<(u64,u64)>, ParserError> {
-        let p = Parser::make(&mut self.inner)?.into_chunk_list()?;
-
-        Ok( p.iter().filter(|item| item.signature == fourcc)
+        Ok( self.chunks.iter().filter(|item| item.signature == fourcc)
             .map(|item| (item.start, item.length)).collect() )
     }
 
     /// Index of first LIST for with the given FORM fourcc
     fn get_list_form(&mut self, fourcc: FourCC) -> Result<Option<u32>, ParserError> {
-        for (n, (start, _)) in self.get_chunks_extents(LIST_SIG)?.iter().enumerate() {
-            self.inner.seek(SeekFrom::Start(*start as u64))?;
-            let this_fourcc = self.inner.read_fourcc()?;
-            if this_fourcc == fourcc {
-                return Ok( Some( n as u32 ) );
-            }
-        }
-
-        Ok( None )
+        Ok( self.chunks.iter()
+            .filter(|item| item.signature == LIST_SIG)
+            .position(|item| item.form == Some(fourcc))
+            .map(|n| n as u32) )
     }
 
     fn get_chunk_extent_at_index(&mut self, fourcc: FourCC, index: u32) -> Result<(u64,u64), ParserError> {
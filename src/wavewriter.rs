@@ -1,14 +1,29 @@
 use std::fs::File;
 use std::io::{Write,Seek,SeekFrom,Cursor,BufWriter};
+use std::time::SystemTime;
 
 use super::Error;
 use super::fourcc::{FourCC, WriteFourCC, RIFF_SIG, RF64_SIG, DS64_SIG,
     WAVE_SIG, FMT__SIG, DATA_SIG, ELM1_SIG, JUNK_SIG, BEXT_SIG,AXML_SIG, 
-    IXML_SIG};
-use super::fmt::WaveFmt;
-//use super::common_format::CommonFormat;
+    IXML_SIG, FACT_SIG, LIST_SIG, CUE__SIG, ADTL_SIG, R64M_SIG, CHNA_SIG,
+    DBMD_SIG, INFO_SIG, CSET_SIG, SMPL_SIG, INST_SIG, LEVL_SIG, CART_SIG};
+use super::list_form::{ListFormItem, compile_list_form};
+use super::dbmd::DolbyMetadata;
+use super::info::Info;
+use super::sampler::{Sampler, Instrument};
+use super::cart::Cart;
+use super::levl::{PeakEnvelope, PeakEnvelopeGenerator, PeakFormat};
+use super::loudness::LoudnessMeter;
+use super::fmt::{WaveFmt, ChannelDescriptor, ADMAudioID};
+use super::common_format::CommonFormat;
+use super::sample::{Sample, SampleEncoding};
 use super::chunks::WriteBWaveChunks;
-use super::bext::Bext;
+use super::bext::{Bext, OriginationDate, OriginationTime};
+use super::usid::UsidSource;
+use super::timecode::{Timecode, Pull};
+#[cfg(feature = "ixml")]
+use super::ixml::IXml;
+use super::cue::Cue;
 
 use byteorder::LittleEndian;
 use byteorder::WriteBytesExt;
@@ -26,36 +41,114 @@ impl<W> AudioFrameWriter<W> where W: Write + Seek {
         AudioFrameWriter { inner }
     }
 
-    fn write_integer_frames_to_buffer(&self, from_frames :&[i32], to_buffer : &mut [u8]) -> () {
-        assert!(from_frames.len() % self.inner.inner.format.channel_count as usize == 0, 
-            "frames buffer does not contain a number of samples % channel_count == 0");
-        self.inner.inner.format.pack_frames(&from_frames, to_buffer);
-        ()
+    /// Format of the audio data.
+    pub fn format(&self) -> WaveFmt {
+        self.inner.inner.format
     }
 
     /// Write interleaved samples in `buffer`
     /// 
+    /// Samples are "right-aligned" integers with the sample size of the 
+    /// file, the same as the samples returned by 
+    /// `AudioFrameReader::read_integer_frame()`.
+    /// 
+    /// Returns the number of frames written.
+    /// 
     /// # Panics
     /// 
     /// This function will panic if `buffer.len()` modulo the Wave file's channel count
     /// is not zero.
     pub fn write_integer_frames(&mut self, buffer: &[i32]) -> Result<u64,Error> {
-        let mut write_buffer = self.inner.inner.format
-            .create_raw_buffer(buffer.len() / self.inner.inner.format.channel_count as usize);
+        let encoding = SampleEncoding::for_format(&self.inner.inner.format);
+        let shift = 32u16.saturating_sub(encoding.bit_count());
+        let aligned : Vec<i32> = buffer.iter().map(|s| s << shift).collect();
+        self.write_frames(&aligned)
+    }
 
-        self.write_integer_frames_to_buffer(&buffer, &mut write_buffer);
+    /// Write interleaved floating-point samples in `buffer`
+    /// 
+    /// Samples are written as-is to IEEE float files. If the file has an
+    /// integer format, samples are scaled so that `-1.0..1.0` spans the 
+    /// full integer range and samples outside of this range are clipped.
+    /// 
+    /// Returns the number of frames written.
+    /// 
+    /// # Panics
+    /// 
+    /// This function will panic if `buffer.len()` modulo the Wave file's channel count
+    /// is not zero.
+    pub fn write_float_frames(&mut self, buffer: &[f32]) -> Result<u64,Error> {
+        self.write_frames(buffer)
+    }
 
-        self.inner.write(&write_buffer)?;
+    /// Write interleaved double-precision floating-point samples in `buffer`
+    /// 
+    /// Behaves identically to `write_float_frames()`, 64-bit float files 
+    /// will preserve the full precision of the samples.
+    /// 
+    /// # Panics
+    /// 
+    /// This function will panic if `buffer.len()` modulo the Wave file's channel count
+    /// is not zero.
+    pub fn write_double_frames(&mut self, buffer: &[f64]) -> Result<u64,Error> {
+        self.write_frames(buffer)
+    }
+
+    /// Write interleaved samples of any `Sample` type in `buffer`
+    /// 
+    /// Samples are converted to the file's sample format as described by
+    /// `Sample`; for example `i16` samples written to a 24-bit file are
+    /// scaled to 24 bits.
+    /// 
+    /// Returns the number of frames written.
+    /// 
+    /// # Panics
+    /// 
+    /// This function will panic if `buffer.len()` modulo the Wave file's channel count
+    /// is not zero.
+    pub fn write_frames<S: Sample>(&mut self, buffer: &[S]) -> Result<u64,Error> {
+        let format = self.inner.inner.format;
+        let frame_count = buffer.len() / format.channel_count as usize;
+        let mut write_buffer = format.create_raw_buffer(frame_count);
+
+        format.pack_samples(buffer, &mut write_buffer);
+        if let Some(generator) = self.inner.inner.peak_envelope.as_mut() {
+            generator.push_frames(buffer);
+        }
+        if let Some(meter) = self.inner.inner.loudness_meter.as_mut() {
+            meter.push_frames(buffer);
+        }
+
+        self.inner.write_all(&write_buffer)?;
         self.inner.flush()?;
-        Ok(write_buffer.len() as u64 / self.inner.inner.format.channel_count as u64)
+        Ok(frame_count as u64)
+    }
+
+    /// The loudness of the audio written so far, if the `WaveWriter` is
+    /// measuring loudness.
+    pub fn loudness_meter(&self) -> Option<&LoudnessMeter> {
+        self.inner.inner.loudness_meter.as_ref()
     }
 
     /// Finish writing audio frames and unwrap the inner `WaveWriter`.
     /// 
     /// This method must be called when the client has finished writing audio
-    /// data. This will finalize the audio data chunk.
+    /// data. This will finalize the audio data chunk, and the `fact` chunk
+    /// if the file has one.
+    /// 
+    /// If the `WaveWriter` is generating a peak envelope, the `levl` chunk
+    /// is written after the audio data.
+    /// 
+    /// The returned `WaveWriter` can be used to add more metadata chunks to
+    /// the file, which will be written after the audio data.
     pub fn end(self) -> Result<WaveWriter<W>, Error> {
-        self.inner.end()
+        let frame_count = self.inner.length / self.inner.inner.format.block_alignment as u64;
+        let mut inner = self.inner.end()?;
+        inner.update_fact_frame_count(frame_count)?;
+        if let Some(generator) = inner.peak_envelope.take() {
+            inner.write_chunk(LEVL_SIG, &generator.finish().to_bytes())?;
+        }
+        Ok( inner )
     }
 }
 
@@ -205,7 +298,23 @@ pub struct WaveWriter<W> where W: Write + Seek {
     pub is_rf64: bool,
 
     /// Format of the wave file.
-    pub format: WaveFmt
+    pub format: WaveFmt,
+
+    /// Position of the `fact` chunk's content, if one was written
+    fact_content_pos: Option<u64>,
+
+    /// Peak envelope of the audio written, if one is being generated
+    peak_envelope: Option<PeakEnvelopeGenerator>,
+
+    /// Loudness of the audio written, if it's being measured
+    loudness_meter: Option<LoudnessMeter>,
+
+    /// Source of generated USIDs, if empty originator references should be
+    /// filled in
+    usid_source: Option<UsidSource>,
+
+    /// Start timecode to stamp into metadata, and its time reference
+    start_timecode: Option<(Timecode, u64)>
 }
 
 const DS64_RESERVATION_LENGTH : u32 = 96;
@@ -235,12 +344,19 @@ impl<W> WaveWriter<W> where W: Write + Seek {
     /// The inner writer will immediately have a RIFF WAVE file header 
     /// written to it along with the format descriptor (and possibly a `fact`
     /// chunk if appropriate).
+    /// 
+    /// A `fact` chunk is written for every format other than integer PCM, 
+    /// as required by the RIFF specification for non-PCM audio data. Its 
+    /// frame count is filled-in when the audio data is finished with 
+    /// `AudioFrameWriter::end()`.
     pub fn new(mut inner : W, format: WaveFmt) -> Result<Self, Error> {
         inner.write_fourcc(RIFF_SIG)?;
         inner.write_u32::<LittleEndian>(0)?;
         inner.write_fourcc(WAVE_SIG)?;
 
-        let mut retval = WaveWriter { inner, form_length: 0, is_rf64: false, format};
+        let mut retval = WaveWriter { inner, form_length: 0, is_rf64: false, format, 
+            fact_content_pos: None, peak_envelope: None, loudness_meter: None, usid_source: None,
+            start_timecode: None };
 
         retval.increment_form_length(4)?;
 
@@ -249,33 +365,119 @@ impl<W> WaveWriter<W> where W: Write + Seek {
 
         let mut chunk = retval.chunk(FMT__SIG)?;
         chunk.write_wave_fmt(&format)?;
-        let retval = chunk.end()?;
+        let mut retval = chunk.end()?;
+
+        if format.common_format() != CommonFormat::IntegerPCM {
+            let fact_start = retval.inner.seek(SeekFrom::End(0))?;
+            retval.write_chunk(FACT_SIG, &[0u8; 4])?;
+            retval.fact_content_pos = Some(fact_start + 8);
+        }
 
         Ok( retval )
     }
 
-    fn write_chunk(&mut self, ident: FourCC, data : &[u8]) -> Result<(),Error> {
+    /// Write a chunk with signature `ident` and content `data`.
+    ///
+    /// This function will write the chunk immediately to the end of the
+    /// file; if you have already written and closed the audio data the chunk
+    /// will be positioned after it. A pad byte is added if `data` has an odd
+    /// length.
+    ///
+    /// This can be used to write chunks this crate does not otherwise model,
+    /// or to pass-through chunks read with `WaveReader::read_chunk()`. Clients
+    /// should not use this to write `fmt `, `data` or `ds64` chunks, which
+    /// `WaveWriter` creates itself.
+    ///
+    /// ```
+    /// use bwavfile::{WaveWriter, WaveReader, WaveFmt, FourCC};
+    /// # use std::io::Cursor;
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8;0]);
+    /// let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 24)).unwrap();
+    /// w.write_chunk(FourCC::make(b"abcd"), b"before audio").unwrap();
+    ///
+    /// let mut frame_writer = w.audio_frame_writer().unwrap();
+    /// frame_writer.write_integer_frames(&[0i32, 0i32]).unwrap();
+    /// let mut w = frame_writer.end().unwrap();
+    /// w.write_chunk(FourCC::make(b"wxyz"), b"after audio").unwrap();
+    ///
+    /// let mut r = WaveReader::new(&mut cursor).unwrap();
+    /// let mut buf = vec![];
+    /// r.read_chunk(FourCC::make(b"wxyz"), 0, &mut buf).unwrap();
+    /// assert_eq!(buf, b"after audio");
+    /// ```
+    pub fn write_chunk(&mut self, ident: FourCC, data : &[u8]) -> Result<(),Error> {
         self.inner.seek(SeekFrom::End(0))?;
         self.inner.write_fourcc(ident)?;
         assert!(data.len() < u32::MAX as usize);
         self.inner.write_u32::<LittleEndian>(data.len() as u32)?;
-        self.inner.write(data)?;
+        self.inner.write_all(data)?;
         if data.len() % 2 == 0 {
             self.increment_form_length(8 + data.len() as u64)?;
         } else {
-            self.inner.write(&[0u8])?;
+            self.inner.write_all(&[0u8])?;
             self.increment_form_length(8 + data.len() as u64 + 1)?;
         }
         Ok(())
     }
 
+    /// Write a `LIST` chunk with form type `form` containing `items`.
+    ///
+    /// Each item is written as a sub-chunk of the `LIST`, in order. Like
+    /// `write_chunk()` the `LIST` is written immediately to the end of the
+    /// file.
+    ///
+    /// ```
+    /// use bwavfile::{WaveWriter, WaveReader, WaveFmt, FourCC, ListFormItem};
+    /// # use std::io::Cursor;
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8;0]);
+    /// let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 24)).unwrap();
+    /// let items = [
+    ///     ListFormItem { signature: FourCC::make(b"ISFT"), contents: b"bwavfile\0".to_vec() }
+    /// ];
+    /// w.write_list(FourCC::make(b"INFO"), &items).unwrap();
+    /// w.audio_frame_writer().unwrap().end().unwrap();
+    ///
+    /// let mut r = WaveReader::new(&mut cursor).unwrap();
+    /// let read = r.list_form_items(FourCC::make(b"INFO")).unwrap();
+    /// assert_eq!(read.len(), 1);
+    /// assert_eq!(read[0].signature, FourCC::make(b"ISFT"));
+    /// assert_eq!(read[0].contents, b"bwavfile\0");
+    /// ```
+    pub fn write_list(&mut self, form: FourCC, items: &[ListFormItem]) -> Result<(), Error> {
+        let buf = compile_list_form(form, items)?;
+        self.write_chunk(LIST_SIG, &buf)
+    }
+
     /// Write Broadcast-Wave metadata to the file.
     /// 
     /// This function will write the metadata chunk immediately to the end of 
     /// the file; if you have already written and closed the audio data the 
-    /// bext chunk will be positioned after it.
+    /// bext chunk will be positioned after it. To replace the metadata of an
+    /// existing file, use `WaveEditor`.
+    ///
+    /// If `generate_originator_reference()` has been called and `bext` has
+    /// an empty `originator_reference`, a new USID is written in its place.
+    /// If `set_start_timecode()` has been called, its time reference is
+    /// written in place of `bext`'s.
     pub fn write_broadcast_metadata(&mut self, bext: &Bext) -> Result<(),Error> {
-        //FIXME Implement re-writing
+        let mut bext = bext.clone();
+        if let Some((_, time_reference)) = self.start_timecode {
+            bext.time_reference = time_reference;
+        }
+        if let Some(source) = self.usid_source.as_ref().filter(|_| bext.originator_reference.is_empty()) {
+            let now = SystemTime::now();
+            if bext.origination_date.is_empty() {
+                bext.set_origination_date(OriginationDate::from_system_time(now));
+            }
+            if bext.origination_time.is_empty() {
+                bext.set_origination_time(OriginationTime::from_system_time(now));
+            }
+            let time = bext.parsed_origination_time()
+                .unwrap_or_else(|| OriginationTime::from_system_time(now));
+            bext.originator_reference = source.generate_at(time)?.to_string();
+        }
         let mut c = Cursor::new(vec![0u8; 0]);
         c.write_bext(&bext)?;
         let buf = c.into_inner();
@@ -283,18 +485,338 @@ impl<W> WaveWriter<W> where W: Write + Seek {
         Ok(())
     }
 
+    /// Fill in empty originator references with EBU R99 USIDs.
+    ///
+    /// After this is called, `write_broadcast_metadata()` writes a newly
+    /// generated USID for this recorder if the `originator_reference` of the
+    /// `Bext` it's given is empty. The origination time of the USID is the
+    /// `Bext`'s `origination_time`, or the current time in UTC if it doesn't
+    /// have a valid one. An empty `origination_date` or `origination_time` is
+    /// filled in with the same current time. Returns an error if the codes
+    /// aren't valid USID fields.
+    ///
+    /// ```
+    /// use bwavfile::{WaveWriter, WaveReader, WaveFmt, Bext, Usid};
+    /// # use std::io::Cursor;
+    ///
+    /// let bext = Bext {
+    ///     description: String::from("Take 1"),
+    ///     originator: String::from("bwavfile"),
+    ///     originator_reference: String::from(""),
+    ///     origination_date: String::from("2021-06-01"),
+    ///     origination_time: String::from("10:20:30"),
+    ///     time_reference: 0,
+    ///     version: 0,
+    ///     umid: None,
+    ///     loudness_value: None,
+    ///     loudness_range: None,
+    ///     max_true_peak_level: None,
+    ///     max_momentary_loudness: None,
+    ///     max_short_term_loudness: None,
+    ///     coding_history: String::from(""),
+    /// };
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8;0]);
+    /// let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 24)).unwrap();
+    /// w.generate_originator_reference("NL", "NOS", "000000000042").unwrap();
+    /// w.write_broadcast_metadata(&bext).unwrap();
+    /// w.audio_frame_writer().unwrap().end().unwrap();
+    ///
+    /// let mut r = WaveReader::new(&mut cursor).unwrap();
+    /// let read = r.broadcast_extension().unwrap().unwrap();
+    /// let usid = Usid::parse(&read.originator_reference).unwrap();
+    /// assert_eq!(usid.serial_number(), "000000000042");
+    /// assert_eq!(usid.origination_time().to_string(), "10:20:30");
+    /// ```
+    pub fn generate_originator_reference(&mut self, country: &str, organization: &str,
+        serial_number: &str) -> Result<(), Error> {
+        self.usid_source = Some( UsidSource::new(country, organization, serial_number)? );
+        Ok(())
+    }
+
     /// Write iXML metadata
+    /// 
+    /// The bytes are written as they are given. With the `ixml` feature,
+    /// typed metadata can be written with `write_ixml_metadata()`, which
+    /// also records the start timecode.
     pub fn write_ixml(&mut self, ixml: &[u8]) -> Result<(),Error> {
-        //FIXME Implement re-writing
         self.write_chunk(IXML_SIG, &ixml)
     }
 
+    /// Write typed iXML metadata
+    ///
+    /// If `set_start_timecode()` has been called, the start timecode is
+    /// recorded in the iXML's `SPEED` and `BEXT` elements in place of
+    /// `ixml`'s.
+    #[cfg(feature = "ixml")]
+    pub fn write_ixml_metadata(&mut self, ixml: &IXml) -> Result<(),Error> {
+        let mut ixml = ixml.clone();
+        if let Some((timecode, time_reference)) = self.start_timecode {
+            ixml.set_start_timecode(&timecode, time_reference, self.format.sample_rate);
+        }
+        self.write_chunk(IXML_SIG, &ixml.to_bytes())
+    }
+
+    /// Set the start timecode of the file.
+    ///
+    /// The timecode is converted to a time reference in samples since
+    /// midnight at the file's sample rate, counted at `pull`, and
+    /// `write_broadcast_metadata()` and `write_ixml_metadata()` record it in the
+    /// metadata they write after this is called.
+    ///
+    /// ```
+    /// use bwavfile::{WaveWriter, WaveReader, WaveFmt, Bext, Timecode, FrameRate, Pull};
+    /// # use std::io::Cursor;
+    /// # let bext = Bext {
+    /// #     description: String::from(""), originator: String::from(""),
+    /// #     originator_reference: String::from(""), origination_date: String::from(""),
+    /// #     origination_time: String::from(""), time_reference: 0, version: 0, umid: None,
+    /// #     loudness_value: None, loudness_range: None, max_true_peak_level: None,
+    /// #     max_momentary_loudness: None, max_short_term_loudness: None,
+    /// #     coding_history: String::from(""),
+    /// # };
+    ///
+    /// let tc = Timecode::parse("14:02:30;12", FrameRate::Fps29_97DropFrame).unwrap();
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8;0]);
+    /// let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 24)).unwrap();
+    /// w.set_start_timecode(tc, Pull::Nominal);
+    /// w.write_broadcast_metadata(&bext).unwrap();
+    /// w.audio_frame_writer().unwrap().end().unwrap();
+    ///
+    /// let mut r = WaveReader::new(&mut cursor).unwrap();
+    /// assert_eq!(r.start_timecode(FrameRate::Fps29_97DropFrame).unwrap(), Some(tc));
+    /// ```
+    pub fn set_start_timecode(&mut self, timecode: Timecode, pull: Pull) {
+        let time_reference = timecode.to_samples_pulled(self.format.sample_rate, pull);
+        self.start_timecode = Some( (timecode, time_reference) );
+    }
+
     /// Write axml/ADM metadata
+    /// 
+    /// With the `adm` feature, typed metadata can be written by passing 
+    /// `Adm::to_bytes()`. Tracks are associated with the ADM by writing a 
+    /// `chna` chunk with `write_chna()`.
     pub fn write_axml(&mut self, axml: &[u8]) -> Result<(), Error> {
-        //FIXME Implement re-writing
         self.write_chunk(AXML_SIG, &axml)
     }
 
+    /// Write INFO metadata
+    ///
+    /// Writes a `LIST` chunk with form type `INFO`, with strings encoded 
+    /// with `info.code_page`. Unless the code page is UTF-8, a `CSET` chunk
+    /// recording it is written too, so readers can decode the strings.
+    ///
+    /// Returns an error, without writing anything, if the code page isn't
+    /// supported or a string can't be represented in it.
+    pub fn write_info(&mut self, info: &Info) -> Result<(), Error> {
+        let items = info.to_list_items()?;
+        if info.code_page != 0 && info.code_page != 65001 {
+            // wCodePage, wCountryCode, wLanguage, wDialect
+            let mut cset = vec![0u8; 8];
+            cset[0..2].copy_from_slice(&info.code_page.to_le_bytes());
+            self.write_chunk(CSET_SIG, &cset)?;
+        }
+        self.write_list(INFO_SIG, &items)
+    }
+
+    /// Write sampler metadata
+    ///
+    /// To tie sample loops to cues, write the cues with `write_cue_points()`
+    /// and create each loop with `SampleLoop::from_cue()`.
+    pub fn write_sampler(&mut self, sampler: &Sampler) -> Result<(), Error> {
+        self.write_chunk(SMPL_SIG, &sampler.to_bytes())
+    }
+
+    /// Write instrument metadata
+    pub fn write_instrument(&mut self, instrument: &Instrument) -> Result<(), Error> {
+        self.write_chunk(INST_SIG, &instrument.to_bytes())
+    }
+
+    /// Write radio traffic metadata
+    ///
+    /// Returns an error if a text field of `cart` is too long or isn't
+    /// ASCII, without writing anything.
+    pub fn write_cart(&mut self, cart: &Cart) -> Result<(), Error> {
+        cart.validate()?;
+        self.write_chunk(CART_SIG, &cart.to_bytes())
+    }
+
+    /// Write a peak envelope
+    ///
+    /// To copy the peak envelope of another file, read it with 
+    /// `WaveReader::peak_envelope()`. To compute a peak envelope for the
+    /// audio being written, use `generate_peak_envelope()` instead.
+    pub fn write_peak_envelope(&mut self, envelope: &PeakEnvelope) -> Result<(), Error> {
+        self.write_chunk(LEVL_SIG, &envelope.to_bytes())
+    }
+
+    /// Generate a peak envelope while audio is written
+    ///
+    /// When audio frames are written with the `AudioFrameWriter`, their 
+    /// peak values are computed for every `block_size` frames (256 is 
+    /// typical), and the peak envelope is written as a `levl` chunk when
+    /// `AudioFrameWriter::end()` is called. `points_per_value` is 1 to 
+    /// record only the absolute peak, or 2 to record positive and negative
+    /// peaks.
+    ///
+    /// ```
+    /// use bwavfile::{WaveWriter, WaveReader, WaveFmt, PeakFormat};
+    /// # use std::io::Cursor;
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8;0]);
+    /// let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 24)).unwrap();
+    /// w.generate_peak_envelope(PeakFormat::UnsignedShort, 1, 256);
+    ///
+    /// let mut frame_writer = w.audio_frame_writer().unwrap();
+    /// frame_writer.write_float_frames(&vec![0.5f32; 1000]).unwrap();
+    /// frame_writer.end().unwrap();
+    ///
+    /// let mut r = WaveReader::new(&mut cursor).unwrap();
+    /// let envelope = r.peak_envelope().unwrap().unwrap();
+    /// assert_eq!(envelope.frame_count, 4);
+    /// assert_eq!(envelope.peak(3, 0), Some(&[32768u16][..]));
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// This function will panic if `points_per_value` is not 1 or 2, or if
+    /// `block_size` is zero.
+    pub fn generate_peak_envelope(&mut self, format: PeakFormat, points_per_value: u32, block_size: u32) {
+        self.peak_envelope = Some( PeakEnvelopeGenerator::new(self.format.channel_count, 
+            format, points_per_value, block_size) );
+    }
+
+    /// Measure the loudness of audio while it's written
+    ///
+    /// When audio frames are written with the `AudioFrameWriter`, they are
+    /// measured by a `LoudnessMeter`. After the audio data is finished the
+    /// measurement can be recorded in a version 2 `bext` chunk, which will 
+    /// be written after the audio data.
+    ///
+    /// ```
+    /// use bwavfile::{WaveWriter, WaveReader, WaveFmt, Bext};
+    /// # use std::io::Cursor;
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8;0]);
+    /// let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 24)).unwrap();
+    /// w.measure_loudness();
+    ///
+    /// let mut frame_writer = w.audio_frame_writer().unwrap();
+    /// let tone : Vec<f32> = (0..48000 * 4).map(|i| 0.1 * (i as f32 / 10.0).sin()).collect();
+    /// frame_writer.write_float_frames(&tone).unwrap();
+    /// let mut w = frame_writer.end().unwrap();
+    ///
+    /// let mut bext = Bext {
+    ///     description: String::from("Tone"),
+    ///     originator: String::from(""),
+    ///     originator_reference: String::from(""),
+    ///     origination_date: String::from("2020-01-01"),
+    ///     origination_time: String::from("12:34:56"),
+    ///     time_reference: 0,
+    ///     version: 0,
+    ///     umid: None,
+    ///     loudness_value: None,
+    ///     loudness_range: None,
+    ///     max_true_peak_level: None,
+    ///     max_momentary_loudness: None,
+    ///     max_short_term_loudness: None,
+    ///     coding_history: String::from(""),
+    /// };
+    /// w.loudness_meter().unwrap().fill_bext(&mut bext);
+    /// w.write_broadcast_metadata(&bext).unwrap();
+    ///
+    /// let mut r = WaveReader::new(&mut cursor).unwrap();
+    /// let read = r.broadcast_extension().unwrap().unwrap();
+    /// assert_eq!(read.version, 2);
+    /// assert!(read.loudness_value.unwrap() < -20.0);
+    /// ```
+    pub fn measure_loudness(&mut self) {
+        self.loudness_meter = Some( LoudnessMeter::new(&self.format) );
+    }
+
+    /// The loudness of the audio written, if loudness is being measured.
+    pub fn loudness_meter(&self) -> Option<&LoudnessMeter> {
+        self.loudness_meter.as_ref()
+    }
+
+    /// Write Dolby metadata
+    pub fn write_dolby_metadata(&mut self, dbmd: &DolbyMetadata) -> Result<(), Error> {
+        self.write_chunk(DBMD_SIG, &dbmd.to_bytes())
+    }
+
+    /// Write cue points and regions
+    ///
+    /// Writes a `cue ` chunk with a cue point for each of `cues`, and an 
+    /// `adtl` `LIST` with the labels, notes and region lengths of the cues,
+    /// if any are present. Like the other metadata writers these chunks are 
+    /// written immediately to the end of the file, so cues can be written 
+    /// before or after the audio data.
+    ///
+    /// If any cue is positioned past 2^32 frames, an RF64 `r64m` marker chunk
+    /// with the 64-bit position of every cue is also written.
+    ///
+    /// ```
+    /// use bwavfile::{WaveWriter, WaveReader, WaveFmt, Cue};
+    /// # use std::io::Cursor;
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8;0]);
+    /// let w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 24)).unwrap();
+    /// let mut frame_writer = w.audio_frame_writer().unwrap();
+    /// frame_writer.write_integer_frames(&[0i32; 16]).unwrap();
+    /// let mut w = frame_writer.end().unwrap();
+    ///
+    /// let cues = [
+    ///     Cue::marker(1, 4, "Marker"),
+    ///     Cue::region(2, 8, 4, "Region"),
+    /// ];
+    /// w.write_cue_points(&cues).unwrap();
+    ///
+    /// let mut r = WaveReader::new(&mut cursor).unwrap();
+    /// assert_eq!(r.cue_points().unwrap(), cues);
+    /// ```
+    pub fn write_cue_points(&mut self, cues: &[Cue]) -> Result<(), Error> {
+        let (cue_chunk, adtl) = Cue::compile_to_chunks(cues);
+        self.write_chunk(CUE__SIG, &cue_chunk)?;
+        if !adtl.is_empty() {
+            self.write_list(ADTL_SIG, &adtl)?;
+        }
+        if Cue::requires_rf64_markers(cues) {
+            self.write_chunk(R64M_SIG, &Cue::compile_to_rf64_markers(cues))?;
+        }
+        Ok(())
+    }
+
+    /// Write a `chna` ADM channel allocation chunk
+    /// 
+    /// The chunk will relate each channel in `channels` to its ADM audio IDs,
+    /// the `chna` chunk is used in tandem with ADM metadata written with 
+    /// `write_axml()`.
+    /// 
+    /// ```
+    /// use bwavfile::{WaveWriter, WaveReader, WaveFmt, ADMAudioID};
+    /// # use std::io::Cursor;
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8;0]);
+    /// let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_stereo(48000, 24)).unwrap();
+    /// let mut channels = w.format.channels();
+    /// channels[0].adm_track_audio_ids.push(ADMAudioID::new("ATU_00000001", "AT_00010001_01", "AP_00010002"));
+    /// channels[1].adm_track_audio_ids.push(ADMAudioID::new("ATU_00000002", "AT_00010002_01", "AP_00010002"));
+    /// w.write_chna(&channels).unwrap();
+    /// w.audio_frame_writer().unwrap().end().unwrap();
+    ///
+    /// let mut r = WaveReader::new(&mut cursor).unwrap();
+    /// assert_eq!(r.channels().unwrap(), channels);
+    /// ```
+    pub fn write_chna(&mut self, channels: &[ChannelDescriptor]) -> Result<(), Error> {
+        let ids : Vec<(u16, ADMAudioID)> = channels.iter()
+            .flat_map(|c| c.adm_track_audio_ids.iter().map(move |id| (c.index + 1, *id)))
+            .collect();
+        let mut c = Cursor::new(vec![0u8; 0]);
+        c.write_chna(&ids)?;
+        self.write_chunk(CHNA_SIG, &c.into_inner())
+    }
+
     /// Write a `JUNK` filler chunk
     pub fn write_junk(&mut self, length: u32) -> Result<(), Error> {
         let filler = vec![0u8; length as usize];
@@ -325,6 +847,24 @@ impl<W> WaveWriter<W> where W: Write + Seek {
         WaveChunkWriter::begin(self, ident)
     }
 
+    /// Record the count of frames in the `fact` chunk, if present
+    /// 
+    /// In an RF64 file the `fact` field is set to 0xFFFFFFFF and the 
+    /// frame count is recorded in the `ds64` record.
+    fn update_fact_frame_count(&mut self, frame_count: u64) -> Result<(), std::io::Error> {
+        if let Some(pos) = self.fact_content_pos {
+            self.inner.seek(SeekFrom::Start(pos))?;
+            if self.is_rf64 {
+                self.inner.write_u32::<LittleEndian>(0xFFFF_FFFF)?;
+                self.inner.seek(SeekFrom::Start(8 + 4 + 8 + 8 + 8))?;
+                self.inner.write_u64::<LittleEndian>(frame_count)?;
+            } else {
+                self.inner.write_u32::<LittleEndian>(frame_count as u32)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Upgrade this file to RF64
     fn promote_to_rf64(&mut self) -> Result<(), std::io::Error> {
         if !self.is_rf64 {
@@ -468,6 +1008,189 @@ fn test_write_bext() {
     frame_writer.end().unwrap();
 }
 
+#[test]
+fn test_write_bext_keeps_originator_reference() {
+    use std::io::Cursor;
+    use super::wavereader::WaveReader;
+
+    let mut cursor = Cursor::new(vec![0u8;0]);
+    let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 24)).unwrap();
+    assert!(matches!(w.generate_originator_reference("US", "acme", "000000000001"),
+        Err(Error::UsidFieldInvalid { field: "organization" })));
+    w.generate_originator_reference("US", "ACM", "000000000001").unwrap();
+
+    let bext = Bext {
+        description: String::from(""),
+        originator: String::from(""),
+        originator_reference: String::from("Existing reference"),
+        origination_date: String::from(""),
+        origination_time: String::from(""),
+        time_reference: 0,
+        version: 0,
+        umid: None,
+        loudness_value: None,
+        loudness_range: None,
+        max_true_peak_level: None,
+        max_momentary_loudness: None,
+        max_short_term_loudness: None,
+        coding_history: String::from(""),
+    };
+    w.write_broadcast_metadata(&bext).unwrap();
+    w.audio_frame_writer().unwrap().end().unwrap();
+
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    assert_eq!(r.broadcast_extension().unwrap().unwrap().originator_reference, "Existing reference");
+}
+
+#[test]
+fn test_write_bext_fills_origination_from_usid() {
+    use std::io::Cursor;
+    use super::wavereader::WaveReader;
+    use super::usid::Usid;
+
+    let mut cursor = Cursor::new(vec![0u8;0]);
+    let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 24)).unwrap();
+    w.generate_originator_reference("US", "ACM", "000000000001").unwrap();
+    let bext = Bext {
+        description: String::from(""),
+        originator: String::from(""),
+        originator_reference: String::from(""),
+        origination_date: String::from(""),
+        origination_time: String::from(""),
+        time_reference: 0,
+        version: 0,
+        umid: None,
+        loudness_value: None,
+        loudness_range: None,
+        max_true_peak_level: None,
+        max_momentary_loudness: None,
+        max_short_term_loudness: None,
+        coding_history: String::from(""),
+    };
+    w.write_broadcast_metadata(&bext).unwrap();
+    w.audio_frame_writer().unwrap().end().unwrap();
+
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    let bext = r.broadcast_extension().unwrap().unwrap();
+    let usid = Usid::parse(&bext.originator_reference).unwrap();
+    assert!(bext.parsed_origination_date().is_some());
+    assert_eq!(bext.parsed_origination_time(), Some(usid.origination_time()));
+}
+
+#[test]
+fn test_write_chunks_after_data() {
+    use super::wavereader::WaveReader;
+
+    let mut cursor = Cursor::new(vec![0u8;0]);
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let mut w = WaveWriter::new(&mut cursor, format).unwrap();
+    w.write_chunk(FourCC::make(b"abcd"), &[1, 2, 3]).unwrap();
+
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.write_integer_frames(&[0i32, 1i32, 2i32, 3i32]).unwrap();
+    let mut w = frame_writer.end().unwrap();
+
+    let items = [ ListFormItem { signature: FourCC::make(b"ICMT"), contents: b"note".to_vec() } ];
+    w.write_list(FourCC::make(b"INFO"), &items).unwrap();
+    w.write_chunk(FourCC::make(b"wxyz"), &[4, 5]).unwrap();
+
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    let signatures : Vec<FourCC> = r.chunks().iter().map(|c| c.signature).collect();
+    assert_eq!(signatures, [JUNK_SIG, FMT__SIG, FourCC::make(b"abcd"), ELM1_SIG, DATA_SIG, 
+        LIST_SIG, FourCC::make(b"wxyz")]);
+    assert_eq!(r.chunks()[5].form, Some(FourCC::make(b"INFO")));
+    assert_eq!(r.frame_length().unwrap(), 2);
+
+    let mut buf = vec![];
+    r.read_chunk(FourCC::make(b"abcd"), 0, &mut buf).unwrap();
+    assert_eq!(buf, [1, 2, 3]);
+    r.read_chunk(FourCC::make(b"wxyz"), 0, &mut buf).unwrap();
+    assert_eq!(buf, [4, 5]);
+    assert_eq!(r.list_form_items(FourCC::make(b"INFO")).unwrap(), items);
+}
+
+#[test]
+fn test_write_float_audio() {
+    use super::wavereader::WaveReader;
+    use super::parser::Parser;
+    use byteorder::ReadBytesExt;
+
+    let mut cursor = Cursor::new(vec![0u8;0]);
+    let format = WaveFmt::new_float_stereo(48000, 32);
+    assert_eq!(format.tag, 0x0003);
+    assert_eq!(format.block_alignment, 8);
+
+    let w = WaveWriter::new(&mut cursor, format).unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+
+    assert_eq!(frame_writer.write_float_frames(&[0.5f32, -0.25f32, 1.0f32, -1.0f32]).unwrap(), 2);
+    assert_eq!(frame_writer.write_double_frames(&[0.125f64, 0.0f64]).unwrap(), 1);
+    frame_writer.end().unwrap();
+
+    let chunks = Parser::make(&mut cursor).unwrap().into_chunk_list().unwrap();
+    let fact = chunks.iter().find(|c| c.signature == FACT_SIG).unwrap();
+    assert_eq!(fact.length, 4);
+    cursor.seek(SeekFrom::Start(fact.start)).unwrap();
+    assert_eq!(cursor.read_u32::<LittleEndian>().unwrap(), 3);
+
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    assert_eq!(r.format().unwrap().common_format(), CommonFormat::IeeeFloatPCM);
+    assert_eq!(r.frame_length().unwrap(), 3);
+
+    let mut reader = r.audio_frame_reader().unwrap();
+    let mut buffer = [0f32; 2];
+    reader.read_float_frame(&mut buffer).unwrap();
+    assert_eq!(buffer, [0.5f32, -0.25f32]);
+    reader.read_float_frame(&mut buffer).unwrap();
+    assert_eq!(buffer, [1.0f32, -1.0f32]);
+    reader.read_float_frame(&mut buffer).unwrap();
+    assert_eq!(buffer, [0.125f32, 0.0f32]);
+}
+
+#[test]
+fn test_write_sample_conversion() {
+    use super::wavereader::WaveReader;
+    use super::sample::I24;
+
+    let mut cursor = Cursor::new(vec![0u8;0]);
+    let format = WaveFmt::new_pcm_stereo(48000, 24);
+    let w = WaveWriter::new(&mut cursor, format).unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    assert_eq!(frame_writer.write_frames(&[0x1234i16, -0x1234i16]).unwrap(), 1);
+    assert_eq!(frame_writer.write_integer_frames(&[0x12_3456i32, -1i32]).unwrap(), 1);
+    frame_writer.end().unwrap();
+
+    let mut reader = WaveReader::new(&mut cursor).unwrap().audio_frame_reader().unwrap();
+    let mut buffer = [I24::default(); 4];
+    assert_eq!(reader.read_frames(&mut buffer).unwrap(), 2);
+    assert_eq!(buffer, [I24::new(0x12_3400), I24::new(-0x12_3400), I24::new(0x12_3456), I24::new(-1)]);
+
+    reader.locate(0).unwrap();
+    let mut buffer = [0f64; 4];
+    assert_eq!(reader.read_frames(&mut buffer).unwrap(), 2);
+    assert_eq!(buffer[0], 0x1234 as f64 / 0x8000 as f64);
+}
+
+#[test]
+fn test_write_float_to_integer_clips() {
+    use super::wavereader::WaveReader;
+
+    let mut cursor = Cursor::new(vec![0u8;0]);
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let w = WaveWriter::new(&mut cursor, format).unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.write_float_frames(&[0.5f32, 2.0f32, -2.0f32]).unwrap();
+    frame_writer.end().unwrap();
+
+    let mut reader = WaveReader::new(&mut cursor).unwrap().audio_frame_reader().unwrap();
+    let mut buffer = [0i32; 1];
+    reader.read_integer_frame(&mut buffer).unwrap();
+    assert_eq!(buffer[0], 0x4000);
+    reader.read_integer_frame(&mut buffer).unwrap();
+    assert_eq!(buffer[0], 0x7FFF);
+    reader.read_integer_frame(&mut buffer).unwrap();
+    assert_eq!(buffer[0], -0x8000);
+}
 
 // NOTE! This test of RF64 writing takes several minutes to complete.
 #[test]
@@ -3,6 +3,20 @@ extern crate bwavfile;
 use bwavfile::WaveReader;
 use bwavfile::Error;
 use bwavfile::{ ChannelMask}; 
+use bwavfile::{WaveWriter, WaveFmt};
+
+use std::io::Cursor;
+
+/// Write a file with no audio and the metadata written by `write`, and open
+/// it for reading.
+fn write_metadata<F>(format: WaveFmt, write: F) -> WaveReader<Cursor<Vec<u8>>>
+    where F: FnOnce(&mut WaveWriter<&mut Cursor<Vec<u8>>>) {
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let mut w = WaveWriter::new(&mut cursor, format).unwrap();
+    write(&mut w);
+    w.audio_frame_writer().unwrap().end().unwrap();
+    WaveReader::new(cursor).unwrap()
+}
 
 #[test]
 fn test_open() {
@@ -175,4 +189,686 @@ fn test_frame_reader_consumes_reader() {
     }
 
     let _result = from_wav_filename("tests/media/pt_24bit_stereo.wav").unwrap();
-}
\ No newline at end of file
+}
+#[test]
+fn test_read_float() {
+    let path = "tests/media/ff_float.wav";
+
+    let mut w = WaveReader::open(path).expect("Failure opening test file");
+    let channel_count = w.format().unwrap().channel_count as usize;
+    let mut buffer = vec![0f32; channel_count];
+
+    let mut reader = w.audio_frame_reader().unwrap();
+
+    assert_eq!(reader.read_float_frame(&mut buffer).unwrap(), 1);
+    assert_eq!(buffer[0], 0.09032739_f32);
+    assert_eq!(reader.read_float_frame(&mut buffer).unwrap(), 1);
+    assert_eq!(buffer[0], 0.07036128_f32);
+}
+
+#[test]
+fn test_read_integer_as_float() {
+    let path = "tests/media/audacity_16bit.wav";
+
+    let mut w = WaveReader::open(path).expect("Failure opening test file");
+    let mut buffer = vec![0f32; 1];
+
+    let mut reader = w.audio_frame_reader().unwrap();
+
+    assert_eq!(reader.read_float_frame(&mut buffer).unwrap(), 1);
+    assert_eq!(buffer[0], -2823_f32 / 32768_f32);
+    assert_eq!(reader.read_float_frame(&mut buffer).unwrap(), 1);
+    assert_eq!(buffer[0], 2012_f32 / 32768_f32);
+}
+
+#[test]
+fn test_read_frames_block() {
+    let path = "tests/media/ff_pink.wav";
+
+    let mut w = WaveReader::open(path).expect("Failure opening test file");
+    let frame_length = w.frame_length().unwrap();
+    let mut buffer = w.format().unwrap().create_frame_buffer(4);
+
+    let mut reader = w.audio_frame_reader().unwrap();
+
+    assert_eq!(reader.read_integer_frames(&mut buffer).unwrap(), 4);
+    assert_eq!(buffer[0], 332702_i32);
+    assert_eq!(buffer[1], 3258791_i32);
+    assert_eq!(buffer[2], -258742_i32);
+    assert_eq!(buffer[3], 0x0D7EF9_i32);
+
+    assert_eq!(reader.locate(frame_length - 3).unwrap(), frame_length - 3);
+    assert_eq!(reader.read_integer_frames(&mut buffer).unwrap(), 3);
+    assert_eq!(reader.read_integer_frames(&mut buffer).unwrap(), 0);
+}
+
+#[test]
+fn test_read_float_frames_block() {
+    let path = "tests/media/ff_float.wav";
+
+    let mut w = WaveReader::open(path).expect("Failure opening test file");
+    let frame_length = w.frame_length().unwrap();
+    let mut buffer = vec![0f32; 256];
+
+    let mut reader = w.audio_frame_reader().unwrap();
+
+    let mut total_read = 0u64;
+    loop {
+        match reader.read_float_frames(&mut buffer).unwrap() {
+            0 => break,
+            n => total_read += n
+        }
+    }
+
+    assert_eq!(total_read, frame_length);
+}
+
+#[test]
+fn test_frame_iterators() {
+    let path = "tests/media/ff_pink.wav";
+
+    let mut w = WaveReader::open(path).expect("Failure opening test file");
+    let frame_length = w.frame_length().unwrap();
+    let mut reader = w.audio_frame_reader().unwrap();
+
+    let frames : Vec<Vec<i32>> = reader.frames().collect::<Result<_,_>>().unwrap();
+    assert_eq!(frames.len() as u64, frame_length);
+    assert_eq!(frames[0], [332702_i32 << 8, 3258791_i32 << 8]);
+    assert!(reader.frames::<i32>().next().is_none());
+
+    reader.locate(0).unwrap();
+    let blocks : Vec<Vec<f32>> = reader.blocks(1000).collect::<Result<_,_>>().unwrap();
+    assert_eq!(blocks.len() as u64, frame_length.div_ceil(1000));
+    assert_eq!(blocks.iter().map(|b| b.len() as u64).sum::<u64>(), frame_length * 2);
+
+    reader.locate(100).unwrap();
+    let right : Vec<i32> = reader.channel_samples(1).collect::<Result<_,_>>().unwrap();
+    assert_eq!(right.len() as u64, frame_length - 100);
+    assert_eq!(right[0], -698901_i32 << 8);
+}
+
+#[test]
+fn test_read_channels() {
+    let path = "tests/media/ff_pink.wav";
+
+    let mut w = WaveReader::open(path).expect("Failure opening test file");
+    let mut reader = w.audio_frame_reader().unwrap();
+
+    let mut buffer = vec![0i32; 4];
+    assert_eq!(reader.read_channels(&[1, 0], &mut buffer).unwrap(), 2);
+    assert_eq!(buffer, [3258791_i32 << 8, 332702_i32 << 8, 0x0D7EF9_i32 << 8, -258742_i32 << 8]);
+
+    let mut buffer = vec![0i32; 1];
+    assert_eq!(reader.read_channels(&[1], &mut buffer).unwrap(), 1);
+    reader.locate(2).unwrap();
+    let mut frame = vec![0i32; 2];
+    reader.read_frames(&mut frame).unwrap();
+    assert_eq!(buffer[0], frame[1]);
+}
+
+#[test]
+fn test_deinterleave_into() {
+    use bwavfile::{WaveWriter, WaveFmt};
+    use std::io::Cursor;
+
+    let path = "tests/media/ff_pink.wav";
+
+    let mut w = WaveReader::open(path).expect("Failure opening test file");
+    let format = w.format().unwrap();
+    let frame_length = w.frame_length().unwrap();
+    let mut reader = w.audio_frame_reader().unwrap();
+
+    let mut left = Cursor::new(vec![0u8; 0]);
+    let mut right = Cursor::new(vec![0u8; 0]);
+    let mono = WaveFmt::new_pcm_mono(format.sample_rate, format.bits_per_sample);
+    let mut writers = vec![
+        WaveWriter::new(&mut left, mono).unwrap().audio_frame_writer().unwrap(),
+        WaveWriter::new(&mut right, mono).unwrap().audio_frame_writer().unwrap()
+    ];
+
+    assert_eq!(reader.deinterleave_into(&mut writers).unwrap(), frame_length);
+    for writer in writers {
+        writer.end().unwrap();
+    }
+
+    let mut right_reader = WaveReader::new(&mut right).unwrap();
+    assert_eq!(right_reader.frame_length().unwrap(), frame_length);
+    let mut right_frames = right_reader.audio_frame_reader().unwrap();
+    let mut buffer = vec![0i32; 2];
+    right_frames.read_integer_frames(&mut buffer).unwrap();
+    assert_eq!(buffer, [3258791_i32, 0x0D7EF9_i32]);
+}
+
+#[test]
+fn test_chunk_list() {
+    use bwavfile::FourCC;
+
+    let w = WaveReader::open("tests/media/audacity_16bit.wav").unwrap();
+    let signatures : Vec<String> = w.chunks().iter().map(|c| c.signature.into()).collect();
+    assert_eq!(signatures, ["fmt ", "data", "LIST", "id3 "]);
+
+    let list = w.chunks()[2];
+    assert_eq!(list.start, 8888);
+    assert_eq!(list.length, 70);
+    assert_eq!(list.form, Some(FourCC::make(b"INFO")));
+    assert_eq!(w.chunks()[0].form, None);
+}
+
+#[test]
+fn test_read_raw_chunks() {
+    use std::io::{Read, Seek, SeekFrom};
+    use bwavfile::FourCC;
+
+    let mut w = WaveReader::open("tests/media/pt_24bit.wav").unwrap();
+
+    let mut buf = vec![];
+    assert_eq!(w.read_chunk(FourCC::make(b"regn"), 0, &mut buf).unwrap(), 92);
+
+    let mut reader = w.chunk_reader(FourCC::make(b"regn"), 0).unwrap();
+    assert_eq!(reader.length(), 92);
+    let mut streamed = vec![];
+    reader.read_to_end(&mut streamed).unwrap();
+    assert_eq!(streamed, buf);
+
+    reader.seek(SeekFrom::Start(90)).unwrap();
+    let mut tail = vec![];
+    reader.read_to_end(&mut tail).unwrap();
+    assert_eq!(tail, &buf[90..]);
+
+    match w.chunk_reader(FourCC::make(b"regn"), 1) {
+        Err(Error::ChunkMissing { signature }) => assert_eq!(signature, FourCC::make(b"regn")),
+        _ => panic!("Expected a missing chunk error")
+    }
+
+    let mut w = WaveReader::open("tests/media/izotope_test.wav").unwrap();
+    let mut adtl = vec![];
+    assert_eq!(w.read_list(FourCC::make(b"adtl"), &mut adtl).unwrap(), 190);
+    assert_eq!(&adtl[0..4], b"adtl");
+}
+
+#[test]
+fn test_absent_metadata() {
+    let mut r = WaveReader::open("tests/media/ff_minimal.wav").unwrap();
+    assert!(r.broadcast_extension().unwrap().is_none());
+    #[cfg(feature = "ixml")]
+    assert!(r.ixml().unwrap().is_none());
+    #[cfg(feature = "ixml")]
+    assert!(r.timecode_rate().unwrap().is_none());
+    #[cfg(feature = "adm")]
+    assert!(r.adm().unwrap().is_none());
+    assert!(r.dolby_metadata().unwrap().is_none());
+    assert!(r.sampler().unwrap().is_none());
+    assert!(r.instrument().unwrap().is_none());
+    assert!(r.cart().unwrap().is_none());
+    assert!(r.peak_envelope().unwrap().is_none());
+    assert!(r.cue_points().unwrap().is_empty());
+}
+
+#[test]
+fn test_cue_points_round_trip() {
+    let mut source = WaveReader::open("tests/media/izotope_test.wav").unwrap();
+    let cues = source.cue_points().unwrap();
+    let format = source.format().unwrap();
+
+    let mut r = write_metadata(format, |w| w.write_cue_points(&cues).unwrap());
+    assert_eq!(r.cue_points().unwrap(), cues);
+}
+
+#[test]
+fn test_rf64_markers_round_trip() {
+    use bwavfile::{WaveWriter, WaveFmt, Cue};
+    use std::io::Cursor;
+
+    let cues = vec![
+        Cue::marker(1, 48000, "Start"),
+        Cue::region(2, 0x1_0000_0000 + 48000, 96000, "Late Region"),
+    ];
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 24)).unwrap();
+    let mut w = w.audio_frame_writer().unwrap().end().unwrap();
+    w.write_cue_points(&cues).unwrap();
+
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    let signatures : Vec<String> = r.chunks().iter().map(|c| c.signature.into()).collect();
+    assert!(signatures.contains(&String::from("r64m")));
+    assert_eq!(r.cue_points().unwrap(), cues);
+}
+
+#[cfg(feature = "ixml")]
+#[test]
+fn test_ixml_round_trip() {
+    use bwavfile::{IXml, IXmlTrack, IXmlSpeed};
+
+    let mut ixml = IXml::default();
+    ixml.project = Some(String::from("Project"));
+    ixml.scene = Some(String::from("1"));
+    ixml.take = Some(String::from("2"));
+    let mut speed = IXmlSpeed::default();
+    speed.timecode_rate = Some(String::from("25/1"));
+    speed.timecode_flag = Some(String::from("NDF"));
+    speed.timestamp_samples_since_midnight = Some(48000 * 3600);
+    ixml.speed = Some(speed);
+    ixml.tracks = vec![IXmlTrack::new(1, 1, "Left"), IXmlTrack::new(2, 2, "Right")];
+
+    let mut r = write_metadata(WaveFmt::new_pcm_stereo(48000, 24),
+        |w| w.write_ixml(&ixml.to_bytes()).unwrap());
+    assert_eq!(r.ixml().unwrap(), Some(ixml));
+}
+
+#[cfg(feature = "adm")]
+#[test]
+fn test_adm_round_trip() {
+    use bwavfile::{ADMAudioID, Adm, AudioObject, AudioTrackUid};
+
+    let mut adm = Adm::default();
+    let mut object = AudioObject::new("AO_1001", "Stereo");
+    object.pack_format_refs.push(String::from("AP_00010002"));
+    for (i, track_format) in ["AT_00010001_01", "AT_00010002_01"].iter().enumerate() {
+        let uid = format!("ATU_0000000{}", i + 1);
+        let mut track_uid = AudioTrackUid::new(&uid);
+        track_uid.track_format_ref = Some(track_format.to_string());
+        track_uid.pack_format_ref = Some(String::from("AP_00010002"));
+        object.track_uid_refs.push(uid);
+        adm.track_uids.push(track_uid);
+    }
+    adm.objects.push(object);
+
+    let format = WaveFmt::new_pcm_stereo(48000, 24);
+    let mut channels = format.channels();
+    channels[0].adm_track_audio_ids.push(ADMAudioID::new("ATU_00000001", "AT_00010001_01", "AP_00010002"));
+    channels[1].adm_track_audio_ids.push(ADMAudioID::new("ATU_00000002", "AT_00010002_01", "AP_00010002"));
+
+    let mut r = write_metadata(format, |w| {
+        w.write_chna(&channels).unwrap();
+        w.write_axml(&adm.to_bytes()).unwrap();
+    });
+    let read_adm = r.adm().unwrap().unwrap();
+    assert_eq!(read_adm, adm);
+
+    let read_channels = r.channels().unwrap();
+    assert_eq!(read_channels, channels);
+    let track_uid = read_adm.track_uid(&read_channels[1].adm_track_audio_ids[0].track_uid()).unwrap();
+    assert_eq!(track_uid.track_format_ref.as_deref(), Some("AT_00010002_01"));
+}
+
+#[test]
+fn test_dolby_metadata_round_trip() {
+    use bwavfile::{DolbyMetadata, DolbyMetadataSegment, DolbyAtmosMetadata};
+
+    let mut atmos = DolbyAtmosMetadata::default();
+    atmos.content_creation_tool = String::from("Renderer");
+    atmos.content_creation_tool_version = (3, 1, 0);
+    atmos.warp_mode = 4;
+
+    let dbmd = DolbyMetadata {
+        version: 0x01000006,
+        segments: vec![
+            DolbyMetadataSegment::DolbyAtmos(atmos),
+            DolbyMetadataSegment::Other { id: 10, payload: vec![0u8; 11] }
+        ]
+    };
+
+    let mut r = write_metadata(WaveFmt::new_pcm_stereo(48000, 24),
+        |w| w.write_dolby_metadata(&dbmd).unwrap());
+    let read = r.dolby_metadata().unwrap().unwrap();
+    assert_eq!(read, dbmd);
+    assert_eq!(read.dolby_atmos().unwrap().content_creation_tool, "Renderer");
+}
+
+#[test]
+fn test_read_info() {
+    let mut r = WaveReader::open("tests/media/audacity_16bit.wav").unwrap();
+    let info = r.info().unwrap().unwrap();
+    assert_eq!(info.title.as_deref(), Some("Pink Noise 16bit"));
+    assert_eq!(info.product.as_deref(), Some("Test Media"));
+    assert_eq!(info.artist.as_deref(), Some("Jamie Hardt"));
+
+    let mut r = WaveReader::open("tests/media/ff_pink.wav").unwrap();
+    assert_eq!(r.info().unwrap().unwrap().software.as_deref(), Some("Lavf58.45.100"));
+
+    let mut r = WaveReader::open("tests/media/pt_24bit.wav").unwrap();
+    assert_eq!(r.info().unwrap(), None);
+}
+
+#[test]
+fn test_info_round_trip() {
+    use bwavfile::{Info, FourCC};
+
+    let mut info = Info::default();
+    info.code_page = 1252;
+    info.title = Some(String::from("Señal de prueba"));
+    info.comment = Some(String::from("Odd"));
+    info.set(FourCC::make(b"IMED"), "DAT");
+
+    let mut r = write_metadata(WaveFmt::new_pcm_mono(48000, 16), |w| w.write_info(&info).unwrap());
+    let read = r.info_with_code_page(Some(1252)).unwrap().unwrap();
+    assert_eq!(read, info);
+
+    let detected = r.info().unwrap().unwrap();
+    assert_eq!(detected.title, info.title);
+    assert_eq!(detected.get(FourCC::make(b"IMED")), Some("DAT"));
+}
+
+#[test]
+fn test_info_code_page_round_trip() {
+    use bwavfile::Info;
+
+    for (code_page, title) in [(1251u16, "Пробная запись"), (932, "テスト録音")].iter() {
+        let mut info = Info::default();
+        info.code_page = *code_page;
+        info.title = Some(title.to_string());
+
+        let mut r = write_metadata(WaveFmt::new_pcm_mono(48000, 16), |w| w.write_info(&info).unwrap());
+        assert_eq!(r.info().unwrap().unwrap(), info);
+    }
+
+    let mut info = Info::default();
+    info.code_page = 1252;
+    info.title = Some(String::from("テスト録音"));
+    let mut w = WaveWriter::new(Cursor::new(vec![0u8; 0]), WaveFmt::new_pcm_mono(48000, 16)).unwrap();
+    assert!(matches!(w.write_info(&info),
+        Err(Error::TextNotEncodable { code_page: 1252, .. })));
+    info.code_page = 1;
+    assert!(matches!(w.write_info(&info),
+        Err(Error::CodePageNotSupported { code_page: 1 })));
+}
+
+#[test]
+fn test_sampler_round_trip() {
+    use bwavfile::{Cue, Sampler, SampleLoop, LoopType, Instrument};
+
+    let cues = vec![Cue::marker(1, 0, "Attack"), Cue::region(2, 2400, 4800, "Sustain")];
+
+    let mut sampler = Sampler::default();
+    sampler.sample_period = 1_000_000_000 / 48000;
+    sampler.midi_unity_note = 64;
+    sampler.loops.push(SampleLoop::from_cue(&cues[1], LoopType::Alternating).unwrap());
+    assert_eq!(SampleLoop::from_cue(&cues[0], LoopType::Forward), None);
+
+    let instrument = Instrument { unshifted_note: 64, fine_tune: 3, gain: -3, low_note: 60,
+        high_note: 67, low_velocity: 1, high_velocity: 127 };
+
+    let mut r = write_metadata(WaveFmt::new_pcm_mono(48000, 24), |w| {
+        w.write_cue_points(&cues).unwrap();
+        w.write_sampler(&sampler).unwrap();
+        w.write_instrument(&instrument).unwrap();
+    });
+    let read = r.sampler().unwrap().unwrap();
+    assert_eq!(read, sampler);
+    assert_eq!(read.loops[0].end, 2400 + 4800 - 1);
+
+    let read_cues = r.cue_points().unwrap();
+    assert_eq!(read.loops[0].cue(&read_cues).unwrap().label(), Some("Sustain"));
+
+    assert_eq!(r.instrument().unwrap(), Some(instrument));
+}
+
+#[test]
+fn test_peak_envelope_generated_by_writer() {
+    use bwavfile::PeakFormat;
+
+    let frames : Vec<i32> = (0..1000).flat_map(|i| vec![i * 8, -i * 8]).collect();
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_stereo(48000, 16)).unwrap();
+    w.generate_peak_envelope(PeakFormat::UnsignedChar, 2, 256);
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.write_integer_frames(&frames[..600]).unwrap();
+    frame_writer.write_integer_frames(&frames[600..]).unwrap();
+    frame_writer.end().unwrap();
+
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    assert_eq!(r.frame_length().unwrap(), 1000);
+
+    let envelope = r.peak_envelope().unwrap().unwrap();
+    assert_eq!(envelope.channel_count, 2);
+    assert_eq!(envelope.block_size, 256);
+    assert_eq!(envelope.frame_count, 4);
+    assert_eq!(envelope.position_of_peak, 999);
+    assert_eq!(envelope.peak(0, 0), Some(&[16u16, 0][..]));
+    assert_eq!(envelope.peak(0, 1), Some(&[0u16, 16][..]));
+    assert_eq!(envelope.peak(3, 0), Some(&[62u16, 0][..]));
+    assert_eq!(envelope.timestamp.len(), 23);
+
+    let mut copy = write_metadata(WaveFmt::new_pcm_stereo(48000, 16),
+        |w| w.write_peak_envelope(&envelope).unwrap());
+    assert_eq!(copy.peak_envelope().unwrap(), Some(envelope));
+}
+
+#[test]
+fn test_loudness_reader_and_writer_agree() {
+    use bwavfile::{WaveWriter, WaveFmt, LoudnessMeter};
+    use std::io::Cursor;
+
+    // five seconds of a tone that changes level each second
+    let frames : Vec<i32> = (0..48000 * 5)
+        .map(|i| {
+            let amplitude = 400_000.0 * (1 + i / 48000) as f64;
+            (amplitude * (i as f64 * 0.0731).sin()) as i32
+        })
+        .flat_map(|s| vec![s, s / 2])
+        .collect();
+
+    let format = WaveFmt::new_pcm_stereo(48000, 24);
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let mut w = WaveWriter::new(&mut cursor, format).unwrap();
+    w.measure_loudness();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    for chunk in frames.chunks(2 * 1000) {
+        frame_writer.write_integer_frames(chunk).unwrap();
+    }
+    let w = frame_writer.end().unwrap();
+    let write_meter = w.loudness_meter().unwrap().clone();
+
+    let r = WaveReader::new(&mut cursor).unwrap();
+    let read_meter = LoudnessMeter::measure(&mut r.audio_frame_reader().unwrap()).unwrap();
+
+    assert!(read_meter.loudness_range().unwrap() > 3.0);
+    assert!(read_meter.max_true_peak_level().unwrap() > read_meter.max_short_term_loudness().unwrap());
+    assert_eq!(write_meter.integrated_loudness(), read_meter.integrated_loudness());
+    assert_eq!(write_meter.loudness_range(), read_meter.loudness_range());
+    assert_eq!(write_meter.max_momentary_loudness(), read_meter.max_momentary_loudness());
+    assert_eq!(write_meter.max_true_peak_level(), read_meter.max_true_peak_level());
+}
+
+#[test]
+fn test_edit_bext_in_existing_file() {
+    use bwavfile::{WaveEditor, FourCC};
+    use std::io::Cursor;
+
+    let path = "tests/media/pt_24bit.wav";
+    let mut cursor = Cursor::new(std::fs::read(path).unwrap());
+
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    let mut bext = r.broadcast_extension().unwrap().unwrap();
+    let data_start = r.chunks().iter().find(|c| c.signature == FourCC::make(b"data")).unwrap().start;
+    let mut audio = vec![0i32; 2400];
+    r.audio_frame_reader().unwrap().read_integer_frames(&mut audio).unwrap();
+
+    bext.description = String::from("Scene 12A Take 3");
+    bext.coding_history = String::from("A=PCM,F=48000,W=24,M=mono,T=Pro Tools\r\n");
+
+    let mut editor = WaveEditor::new(&mut cursor).unwrap();
+    editor.write_broadcast_metadata(&bext).unwrap();
+    editor.write_ixml(b"<BWFXML><SCENE>12A</SCENE><TAKE>3</TAKE></BWFXML>").unwrap();
+
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    let read = r.broadcast_extension().unwrap().unwrap();
+    assert_eq!(read.description, bext.description);
+    assert_eq!(read.coding_history, bext.coding_history);
+    assert_eq!(read.originator, bext.originator);
+    assert_eq!(read.time_reference, bext.time_reference);
+
+    let mut ixml = vec![];
+    r.read_ixml(&mut ixml).unwrap();
+    assert_eq!(ixml, b"<BWFXML><SCENE>12A</SCENE><TAKE>3</TAKE></BWFXML>");
+
+    assert_eq!(r.chunks().iter().find(|c| c.signature == FourCC::make(b"data")).unwrap().start, data_start);
+    let mut edited_audio = vec![0i32; 2400];
+    r.audio_frame_reader().unwrap().read_integer_frames(&mut edited_audio).unwrap();
+    assert_eq!(edited_audio, audio);
+}
+
+#[test]
+fn test_bext_typed_fields_round_trip() {
+    use bwavfile::{Bext, Umid, UmidSourcePack, CodingHistoryEntry,
+        OriginationDate, OriginationTime};
+
+    let mut bext = Bext {
+        description: String::from("Typed fields"),
+        originator: String::from("bwavfile"),
+        originator_reference: String::from(""),
+        origination_date: String::from(""),
+        origination_time: String::from(""),
+        time_reference: 0,
+        version: 0,
+        umid: None,
+        loudness_value: None,
+        loudness_range: None,
+        max_true_peak_level: None,
+        max_momentary_loudness: None,
+        max_short_term_loudness: None,
+        coding_history: String::from(""),
+    };
+
+    let mut umid = Umid::new(0x08, 0x20, 0, [0xA5; 16]);
+    umid.source_pack = Some(UmidSourcePack { country: *b"FRA\0", ..UmidSourcePack::default() });
+    let history = vec![
+        CodingHistoryEntry::parse("A=ANALOGUE,M=mono,T=Nagra IV-S"),
+        CodingHistoryEntry {
+            algorithm: Some(String::from("PCM")), sampling_frequency: Some(48000),
+            word_length: Some(24), mode: Some(String::from("mono")),
+            ..CodingHistoryEntry::default()
+        }
+    ];
+
+    bext.set_umid(&umid);
+    bext.set_coding_history(&history);
+    bext.set_origination_date(OriginationDate::new(2020, 12, 31).unwrap());
+    bext.set_origination_time(OriginationTime::new(23, 59, 1).unwrap());
+    assert_eq!(bext.version, 1);
+
+    let mut r = write_metadata(WaveFmt::new_pcm_mono(48000, 24),
+        |w| w.write_broadcast_metadata(&bext).unwrap());
+    let read = r.broadcast_extension().unwrap().unwrap();
+    assert_eq!(read.parsed_umid(), Some(umid));
+    assert_eq!(read.parsed_coding_history(), history);
+    assert_eq!(read.parsed_origination_date(), OriginationDate::new(2020, 12, 31));
+    assert_eq!(read.parsed_origination_time().map(|t| t.to_string()), Some(String::from("23:59:01")));
+}
+
+#[cfg(feature = "ixml")]
+#[test]
+fn test_start_timecode_stamped_in_ixml() {
+    use bwavfile::{IXml, IXmlBext, Timecode, FrameRate, Pull};
+
+    let mut ixml = IXml::default();
+    ixml.scene = Some(String::from("4B"));
+    ixml.bext = Some(IXmlBext::default());
+
+    let tc = Timecode::parse("10:00:00:00", FrameRate::Fps23_976).unwrap();
+    let mut r = write_metadata(WaveFmt::new_pcm_mono(48000, 24), |w| {
+        w.set_start_timecode(tc, Pull::Up);
+        w.write_ixml_metadata(&ixml).unwrap();
+    });
+    assert_eq!(r.timecode_rate().unwrap(), Some(FrameRate::Fps23_976));
+    let read = r.ixml().unwrap().unwrap();
+    let speed = read.speed.unwrap();
+    assert_eq!(speed.timecode_rate.as_deref(), Some("24000/1001"));
+    assert_eq!(speed.timecode_flag.as_deref(), Some("NDF"));
+    assert_eq!(speed.timestamp_sample_rate, Some(48000));
+    let time_reference = speed.timestamp_samples_since_midnight.unwrap();
+    assert_eq!(time_reference, tc.to_samples_pulled(48000, Pull::Up));
+    assert_eq!(read.bext.unwrap().time_reference, Some(time_reference));
+    assert_eq!(Timecode::from_samples_pulled(time_reference, 48000, FrameRate::Fps23_976, Pull::Up), tc);
+    assert_eq!(read.scene.as_deref(), Some("4B"));
+
+    let opaque = ixml.to_bytes();
+    let mut r = write_metadata(WaveFmt::new_pcm_mono(48000, 24), |w| {
+        w.set_start_timecode(tc, Pull::Up);
+        w.write_ixml(&opaque).unwrap();
+    });
+    let mut buffer = vec![];
+    r.read_ixml(&mut buffer).unwrap();
+    assert_eq!(buffer, opaque);
+}
+
+#[test]
+fn test_bext_builder_round_trip() {
+    use bwavfile::{Bext, OriginationDate, OriginationTime, FourCC};
+
+    let bext = Bext::builder()
+        .description("Built")
+        .originator("bwavfile tests")
+        .origination_date(OriginationDate::new(2022, 3, 4).unwrap())
+        .origination_time(OriginationTime::new(5, 6, 7).unwrap())
+        .coding_history("A=PCM,F=48000,W=24,M=mono,T=test\r\n")
+        .loudness_value(-23.5)
+        .build().unwrap();
+
+    let mut invalid = bext.clone();
+    invalid.originator_reference = String::from("Réf");
+
+    let mut r = write_metadata(WaveFmt::new_pcm_mono(48000, 24), |w| {
+        w.write_broadcast_metadata(&bext).unwrap();
+        assert!(matches!(w.write_broadcast_metadata(&invalid),
+            Err(Error::FieldNotAscii { chunk, field: "originator_reference" }) if chunk == FourCC::make(b"bext")));
+    });
+    let read = r.broadcast_extension().unwrap().unwrap();
+    assert_eq!(read.version, 2);
+    assert_eq!(read.description, "Built");
+    assert_eq!(read.origination_date, "2022-03-04");
+    assert_eq!(read.origination_time, "05:06:07");
+    assert_eq!(read.loudness_value, Some(-23.5));
+    assert_eq!(read.coding_history, bext.coding_history);
+}
+
+#[test]
+fn test_bext_multi_line_description() {
+    use bwavfile::Bext;
+
+    let bext = Bext::builder()
+        .description("sSCENE=4B\r\nsTAKE=2\r\nsNOTE=Wide\tboom only\r\n")
+        .build().unwrap();
+
+    let mut r = write_metadata(WaveFmt::new_pcm_mono(48000, 24),
+        |w| w.write_broadcast_metadata(&bext).unwrap());
+    assert_eq!(r.broadcast_extension().unwrap().unwrap().description, bext.description);
+}
+
+#[test]
+fn test_cart_round_trip() {
+    use bwavfile::{Cart, CartTimer, FourCC};
+
+    let mut cart = Cart::default();
+    cart.title = String::from("Station ID");
+    cart.artist = String::from("Imaging Dept");
+    cart.cut_id = String::from("ID-0007");
+    cart.client_id = String::from("KXYZ");
+    cart.category = String::from("IMG");
+    cart.start_date = String::from("2021-01-01");
+    cart.start_time = String::from("00:00:00");
+    cart.end_date = String::from("2021-12-31");
+    cart.end_time = String::from("23:59:59");
+    cart.producer_app_id = String::from("bwavfile");
+    cart.producer_app_version = String::from("1.0");
+    cart.level_reference = 32768;
+    cart.post_timers[0] = Some(CartTimer { usage: FourCC::make(b"INT1"), value: 12000 });
+    cart.post_timers[1] = Some(CartTimer { usage: FourCC::make(b"SEG1"), value: 36000 });
+    cart.url = String::from("http://example.com/id-0007");
+    cart.tag_text = String::from("<cart>imaging</cart>\r\n");
+
+    let mut invalid = cart.clone();
+    invalid.start_date = String::from("2021-01-01T00:00");
+
+    let mut r = write_metadata(WaveFmt::new_pcm_mono(48000, 16), |w| {
+        w.write_cart(&cart).unwrap();
+        assert!(matches!(w.write_cart(&invalid),
+            Err(Error::FieldTooLong { chunk, field: "start_date", length: 16, limit: 10 }) if chunk == FourCC::make(b"cart")));
+    });
+    let read = r.cart().unwrap().unwrap();
+    assert_eq!(read, cart);
+    assert_eq!(read.timer(FourCC::make(b"SEG1")), Some(36000));
+    assert_eq!(read.timer(FourCC::make(b"EOD ")), None);
+}